@@ -1,11 +1,13 @@
 use bytes::Bytes;
 /// 参考Sled Benchmark
 /// https://github.com/spacejam/sled/blob/main/benchmarks/criterion/benches/sled.rs
-use criterion::{criterion_group, criterion_main, Criterion};
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use std::collections::Bound;
 use std::sync::atomic::AtomicU32;
 use std::sync::atomic::Ordering::Relaxed;
+use std::sync::Arc;
 
-use kip_db::kernel::lsm::storage::LsmStore;
+use kip_db::kernel::lsm::storage::{Config, LsmStore, ReadOptions};
 use kip_db::kernel::sled_storage::SledStore;
 use kip_db::kernel::Storage;
 
@@ -181,6 +183,100 @@ fn empty_opens<T: Storage>(c: &mut Criterion) {
     let _ = std::fs::remove_dir_all("empty_opens");
 }
 
+/// 全量扫描落盘后的数据，对比开启与关闭预读时的表现
+fn lsm_scan(c: &mut Criterion) {
+    const SIZE: u32 = 100000;
+
+    let rt = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+    let _ = std::fs::remove_dir_all("lsm_scan");
+    // Block缓存远小于数据量，使扫描需要从SSTable中读取
+    let config = Config::new("lsm_scan").block_cache_size(1024 * 1024);
+
+    rt.block_on(async {
+        let db = LsmStore::open_with_config(config.clone()).await.unwrap();
+        for i in 0..SIZE {
+            db.set(&i.to_be_bytes(), Bytes::from(vec![0; 128]))
+                .await
+                .unwrap();
+        }
+        db.flush().await.unwrap();
+    });
+    // 重新打开使数据仅存在于SSTable中
+    let db = rt.block_on(LsmStore::open_with_config(config)).unwrap();
+
+    for (name, readahead_size) in [("without readahead", 0), ("with readahead", 256 * 1024)] {
+        c.bench_function(&format!("Store: LsmStore, scan {}", name), |b| {
+            b.to_async(&rt).iter(|| async {
+                let options = ReadOptions::new()
+                    .fill_cache(false)
+                    .readahead_size(readahead_size);
+                let tx = db.new_transaction().await;
+                let vec_kv = tx
+                    .range_scan_with_options(Bound::Unbounded, Bound::Unbounded, options)
+                    .unwrap();
+
+                assert_eq!(vec_kv.len(), SIZE as usize);
+            })
+        });
+    }
+    drop(db);
+    let _ = std::fs::remove_dir_all("lsm_scan");
+}
+
+/// 多个任务并发读取同一个SSTable，观察读取吞吐随并发数的变化
+fn lsm_concurrent_gets(c: &mut Criterion) {
+    const SIZE: u32 = 10000;
+    const GETS_PER_TASK: u32 = 1000;
+
+    let rt = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+    let _ = std::fs::remove_dir_all("lsm_concurrent_gets");
+    // Block缓存远小于数据量，使读取落在SSTable文件上
+    let config = Config::new("lsm_concurrent_gets").block_cache_size(64 * 1024);
+
+    rt.block_on(async {
+        let db = LsmStore::open_with_config(config.clone()).await.unwrap();
+        for i in 0..SIZE {
+            db.set(&i.to_be_bytes(), Bytes::from(vec![0; 128]))
+                .await
+                .unwrap();
+        }
+        db.flush().await.unwrap();
+    });
+    let db = Arc::new(rt.block_on(LsmStore::open_with_config(config)).unwrap());
+
+    let mut group = c.benchmark_group("Store: LsmStore, concurrent gets");
+    for tasks in [1_u32, 2, 4, 8] {
+        let _ = group.throughput(Throughput::Elements((tasks * GETS_PER_TASK) as u64));
+        let _ = group.bench_function(format!("{} tasks", tasks), |b| {
+            b.to_async(&rt).iter(|| async {
+                let handles = (0..tasks)
+                    .map(|_| {
+                        let db = Arc::clone(&db);
+                        tokio::spawn(async move {
+                            for _ in 0..GETS_PER_TASK {
+                                db.get(&random(SIZE).to_be_bytes()).await.unwrap();
+                            }
+                        })
+                    })
+                    .collect::<Vec<_>>();
+
+                for handle in handles {
+                    handle.await.unwrap();
+                }
+            })
+        });
+    }
+    group.finish();
+    drop(db);
+    let _ = std::fs::remove_dir_all("lsm_concurrent_gets");
+}
+
 fn kv_bulk_load(c: &mut Criterion) {
     bulk_load::<LsmStore>(c);
     bulk_load::<SledStore>(c);
@@ -206,6 +302,8 @@ criterion_group!(
     kv_bulk_load,
     kv_monotonic_crud,
     kv_random_crud,
-    kv_empty_opens
+    kv_empty_opens,
+    lsm_scan,
+    lsm_concurrent_gets
 );
 criterion_main!(benches);
@@ -1,6 +1,7 @@
 use clap::Parser;
 use itertools::Itertools;
 use kip_db::cmd::Command;
+use kip_db::kernel::lsm::storage::{Config, LsmStore};
 use kip_db::kernel::CommandData;
 use kip_db::net::{client::Client, Result};
 use kip_db::DEFAULT_PORT;
@@ -40,6 +41,28 @@ async fn main() -> Result<()> {
     tracing_subscriber::fmt::try_init().unwrap();
     let cli: Cli = Cli::parse();
 
+    // 修复与校验需在LsmStore离线时于本地进行，因此无需连接服务端
+    match &cli.command {
+        Command::Repair { path } => {
+            info!("{:?}", LsmStore::repair(path.clone()).await?);
+            return Ok(());
+        }
+        Command::Verify { path } => {
+            // 以只读方式打开，避免校验时重放WAL、清理文件或触发压缩而修改目录
+            let report = LsmStore::open_read_only(Config::new(path.clone()))
+                .await?
+                .verify()
+                .await?;
+            if !report.is_ok() {
+                error!("{report:?}");
+                std::process::exit(1);
+            }
+            info!("{report:?}");
+            return Ok(());
+        }
+        _ => (),
+    }
+
     let addr = format!("{}:{}", cli.host, cli.port);
 
     let mut client = Client::connect(&addr).await?;
@@ -73,6 +96,7 @@ async fn main() -> Result<()> {
         }
         Command::SizeOfDisk => client.size_of_disk().await?.to_string(),
         Command::Len => client.len().await?.to_string(),
+        Command::CacheStats => format!("{:?}", client.cache_stats().await?),
         Command::Flush => {
             client.flush().await?;
             DONE.to_string()
@@ -28,6 +28,17 @@ pub enum Command {
     },
     SizeOfDisk,
     Len,
+    #[clap(about = "cli.exe cache-stats, show the hit and eviction counters of the caches")]
+    CacheStats,
+
+    #[clap(about = "cli.exe repair [path], rebuild the version of an offline LsmStore")]
+    Repair {
+        path: String,
+    },
+    #[clap(about = "cli.exe verify [path], check the integrity of an offline LsmStore")]
+    Verify {
+        path: String,
+    },
 }
 
 impl Command {
@@ -65,4 +76,14 @@ impl Command {
     pub fn batch_get(keys: Vec<String>) -> Command {
         Command::BatchGet { keys }
     }
+
+    #[inline]
+    pub fn repair(path: String) -> Command {
+        Command::Repair { path }
+    }
+
+    #[inline]
+    pub fn verify(path: String) -> Command {
+        Command::Verify { path }
+    }
 }
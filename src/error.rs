@@ -21,12 +21,20 @@ pub enum KernelError {
     KeyNotFound,
     #[fail(display = "Data is empty")]
     DataEmpty,
-    #[fail(display = "Max Level is 7")]
+    #[fail(display = "Level is over the number of levels")]
     LevelOver,
     #[fail(display = "Not the correct type of Cmd")]
     NotMatchCmd,
     #[fail(display = "CRC code does not match")]
     CrcMisMatch,
+    #[fail(display = "SSTable: {} is corrupted", _0)]
+    TableCorrupted(i64),
+    #[fail(display = "SSTable format version: {} is not supported", _0)]
+    UnsupportedTableFormat(u32),
+    #[fail(display = "Keys must be added in strictly ascending order")]
+    KeyOutOfOrder,
+    #[fail(display = "External SSTables overlap with each other")]
+    ExternalTableOverlap,
     #[fail(display = "{}", _0)]
     SledErr(#[cause] sled::Error),
     #[fail(display = "Cache size overflow")]
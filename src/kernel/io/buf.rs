@@ -1,4 +1,4 @@
-use crate::kernel::io::{FileExtension, IoReader, IoType, IoWriter};
+use crate::kernel::io::{read_exact_at, FileExtension, IoReader, IoType, IoWriter};
 use crate::kernel::Result;
 use std::fs::{File, OpenOptions};
 use std::io;
@@ -84,6 +84,11 @@ impl IoReader for BufIoReader {
     fn get_type(&self) -> IoType {
         IoType::Buf
     }
+
+    /// 绕过缓冲区直接读取文件，因此不会使缓冲区中的数据失效
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> Result<()> {
+        Ok(read_exact_at(self.reader.reader.get_ref(), buf, offset)?)
+    }
 }
 
 impl Write for BufIoWriter {
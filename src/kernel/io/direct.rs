@@ -1,4 +1,4 @@
-use crate::kernel::io::{FileExtension, IoReader, IoType, IoWriter};
+use crate::kernel::io::{read_exact_at, FileExtension, IoReader, IoType, IoWriter};
 use crate::kernel::Result;
 use std::fs::{File, OpenOptions};
 use std::io::{Read, Seek, SeekFrom, Write};
@@ -77,6 +77,10 @@ impl IoReader for DirectIoReader {
     fn get_type(&self) -> IoType {
         IoType::Direct
     }
+
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> Result<()> {
+        Ok(read_exact_at(&self.fs, buf, offset)?)
+    }
 }
 
 impl Write for DirectIoWriter {
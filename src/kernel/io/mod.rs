@@ -4,8 +4,8 @@ pub(crate) mod direct;
 use crate::kernel::io::buf::{BufIoReader, BufIoWriter};
 use crate::kernel::io::direct::{DirectIoReader, DirectIoWriter};
 use crate::kernel::Result;
-use std::fs;
-use std::io::{Read, Seek, Write};
+use std::fs::{self, File};
+use std::io::{self, Read, Seek, Write};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
@@ -93,7 +93,7 @@ impl IoFactory {
     #[inline]
     pub fn exists(&self, gen: i64) -> Result<bool> {
         let path = self.extension.path_with_gen(&self.dir_path, gen);
-        Ok(fs::try_exists(path)?)
+        Ok(fs::exists(path)?)
     }
 }
 
@@ -109,6 +109,49 @@ pub trait IoReader: Send + Sync + 'static + Read + Seek {
     }
 
     fn get_type(&self) -> IoType;
+
+    /// 从offset处读取数据直至填满buf
+    ///
+    /// 基于pread实现，无需`&mut self`，因此同一读取器可以被多个线程无锁地并发读取
+    ///
+    /// Tips: Unix下不影响读取器当前的位置；Windows下基于seek_read实现，会移动文件的游标，
+    /// 因此不应与依赖当前位置的Read/Seek读取混用
+    ///
+    /// 默认实现每次通过路径重新打开文件进行读取，持有文件句柄的实现应覆盖此方法
+    #[inline]
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> Result<()> {
+        let file = File::open(self.get_path())?;
+        Ok(read_exact_at(&file, buf, offset)?)
+    }
+}
+
+/// 基于pread(Windows下为seek_read)的定位读取
+///
+/// Tips: Windows下读取后文件的游标会被移动至读取结束的位置
+pub(crate) fn read_exact_at(file: &File, mut buf: &mut [u8], mut offset: u64) -> io::Result<()> {
+    #[cfg(unix)]
+    use std::os::unix::fs::FileExt;
+    #[cfg(windows)]
+    use std::os::windows::fs::FileExt;
+
+    while !buf.is_empty() {
+        #[cfg(unix)]
+        let result = file.read_at(buf, offset);
+        #[cfg(windows)]
+        let result = file.seek_read(buf, offset);
+
+        match result {
+            Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+            Ok(n) => {
+                buf = &mut buf[n..];
+                offset += n as u64;
+            }
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => (),
+            Err(err) => return Err(err),
+        }
+    }
+
+    Ok(())
 }
 
 pub trait IoWriter: Send + Sync + 'static + Write {
@@ -0,0 +1,485 @@
+use crate::kernel::io::FileExtension;
+use crate::kernel::lsm::checkpoint::create_empty_wal;
+use crate::kernel::lsm::mem_table::DEFAULT_WAL_PATH;
+use crate::kernel::lsm::storage::LsmStore;
+use crate::kernel::lsm::table::scope::Scope;
+use crate::kernel::lsm::version::edit::VersionEdit;
+use crate::kernel::lsm::version::manifest::Manifest;
+use crate::kernel::lsm::version::{DEFAULT_SS_TABLE_PATH, DEFAULT_VERSION_PATH};
+use crate::kernel::{Result, Storage};
+use crate::KernelError;
+use chrono::Local;
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use tracing::{error, info};
+
+const DEFAULT_SHARED_PATH: &str = "shared";
+
+const DEFAULT_META_PATH: &str = "meta";
+
+const TEMP_FILE_SUFFIX: &str = "tmp";
+
+/// 单个备份的概要信息
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BackupInfo {
+    pub backup_id: u64,
+    /// 创建时间的毫秒时间戳
+    pub timestamp: i64,
+    /// 该备份所引用的文件大小总和(包含与其他备份共享的文件)
+    pub size: u64,
+    pub num_files: usize,
+}
+
+/// 备份的Manifest，记录恢复所需的Version快照与所引用文件的校验码
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct BackupMeta {
+    backup_id: u64,
+    timestamp: i64,
+    vec_edit: Vec<VersionEdit>,
+    files: Vec<BackupFile>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+struct BackupFile {
+    /// 相对于数据目录的路径，如`ss_table/1.sst`
+    path: String,
+    size: u64,
+    crc: u32,
+}
+
+/// 增量备份引擎
+///
+/// SSTable不可变且以Gen唯一标识，因此所有备份共享`shared`目录中的文件，
+/// 创建备份时仅复制此前备份中不存在的文件
+/// - `shared/`: 备份所引用的SSTable与WAL
+/// - `meta/{backup_id}`: 每个备份的Manifest
+///
+/// 文件均先写入临时文件再rename，中途宕机只会残留临时文件，在打开时清理
+pub struct BackupEngine {
+    backup_dir: PathBuf,
+}
+
+impl BackupEngine {
+    #[inline]
+    pub fn open(backup_dir: impl Into<PathBuf>) -> Result<Self> {
+        let backup_dir = backup_dir.into();
+        for dir_name in [DEFAULT_SS_TABLE_PATH, DEFAULT_WAL_PATH] {
+            fs::create_dir_all(backup_dir.join(DEFAULT_SHARED_PATH).join(dir_name))?;
+        }
+        fs::create_dir_all(backup_dir.join(DEFAULT_META_PATH))?;
+
+        let engine = BackupEngine { backup_dir };
+        engine.clean_temp_files()?;
+        Ok(engine)
+    }
+
+    /// 对LsmStore创建新的备份
+    ///
+    /// 会先进行flush，再在持有Version的期间复制其所引用且尚未备份的文件
+    #[inline]
+    pub async fn create_backup(&self, store: &LsmStore) -> Result<BackupInfo> {
+        store.flush().await?;
+        let version = store.current_version().await;
+        let data_dir = store.config().path();
+
+        let backed_up: HashMap<String, BackupFile> = self
+            .load_metas()?
+            .into_iter()
+            .flat_map(|meta| meta.files)
+            .map(|file| (file.path.clone(), file))
+            .collect();
+        let mut files = Vec::new();
+        let mut copied = 0;
+
+        for (dir_name, extension) in [
+            (DEFAULT_SS_TABLE_PATH, FileExtension::SSTable),
+            (DEFAULT_WAL_PATH, FileExtension::Log),
+        ] {
+            for gen in version.gens() {
+                let src_path = extension.path_with_gen(&data_dir.join(dir_name), gen);
+                if !src_path.exists() {
+                    continue;
+                }
+                let path = format!("{dir_name}/{gen}.{}", extension.extension_str());
+                let shared_path = self.shared_path(&path);
+
+                match backed_up.get(&path) {
+                    Some(file) if shared_path.exists() => files.push(file.clone()),
+                    _ => {
+                        let (size, crc) = copy_with_crc(&src_path, &shared_path)?;
+                        files.push(BackupFile { path, size, crc });
+                        copied += 1;
+                    }
+                }
+            }
+        }
+
+        let meta = BackupMeta {
+            backup_id: self.backup_ids()?.last().map_or(1, |id| id + 1),
+            timestamp: Local::now().timestamp_millis(),
+            vec_edit: version.to_vec_edit(),
+            files,
+        };
+        write_atomic(&self.meta_path(meta.backup_id), &bincode::serialize(&meta)?)?;
+        info!(
+            "[BackupEngine][create_backup][Backup: {}]: {} files, {} copied",
+            meta.backup_id,
+            meta.files.len(),
+            copied
+        );
+
+        Ok(BackupInfo::from(&meta))
+    }
+
+    /// 以创建顺序列出所有备份
+    #[inline]
+    pub fn list_backups(&self) -> Result<Vec<BackupInfo>> {
+        Ok(self
+            .load_metas()?
+            .iter()
+            .map(BackupInfo::from)
+            .collect_vec())
+    }
+
+    /// 删除备份，并清理不再被任何备份引用的共享文件
+    #[inline]
+    pub fn delete_backup(&self, backup_id: u64) -> Result<()> {
+        let meta_path = self.meta_path(backup_id);
+        if !meta_path.exists() {
+            return Err(KernelError::FileNotFound);
+        }
+        fs::remove_file(meta_path)?;
+        info!("[BackupEngine][delete_backup][Backup: {}]", backup_id);
+
+        self.clean_unreferenced_files()
+    }
+
+    /// 仅保留最新的num_to_keep个备份
+    #[inline]
+    pub fn purge_old_backups(&self, num_to_keep: usize) -> Result<()> {
+        let backup_ids = self.backup_ids()?;
+
+        for backup_id in &backup_ids[..backup_ids.len().saturating_sub(num_to_keep)] {
+            fs::remove_file(self.meta_path(*backup_id))?;
+            info!("[BackupEngine][purge_old_backups][Backup: {}]", backup_id);
+        }
+
+        self.clean_unreferenced_files()
+    }
+
+    /// 校验备份所引用的文件是否完整
+    #[inline]
+    pub fn verify_backup(&self, backup_id: u64) -> Result<()> {
+        for file in self.load_meta(backup_id)?.files {
+            let (size, crc) = file_crc(&self.shared_path(&file.path))?;
+
+            if size != file.size || crc != file.crc {
+                return Err(KernelError::CrcMisMatch);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 将备份恢复至target_dir，target_dir需不存在
+    ///
+    /// 复制时会校验每个文件的校验码，恢复后可直接通过`LsmStore::open`打开
+    #[inline]
+    pub fn restore(&self, backup_id: u64, target_dir: impl Into<PathBuf>) -> Result<()> {
+        let target_dir = target_dir.into();
+        if target_dir.exists() {
+            return Err(io::Error::from(io::ErrorKind::AlreadyExists).into());
+        }
+        let meta = self.load_meta(backup_id)?;
+
+        // 恢复失败时清理已复制的文件，避免残留不完整的数据目录
+        if let Err(err) = self.restore_files(&meta, &target_dir) {
+            if let Err(clean_err) = fs::remove_dir_all(&target_dir) {
+                error!(
+                    "[BackupEngine][restore][Backup: {}]: Clean Error!: {:?}",
+                    backup_id, clean_err
+                );
+            }
+            return Err(err);
+        }
+        info!(
+            "[BackupEngine][restore][Backup: {}]: restored to {:?}",
+            backup_id, target_dir
+        );
+
+        Ok(())
+    }
+
+    fn restore_files(&self, meta: &BackupMeta, target_dir: &Path) -> Result<()> {
+        for dir_name in [DEFAULT_SS_TABLE_PATH, DEFAULT_WAL_PATH] {
+            fs::create_dir_all(target_dir.join(dir_name))?;
+        }
+        for file in &meta.files {
+            let (size, crc) =
+                copy_with_crc(&self.shared_path(&file.path), &target_dir.join(&file.path))?;
+
+            if size != file.size || crc != file.crc {
+                return Err(KernelError::CrcMisMatch);
+            }
+        }
+        let gens = meta.vec_edit.iter().flat_map(|edit| match edit {
+            VersionEdit::NewFile((vec_scope, _), _, _) => {
+                vec_scope.iter().map(Scope::get_gen).collect_vec()
+            }
+            _ => Vec::new(),
+        });
+        create_empty_wal(target_dir, gens)?;
+        let _ = Manifest::create(&target_dir.join(DEFAULT_VERSION_PATH), &meta.vec_edit)?;
+
+        Ok(())
+    }
+
+    fn shared_path(&self, path: &str) -> PathBuf {
+        self.backup_dir.join(DEFAULT_SHARED_PATH).join(path)
+    }
+
+    fn meta_path(&self, backup_id: u64) -> PathBuf {
+        self.backup_dir
+            .join(DEFAULT_META_PATH)
+            .join(backup_id.to_string())
+    }
+
+    fn backup_ids(&self) -> Result<Vec<u64>> {
+        Ok(fs::read_dir(self.backup_dir.join(DEFAULT_META_PATH))?
+            .flat_map(|entry| entry.map(|entry| entry.file_name()))
+            .filter_map(|name| name.to_str().and_then(|name| name.parse::<u64>().ok()))
+            .sorted()
+            .collect_vec())
+    }
+
+    fn load_meta(&self, backup_id: u64) -> Result<BackupMeta> {
+        let meta_path = self.meta_path(backup_id);
+        if !meta_path.exists() {
+            return Err(KernelError::FileNotFound);
+        }
+
+        Ok(bincode::deserialize(&fs::read(meta_path)?)?)
+    }
+
+    fn load_metas(&self) -> Result<Vec<BackupMeta>> {
+        self.backup_ids()?
+            .into_iter()
+            .map(|backup_id| self.load_meta(backup_id))
+            .try_collect()
+    }
+
+    fn clean_unreferenced_files(&self) -> Result<()> {
+        let referenced: HashSet<String> = self
+            .load_metas()?
+            .into_iter()
+            .flat_map(|meta| meta.files)
+            .map(|file| file.path)
+            .collect();
+
+        for dir_name in [DEFAULT_SS_TABLE_PATH, DEFAULT_WAL_PATH] {
+            for entry in fs::read_dir(self.backup_dir.join(DEFAULT_SHARED_PATH).join(dir_name))? {
+                let entry = entry?;
+                let path = format!("{dir_name}/{}", entry.file_name().to_string_lossy());
+
+                if !referenced.contains(&path) {
+                    fs::remove_file(entry.path())?;
+                    info!("[BackupEngine][clean][File: {}]: Removed", path);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn clean_temp_files(&self) -> Result<()> {
+        for dir_path in [
+            self.backup_dir
+                .join(DEFAULT_SHARED_PATH)
+                .join(DEFAULT_SS_TABLE_PATH),
+            self.backup_dir
+                .join(DEFAULT_SHARED_PATH)
+                .join(DEFAULT_WAL_PATH),
+            self.backup_dir.join(DEFAULT_META_PATH),
+        ] {
+            for entry in fs::read_dir(dir_path)? {
+                let path = entry?.path();
+
+                if path.extension().is_some_and(|ext| ext == TEMP_FILE_SUFFIX) {
+                    fs::remove_file(path)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl From<&BackupMeta> for BackupInfo {
+    #[inline]
+    fn from(meta: &BackupMeta) -> Self {
+        BackupInfo {
+            backup_id: meta.backup_id,
+            timestamp: meta.timestamp,
+            size: meta.files.iter().map(|file| file.size).sum(),
+            num_files: meta.files.len(),
+        }
+    }
+}
+
+fn temp_path(path: &Path) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(format!(".{TEMP_FILE_SUFFIX}"));
+    path.with_file_name(file_name)
+}
+
+fn write_atomic(path: &Path, bytes: &[u8]) -> Result<()> {
+    let temp_path = temp_path(path);
+    let mut file = File::create(&temp_path)?;
+    file.write_all(bytes)?;
+    file.sync_all()?;
+    fs::rename(temp_path, path)?;
+
+    Ok(())
+}
+
+/// 复制文件并同时计算其大小与校验码
+fn copy_with_crc(from: &Path, to: &Path) -> Result<(u64, u32)> {
+    let temp_path = temp_path(to);
+    let mut writer = File::create(&temp_path)?;
+    let size_with_crc = read_with_crc(from, Some(&mut writer))?;
+    writer.sync_all()?;
+    fs::rename(temp_path, to)?;
+
+    Ok(size_with_crc)
+}
+
+fn file_crc(path: &Path) -> Result<(u64, u32)> {
+    read_with_crc(path, None)
+}
+
+fn read_with_crc(path: &Path, mut option_writer: Option<&mut File>) -> Result<(u64, u32)> {
+    let mut reader = File::open(path)?;
+    let mut hasher = crc32fast::Hasher::new();
+    let mut buf = vec![0; 64 * 1024];
+    let mut size = 0;
+
+    loop {
+        let len = reader.read(&mut buf)?;
+        if len == 0 {
+            break;
+        }
+        hasher.update(&buf[..len]);
+        if let Some(writer) = option_writer.as_mut() {
+            writer.write_all(&buf[..len])?;
+        }
+        size += len as u64;
+    }
+
+    Ok((size, hasher.finalize()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::kernel::lsm::backup::{BackupEngine, DEFAULT_SHARED_PATH};
+    use crate::kernel::lsm::storage::LsmStore;
+    use crate::kernel::lsm::version::DEFAULT_SS_TABLE_PATH;
+    use crate::kernel::{Result, Storage};
+    use crate::KernelError;
+    use bytes::Bytes;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_backup_engine() -> Result<()> {
+        let temp_dir = TempDir::new().expect("unable to create temporary working directory");
+        let backup_dir = TempDir::new().expect("unable to create temporary working directory");
+        let restore_dir = TempDir::new().expect("unable to create temporary working directory");
+
+        tokio_test::block_on(async move {
+            let kv_store = LsmStore::open(temp_dir.path()).await?;
+            let engine = BackupEngine::open(backup_dir.path())?;
+            let shared_sst_path = backup_dir
+                .path()
+                .join(DEFAULT_SHARED_PATH)
+                .join(DEFAULT_SS_TABLE_PATH);
+
+            for i in 0_u32..1000 {
+                let key = Bytes::from(i.to_be_bytes().to_vec());
+                kv_store.set(&key, key.clone()).await?;
+            }
+            let info_1 = engine.create_backup(&kv_store).await?;
+            assert_eq!(info_1.backup_id, 1);
+            let shared_file = fs::read_dir(&shared_sst_path)?
+                .next()
+                .ok_or(KernelError::FileNotFound)??
+                .path();
+            let modified = fs::metadata(&shared_file)?.modified()?;
+
+            for i in 1000_u32..2000 {
+                let key = Bytes::from(i.to_be_bytes().to_vec());
+                kv_store.set(&key, key.clone()).await?;
+            }
+            let info_2 = engine.create_backup(&kv_store).await?;
+            assert_eq!(info_2.backup_id, 2);
+            assert!(info_2.num_files > info_1.num_files);
+            // 已备份的SSTable不会被重复复制
+            assert_eq!(fs::metadata(&shared_file)?.modified()?, modified);
+            assert_eq!(engine.list_backups()?, vec![info_1, info_2]);
+
+            let restore_path_1 = restore_dir.path().join("1");
+            engine.restore(1, &restore_path_1)?;
+            let restore_store = LsmStore::open(&restore_path_1).await?;
+            for i in 0_u32..2000 {
+                let key = Bytes::from(i.to_be_bytes().to_vec());
+                let expected = (i < 1000).then(|| key.clone());
+                assert_eq!(restore_store.get(&key).await?, expected);
+            }
+            drop(restore_store);
+
+            engine.verify_backup(2)?;
+            let restore_path_2 = restore_dir.path().join("2");
+            engine.restore(2, &restore_path_2)?;
+            let restore_store = LsmStore::open(&restore_path_2).await?;
+            for i in 0_u32..2000 {
+                let key = Bytes::from(i.to_be_bytes().to_vec());
+                assert_eq!(restore_store.get(&key).await?, Some(key));
+            }
+            drop(restore_store);
+
+            // 删除备份1后其独有的文件被清理，共享文件仍被备份2引用
+            engine.delete_backup(1)?;
+            assert!(matches!(
+                engine.delete_backup(1),
+                Err(KernelError::FileNotFound)
+            ));
+            assert_eq!(engine.list_backups()?, vec![info_2]);
+            assert!(shared_file.exists());
+            engine.verify_backup(2)?;
+
+            // 损坏的备份无法通过校验，且恢复失败时不会残留数据目录
+            let mut bytes = fs::read(&shared_file)?;
+            bytes[0] = !bytes[0];
+            fs::write(&shared_file, bytes)?;
+            assert!(matches!(
+                engine.verify_backup(2),
+                Err(KernelError::CrcMisMatch)
+            ));
+            let restore_path_3 = restore_dir.path().join("3");
+            assert!(matches!(
+                engine.restore(2, &restore_path_3),
+                Err(KernelError::CrcMisMatch)
+            ));
+            assert!(!restore_path_3.exists());
+
+            engine.purge_old_backups(0)?;
+            assert!(engine.list_backups()?.is_empty());
+            assert_eq!(fs::read_dir(&shared_sst_path)?.count(), 0);
+
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,67 @@
+use crate::kernel::io::FileExtension;
+use crate::kernel::lsm::mem_table::DEFAULT_WAL_PATH;
+use crate::kernel::lsm::storage::Config;
+use crate::kernel::lsm::version::manifest::Manifest;
+use crate::kernel::lsm::version::{Version, DEFAULT_SS_TABLE_PATH, DEFAULT_VERSION_PATH};
+use crate::kernel::Result;
+use std::fs::{self, File};
+use std::io;
+use std::path::Path;
+use tracing::info;
+
+/// 以Version为准在dest_dir中创建检查点
+///
+/// 调用方需在此期间持有该Version，使Cleaner不会删除其所引用的文件
+/// - SSTable与其同Gen的WAL(Level 0的备份，内存Table的数据来源)通过硬链接复制，无法硬链接时退化为复制
+/// - 以该Version的快照在dest_dir中写入新的Manifest
+pub(crate) fn create_checkpoint(config: &Config, version: &Version, dest_dir: &Path) -> Result<()> {
+    if dest_dir.exists() {
+        return Err(io::Error::from(io::ErrorKind::AlreadyExists).into());
+    }
+    for (dir_name, extension) in [
+        (DEFAULT_SS_TABLE_PATH, FileExtension::SSTable),
+        (DEFAULT_WAL_PATH, FileExtension::Log),
+    ] {
+        let src_path = config.path().join(dir_name);
+        let dest_path = dest_dir.join(dir_name);
+        fs::create_dir_all(&dest_path)?;
+
+        for gen in version.gens() {
+            let src_file = extension.path_with_gen(&src_path, gen);
+
+            if src_file.exists() {
+                link_or_copy(&src_file, &extension.path_with_gen(&dest_path, gen))?;
+            }
+        }
+    }
+    create_empty_wal(dest_dir, version.gens())?;
+    let _ = Manifest::create(&dest_dir.join(DEFAULT_VERSION_PATH), &version.to_vec_edit())?;
+    info!(
+        "[Checkpoint][Version: {}]: created at {:?}",
+        version.version_num, dest_dir
+    );
+
+    Ok(())
+}
+
+/// 在dest_dir中创建空的WAL，作为启动时MemTable所使用的WAL
+///
+/// MemTable启动时会使用最新的WAL，若不创建则会误用某个Table的WAL备份，
+/// 使该Table的数据被重放，且flush时会生成与其Gen相同的Table
+pub(crate) fn create_empty_wal(dest_dir: &Path, gens: impl Iterator<Item = i64>) -> Result<()> {
+    let wal_path = dest_dir.join(DEFAULT_WAL_PATH);
+    let gen = gens.max().map_or(0, |gen| gen + 1);
+    fs::create_dir_all(&wal_path)?;
+    let _ = File::create(FileExtension::Log.path_with_gen(&wal_path, gen))?;
+
+    Ok(())
+}
+
+/// 检查点所在目录可能不在同一文件系统上，此时硬链接会失败，因此退化为复制
+fn link_or_copy(from: &Path, to: &Path) -> Result<()> {
+    if fs::hard_link(from, to).is_err() {
+        let _ = fs::copy(from, to)?;
+    }
+
+    Ok(())
+}
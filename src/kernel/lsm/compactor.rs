@@ -1,17 +1,23 @@
+use crate::kernel::io::FileExtension;
 use crate::kernel::lsm::data_sharding;
+use crate::kernel::lsm::ingest::ExternalTable;
 use crate::kernel::lsm::mem_table::{KeyValue, MemTable};
-use crate::kernel::lsm::storage::{Config, StoreInner};
+use crate::kernel::lsm::storage::{Config, Gen, ReadOptions, StoreInner};
 use crate::kernel::lsm::table::meta::TableMeta;
 use crate::kernel::lsm::table::scope::Scope;
-use crate::kernel::lsm::table::{collect_gen, Table};
+use crate::kernel::lsm::table::ss_table::SSTable;
+use crate::kernel::lsm::table::{collect_gen, Table, TableType};
 use crate::kernel::lsm::version::edit::VersionEdit;
 use crate::kernel::lsm::version::status::VersionStatus;
+use crate::kernel::lsm::version::{Version, DEFAULT_SS_TABLE_PATH};
 use crate::kernel::Result;
 use crate::KernelError;
 use bytes::Bytes;
 use futures::future;
 use itertools::Itertools;
 use std::collections::HashSet;
+use std::fmt::Debug;
+use std::fs;
 use std::sync::Arc;
 use std::time::Instant;
 use tokio::sync::oneshot;
@@ -26,10 +32,49 @@ pub(crate) type DelNode = (Vec<i64>, TableMeta);
 /// Major压缩时的待删除Gen封装(N为此次Major所压缩的Level)，第一个为Level N级，第二个为Level N+1级
 pub(crate) type DelNodeTuple = (DelNode, DelNode);
 
+/// Major压缩时Level 1及以下Level的Table选取策略
+///
+/// Tips: Level 0中的Table之间可能存在重叠，因此总是由旧至新进行选取
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompactionPri {
+    /// 以每个Level的压缩指针进行轮询选取，即从上次压缩的最大Key之后开始选取
+    RoundRobin,
+    /// 选取与下一Level重叠数据量占自身数据量比例最小的Tables，以减小写放大
+    MinOverlappingRatio,
+}
+
+/// 压缩过滤器的处理结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CompactionDecision {
+    /// 保留原有数据
+    Keep,
+    /// 移除该数据
+    Remove,
+    /// 使用新的Value替换原有数据
+    ChangeValue(Bytes),
+}
+
+/// 压缩过滤器
+///
+/// 在Major压缩归并数据时对每个存活的KeyValue进行调用，
+/// 可用于在压缩时按照业务规则(如软删除标记、数据格式迁移等)移除或改写数据而无需额外的全表重写
+///
+/// Tips:
+/// - 删除标记不会经过过滤器
+/// - Remove在非最底层时会转换为删除标记以覆盖更底层中的旧数据
+/// - Minor压缩(即flush)时不会调用过滤器，数据需经过Major压缩才会被处理
+pub trait CompactionFilter: Debug + Send + Sync {
+    /// 对Key与Value进行过滤判断
+    ///
+    /// level为此次压缩的起始Level，数据将被写入至其下一Level中(Level 0时则为Base Level)
+    fn filter(&self, level: usize, key: &[u8], value: &[u8]) -> CompactionDecision;
+}
+
 /// Store与Compactor的交互信息
 #[derive(Debug)]
 pub(crate) enum CompactTask {
     Flush(Option<oneshot::Sender<()>>),
+    Ingest(Vec<ExternalTable>, oneshot::Sender<Result<()>>),
 }
 
 /// 压缩器
@@ -71,22 +116,117 @@ impl Compactor {
         Ok(())
     }
 
+    /// 导入外部SSTable
+    ///
+    /// 与压缩在同一任务中串行进行，因此期间Version不会被压缩所改变
+    /// 1. 导入的Table之间不允许相交
+    /// 2. 与MemTable相交时先进行flush并丢弃immutable，使导入的数据不会被MemTable中的旧数据所遮盖
+    /// 3. 以新的Gen重命名，使导入的Table比flush得到的Table更新
+    /// 4. 放置至不与任何Level相交的最底层，或第一个与之相交的Level的上一层(Level 0相交时即Level 0)
+    ///
+    /// 失败时会删除已复制至`ss_table`目录中的文件
+    pub(crate) async fn ingest(&self, mut vec_table: Vec<ExternalTable>) -> Result<()> {
+        let result = self.ingest_(&mut vec_table).await;
+
+        if result.is_err() {
+            let sst_path = self.config().path().join(DEFAULT_SS_TABLE_PATH);
+
+            for table in vec_table {
+                let path = FileExtension::SSTable.path_with_gen(&sst_path, table.gen());
+                if path.exists() {
+                    fs::remove_file(path)?;
+                }
+            }
+        }
+        result
+    }
+
+    async fn ingest_(&self, vec_table: &mut [ExternalTable]) -> Result<()> {
+        let config = self.config();
+        vec_table.sort_by(|table_a, table_b| table_a.scope.start.cmp(&table_b.scope.start));
+
+        if vec_table
+            .windows(2)
+            .any(|window| window[0].scope.meet(&window[1].scope))
+        {
+            return Err(KernelError::ExternalTableOverlap);
+        }
+        if vec_table
+            .iter()
+            .any(|table| self.mem_table().is_overlapped(&table.scope))
+        {
+            if let Some((gen, values)) = self.mem_table().swap()? {
+                self.minor_compaction(gen, values).await?;
+            }
+            self.mem_table().clear_immut();
+        }
+
+        let sst_path = config.path().join(DEFAULT_SS_TABLE_PATH);
+        let version = self.ver_status().current().await;
+        let last_level = config.num_levels - 1;
+        // 同一Level中先导入的Table会使之后的Table的插入位置后移
+        let mut level_inserted = vec![0; config.num_levels];
+        let mut vec_ver_edit = Vec::with_capacity(vec_table.len());
+
+        for table in vec_table.iter_mut() {
+            let level = match (0..config.num_levels).find(|level| {
+                version.level_slice[*level]
+                    .iter()
+                    .any(|scope| scope.meet(&table.scope))
+            }) {
+                None => last_level,
+                Some(level) => level.saturating_sub(1),
+            };
+            let new_gen = Gen::create();
+            let new_path = FileExtension::SSTable.path_with_gen(&sst_path, new_gen);
+
+            fs::rename(
+                FileExtension::SSTable.path_with_gen(&sst_path, table.gen()),
+                &new_path,
+            )?;
+            table.scope.set_gen(new_gen);
+            SSTable::rewrite_level(&new_path, level)?;
+
+            let index = version.insert_index_by_scope(level, &table.scope) + level_inserted[level];
+            level_inserted[level] += 1;
+            vec_ver_edit.push(VersionEdit::NewFile(
+                (vec![table.scope.clone()], level),
+                index,
+                table.meta,
+            ));
+            info!(
+                "[Compactor][ingest][SSTable: {}]: placed at Level {}",
+                new_gen, level
+            );
+        }
+        drop(version);
+
+        self.major_compaction(LEVEL_0, vec_ver_edit).await
+    }
+
     /// 持久化immutable_table为SSTable
     ///
     /// 请注意：vec_values必须是依照key值有序的
     pub(crate) async fn minor_compaction(&self, gen: i64, values: Vec<KeyValue>) -> Result<()> {
         if !values.is_empty() {
-            let (scope, meta) = self.ver_status().loader().create(
-                gen,
-                values,
-                LEVEL_0,
-                self.config().level_table_type[LEVEL_0],
-            )?;
+            let (scope, meta) = self
+                .ver_status()
+                .loader()
+                .create(
+                    gen,
+                    values,
+                    LEVEL_0,
+                    self.config().level_table_type[LEVEL_0],
+                )
+                .await?;
 
             // `Compactor::data_loading_with_level`中会检测是否达到压缩阈值，因此此处直接调用Major压缩
             self.major_compaction(
                 LEVEL_0,
-                vec![VersionEdit::NewFile((vec![scope], 0), 0, meta)],
+                vec![
+                    VersionEdit::NewFile((vec![scope], 0), 0, meta),
+                    VersionEdit::FlushedLogGen(gen),
+                ],
             )
             .await?;
         }
@@ -97,39 +237,72 @@ impl Compactor {
     /// 目前Major压缩的大体步骤是
     /// 1. 获取当前Version，读取当前Level的指定数量SSTable，命名为vec_ss_table_l
     /// 2. vec_ss_table_l的每个SSTable中的scope属性进行融合，并以此获取下一Level与该scope相交的SSTable，命名为vec_ss_table_l_1
-    /// 3. 获取的vec_ss_table_l_1向上一Level进行类似第2步骤的措施，获取两级之间压缩范围内最恰当的数据
-    /// 4. vec_ss_table_l与vec_ss_table_l_1之间的数据并行取出排序归并去重等处理后，分片成多个Vec<KeyValue>
+    /// 3. 若vec_ss_table_l_1为空则直接进行Trivial Move，仅通过VersionEdit将vec_ss_table_l移动至下一Level，跳过以下步骤
+    /// 4. 获取的vec_ss_table_l_1向上一Level进行类似第2步骤的措施，获取两级之间压缩范围内最恰当的数据
+    /// 5. vec_ss_table_l与vec_ss_table_l_1之间的数据并行取出排序归并去重等处理后，分片成多个Vec<KeyValue>
     /// 6. 并行将每个分片各自生成SSTable
     /// 7. 生成的SSTables插入到vec_ss_table_l的第一个SSTable位置，并将vec_ss_table_l和vec_ss_table_l_1的SSTable删除
-    /// 8. 将变更的SSTable插入至vec_ver_edit以持久化
-    /// Final: 将vec_ver_edit中的数据进行log_and_apply生成新的Version作为最新状态
+    /// 8. 将变更的SSTable插入至vec_ver_edit并进行log_and_apply生成新的Version作为最新状态，再对下一Level进行判断
     ///
     /// 经过压缩测试，Level 1的SSTable总是较多，根据原理推断：
     /// Level0的Key基本是无序的，容易生成大量的SSTable至Level1
-    /// 而Level1-7的Key排布有序，故转移至下一层的SSTable数量较小
+    /// 而Level1-N的Key排布有序，故转移至下一层的SSTable数量较小
     /// 因此大量数据压缩的情况下Level 1的SSTable数量会较多
+    ///
+    /// Tips: 此处的下一Level由`Version::next_level`决定，开启动态目标大小时Level 0将直接压缩至Base Level
     pub(crate) async fn major_compaction(
         &self,
         mut level: usize,
-        mut vec_ver_edit: Vec<VersionEdit>,
+        vec_ver_edit: Vec<VersionEdit>,
     ) -> Result<()> {
-        let next_level = level + 1;
         let config = self.config();
+        let last_level = config.num_levels - 1;
 
-        if level > 6 {
+        if level > last_level {
             return Err(KernelError::LevelOver);
         }
+        // 优先应用传入的VersionEdit，使每个Level的压缩判断都基于最新的Version
+        self.ver_status()
+            .log_and_apply(vec_ver_edit, config.ver_log_snapshot_threshold)
+            .await?;
+
+        while level < last_level {
+            let start = Instant::now();
+            let next_level = {
+                let version = self.ver_status().current().await;
 
-        while level < 7 {
-            if let Some((
+                if let Some(rate_limiter) = &config.rate_limiter {
+                    rate_limiter.tune(version.compaction_debt(config));
+                }
+                version.next_level(config, level)
+            };
+
+            if let Some((moved_gens, vec_ver_edit)) =
+                self.trivial_move_with_level(level, next_level).await?
+            {
+                self.ver_status()
+                    .log_and_apply(vec_ver_edit, config.ver_log_snapshot_threshold)
+                    .await?;
+                for gen in moved_gens {
+                    self.ver_status()
+                        .loader()
+                        .unpin_index_with_level(gen, next_level);
+                }
+                info!(
+                    "[LsmStore][Major Compaction][trivial_move][Level: {}][Time: {:?}]",
+                    level,
+                    start.elapsed()
+                );
+            } else if let Some((
                 index,
                 ((del_gens_l, del_meta_l), (del_gens_ll, del_meta_ll)),
                 vec_sharding,
-            )) = self.data_loading_with_level(level).await?
+                compact_point,
+            )) = self.data_loading_with_level(level, next_level).await?
             {
-                let start = Instant::now();
                 // 并行创建SSTable
-                let ss_table_futures = vec_sharding.into_iter().map(|(gen, sharding)| async move {
+                // 写入时分块向限速器请求令牌，因此并行写入的各分片同样会被平滑限速
+                let ss_table_futures = vec_sharding.into_iter().map(|(gen, sharding)| {
                     self.ver_status().loader().create(
                         gen,
                         sharding,
@@ -143,53 +316,116 @@ impl Compactor {
                     vec_ss_table_and_scope.into_iter().unzip();
                 let fusion_meta = TableMeta::fusion(&new_metas);
 
-                vec_ver_edit.append(&mut vec![
-                    VersionEdit::NewFile((new_scopes, next_level), index, fusion_meta),
-                    VersionEdit::DeleteFile((del_gens_l, level), del_meta_l),
-                    VersionEdit::DeleteFile((del_gens_ll, next_level), del_meta_ll),
-                ]);
+                self.ver_status()
+                    .log_and_apply(
+                        vec![
+                            VersionEdit::NewFile((new_scopes, next_level), index, fusion_meta),
+                            VersionEdit::DeleteFile((del_gens_l, level), del_meta_l),
+                            VersionEdit::DeleteFile((del_gens_ll, next_level), del_meta_ll),
+                            VersionEdit::CompactPoint(level, compact_point),
+                        ],
+                        config.ver_log_snapshot_threshold,
+                    )
+                    .await?;
                 info!(
                     "[LsmStore][Major Compaction][recreate_sst][Level: {}][Time: {:?}]",
                     level,
                     start.elapsed()
                 );
-                level += 1;
             } else {
                 break;
             }
+            level = next_level;
         }
-        self.ver_status()
-            .log_and_apply(vec_ver_edit, config.ver_log_snapshot_threshold)
-            .await?;
         Ok(())
     }
 
+    /// Trivial Move
+    ///
+    /// 当选定的Table与下一级不存在键值范围重叠时，无需重写数据，
+    /// 仅生成同一批Gen的DeleteFile与NewFile使其直接移动至下一级
+    ///
+    /// 返回值中的Vec<i64>为被移动的Table的Gen
+    ///
+    /// Tips:
+    /// - Level 0中的Table之间可能存在重叠，因此仅在选定的Table之间互不重叠时才可移动
+    /// - SkipTable仅可使用于Level 0之中，因此仅在两级都为SortedString时才可移动
+    /// - SSTable创建后不可变(Checkpoint与备份会共享该文件)，因此移动后Footer中记录的Level不会变更，
+    ///   Level仅记录于Version中
+    /// - 删除标记占比达到阈值的Table需要通过重写来清除删除标记，因此不进行移动
+    /// - 设置了压缩过滤器时不进行移动，否则被移动的Table不会经过过滤器，需要被过滤的数据可能一直留存至最底层
+    /// - Index Block是否固定取决于Table所处的Level，移动后需由调用方解除下一级无需固定的Index Block
+    async fn trivial_move_with_level(
+        &self,
+        level: usize,
+        next_level: usize,
+    ) -> Result<Option<(Vec<i64>, Vec<VersionEdit>)>> {
+        let version = self.ver_status().current().await;
+        let config = self.config();
+
+        if !version.is_compaction_needed(config, level)
+            || config.compaction_filter.is_some()
+            || !matches!(config.level_table_type[level], TableType::SortedString)
+            || !matches!(config.level_table_type[next_level], TableType::SortedString)
+        {
+            return Ok(None);
+        }
+
+        if let Some((ss_tables_l, scopes_l)) = version.pick_tables(config, level) {
+            let scope_l = Scope::fusion(&scopes_l)?;
+            let is_disjoint = scopes_l
+                .iter()
+                .sorted_by(|scope_a, scope_b| scope_a.start.cmp(&scope_b.start))
+                .tuple_windows()
+                .all(|(scope_a, scope_b)| scope_a.end < scope_b.start);
+            let is_tombstone_dense = ss_tables_l
+                .iter()
+                .any(|table| Version::is_tombstone_dense(config, *table));
+
+            if is_disjoint
+                && !is_tombstone_dense
+                && version.tables_by_scopes(next_level, &scope_l).0.is_empty()
+            {
+                let (gens, meta) = collect_gen(&ss_tables_l)?;
+                let index = version.insert_index_by_scope(next_level, &scope_l);
+
+                return Ok(Some((
+                    gens.clone(),
+                    vec![
+                        VersionEdit::DeleteFile((gens, level), meta),
+                        VersionEdit::NewFile((scopes_l, next_level), index, meta),
+                        VersionEdit::CompactPoint(level, scope_l.end),
+                    ],
+                )));
+            }
+        }
+
+        Ok(None)
+    }
+
     /// 通过Level进行归并数据加载
+    ///
+    /// 返回值中的Bytes为此次压缩的压缩指针
     async fn data_loading_with_level(
         &self,
         level: usize,
-    ) -> Result<Option<(usize, DelNodeTuple, MergeShardingVec)>> {
+        next_level: usize,
+    ) -> Result<Option<(usize, DelNodeTuple, MergeShardingVec, Bytes)>> {
         let version = self.ver_status().current().await;
         let config = self.config();
-        let major_select_file_size = config.major_select_file_size;
-        let next_level = level + 1;
 
-        // 如果该Level的SSTables数量尚未越出阈值则提取返回空
-        if level > 5 || !version.is_threshold_exceeded_major(config, level) {
+        // 如果该Level的SSTables数量尚未越出阈值且不存在删除标记密集的Table则提取返回空
+        if next_level >= config.num_levels || !version.is_compaction_needed(config, level) {
             return Ok(None);
         }
 
         // 此处vec_ss_table_l指此level的Vec<SSTable>, vec_ss_table_ll则是下一级的Vec<SSTable>
         // 类似罗马数字
-        if let Some((mut ss_tables_l, scopes_l)) =
-            version.first_tables(level, major_select_file_size)
-        {
+        if let Some((mut ss_tables_l, scopes_l)) = version.pick_tables(config, level) {
             let start = Instant::now();
             let scope_l = Scope::fusion(&scopes_l)?;
             // 获取下一级中有重复键值范围的SSTable
             let (ss_tables_ll, scopes_ll) = version.tables_by_scopes(next_level, &scope_l);
-            let index =
-                version.find_index_by_level(ss_tables_ll.first().map(|sst| sst.gen()), next_level);
 
             // 若为Level 0则与获取同级下是否存在有键值范围冲突数据并插入至del_gen_l中
             if level == LEVEL_0 {
@@ -197,25 +433,45 @@ impl Compactor {
                     .append(&mut version.tables_by_meet_scope(level, |scope| scope.meet(&scope_l)))
             }
 
-            // 收集需要清除的SSTable
-            let del_gen_l = collect_gen(&ss_tables_l)?;
-            let del_gen_ll = collect_gen(&ss_tables_ll)?;
-
-            // 此处没有chain vec_ss_table_l是因为在vec_ss_table_ll是由vec_ss_table_l检测冲突而获取到的
-            // 因此使用vec_ss_table_ll向上检测冲突时获取的集合应当含有vec_ss_table_l的元素
+            // vec_ss_table_ll向上检测冲突时获取的集合不一定含有vec_ss_table_l的全部元素(如scope_l中存在空隙时)
+            // 因此需要chain vec_ss_table_l避免其数据未参与合并却被删除
             let ss_tables_l_final = match Scope::fusion(&scopes_ll) {
                 Ok(scope_ll) => version.tables_by_meet_scope(level, |scope| scope.meet(&scope_ll)),
-                Err(_) => ss_tables_l,
+                Err(_) => Vec::new(),
             }
             .into_iter()
+            .chain(ss_tables_l)
             .unique_by(|sst| sst.gen())
             .collect_vec();
 
+            // 收集需要清除的SSTable
+            let del_gen_l = collect_gen(&ss_tables_l_final)?;
+            let del_gen_ll = collect_gen(&ss_tables_ll)?;
+
+            // 当下一级之下不存在与此次压缩范围相交的数据时，删除标记已无需继续向下传递
+            let scope_all =
+                Scope::fusion(&[version.scopes_by_gens(level, &del_gen_l.0), scopes_ll].concat())?;
+            let is_bottom_level = version.is_bottom_with_scope(next_level, &scope_all);
+            let index = version.insert_index_by_scope(next_level, &scope_all);
+
+            // 压缩读取同样受限速器限制
+            self.rate_limit(
+                ss_tables_l_final
+                    .iter()
+                    .chain(ss_tables_ll.iter())
+                    .map(|table| table.size_of_disk())
+                    .sum(),
+            )
+            .await;
+
             // 数据合并并切片
             let vec_merge_sharding = Self::data_merge_and_sharding(
                 ss_tables_l_final,
                 ss_tables_ll,
                 config.sst_file_size,
+                level,
+                is_bottom_level,
+                config.compaction_filter.as_deref(),
             )
             .await?;
 
@@ -224,7 +480,12 @@ impl Compactor {
                 start.elapsed()
             );
 
-            Ok(Some((index, (del_gen_l, del_gen_ll), vec_merge_sharding)))
+            Ok(Some((
+                index,
+                (del_gen_l, del_gen_ll),
+                vec_merge_sharding,
+                scope_l.end,
+            )))
         } else {
             Ok(None)
         }
@@ -235,11 +496,16 @@ impl Compactor {
     /// 2. 基于SSTables_l获取唯一KeySet用于迭代过滤
     /// 3. 并行对Level ll的SSTables_ll通过KeySet进行迭代同时过滤数据
     /// 4. 组合SSTables_l和SSTables_ll的数据合并并进行唯一，排序处理
+    /// 5. 若存在CompactionFilter，则对唯一处理后的数据进行过滤或改写
+    /// 6. 若is_bottom_level为true，则此时的删除标记已不存在需要覆盖的旧数据，因此直接去除
     #[allow(clippy::mutable_key_type)]
     async fn data_merge_and_sharding(
         tables_l: Vec<&dyn Table>,
         tables_ll: Vec<&dyn Table>,
         file_size: usize,
+        level: usize,
+        is_bottom_level: bool,
+        option_filter: Option<&dyn CompactionFilter>,
     ) -> Result<MergeShardingVec> {
         // SSTables的Gen会基于时间有序生成,所有以此作为SSTables的排序依据
         let map_futures_l = tables_l
@@ -271,16 +537,35 @@ impl Compactor {
             .flatten()
             .rev()
             .unique_by(|(key, _)| key.clone())
+            .map(|key_value| Self::compaction_filter(option_filter, level, key_value))
+            .filter(|(_, value)| !is_bottom_level || value.is_some())
             .sorted_unstable_by_key(|(key, _)| key.clone())
             .collect();
         Ok(data_sharding(vec_cmd_data, file_size))
     }
 
+    /// 使用CompactionFilter对数据进行处理，Remove时将数据转换为删除标记
+    fn compaction_filter(
+        option_filter: Option<&dyn CompactionFilter>,
+        level: usize,
+        (key, value): KeyValue,
+    ) -> KeyValue {
+        match (option_filter, value) {
+            (Some(filter), Some(value)) => match filter.filter(level, &key, &value) {
+                CompactionDecision::Keep => (key, Some(value)),
+                CompactionDecision::Remove => (key, None),
+                CompactionDecision::ChangeValue(new_value) => (key, Some(new_value)),
+            },
+            (_, value) => (key, value),
+        }
+    }
+
     fn table_load_data<F>(table: &&dyn Table, fn_is_filter: F) -> Result<Vec<KeyValue>>
     where
         F: Fn(&Bytes) -> bool,
     {
-        let mut iter = table.iter()?;
+        // 压缩读取的数据不会再次被读取，因此不放入Block缓存中
+        let mut iter = table.iter_with_options(ReadOptions::default().fill_cache(false))?;
         let mut vec_cmd = Vec::with_capacity(table.len());
         while let Some(item) = iter.next_err()? {
             if fn_is_filter(&item.0) {
@@ -290,6 +575,17 @@ impl Compactor {
         Ok(vec_cmd)
     }
 
+    /// 向限速器请求读取数据量对应的令牌，未设置限速器或数据量为0时直接返回
+    ///
+    /// Tips: 写入的令牌由`SSTable::new_with_rate_limiter`在每次分块写入前请求
+    async fn rate_limit(&self, bytes: u64) {
+        if let Some(rate_limiter) = &self.config().rate_limiter {
+            if bytes > 0 {
+                rate_limiter.request(bytes).await;
+            }
+        }
+    }
+
     pub(crate) fn config(&self) -> &Config {
         &self.store_inner.config
     }
@@ -308,13 +604,12 @@ mod tests {
     use crate::kernel::io::{FileExtension, IoFactory, IoType};
     use crate::kernel::lsm::compactor::Compactor;
     use crate::kernel::lsm::storage::Config;
+    use crate::kernel::lsm::table::ss_table::block::BlockCache;
     use crate::kernel::lsm::table::ss_table::SSTable;
     use crate::kernel::lsm::version::DEFAULT_SS_TABLE_PATH;
-    use crate::kernel::utils::lru_cache::ShardingLruCache;
     use crate::kernel::Result;
+    use crate::KernelError;
     use bytes::Bytes;
-    use std::collections::hash_map::RandomState;
-    use std::sync::Arc;
     use tempfile::TempDir;
 
     #[test]
@@ -326,15 +621,11 @@ mod tests {
             config.dir_path.join(DEFAULT_SS_TABLE_PATH),
             FileExtension::SSTable,
         )?;
-        let cache = Arc::new(ShardingLruCache::new(
-            config.block_cache_size,
-            16,
-            RandomState::default(),
-        )?);
+        let cache = BlockCache::new(config.block_cache_size)?.handle();
         let ss_table_1 = SSTable::new(
             &sst_factory,
             &config,
-            Arc::clone(&cache),
+            cache.clone(),
             1,
             vec![
                 (Bytes::from_static(b"1"), Some(Bytes::from_static(b"1"))),
@@ -347,7 +638,7 @@ mod tests {
         let ss_table_2 = SSTable::new(
             &sst_factory,
             &config,
-            Arc::clone(&cache),
+            cache.clone(),
             2,
             vec![
                 (Bytes::from_static(b"3"), Some(Bytes::from_static(b"3"))),
@@ -359,7 +650,7 @@ mod tests {
         let ss_table_3 = SSTable::new(
             &sst_factory,
             &config,
-            Arc::clone(&cache),
+            cache.clone(),
             3,
             vec![
                 (Bytes::from_static(b"1"), Some(Bytes::from_static(b"11"))),
@@ -371,7 +662,7 @@ mod tests {
         let ss_table_4 = SSTable::new(
             &sst_factory,
             &config,
-            Arc::clone(&cache),
+            cache.clone(),
             4,
             vec![
                 (Bytes::from_static(b"3"), Some(Bytes::from_static(b"32"))),
@@ -387,6 +678,9 @@ mod tests {
                 vec![&ss_table_1, &ss_table_2],
                 vec![&ss_table_3, &ss_table_4],
                 config.sst_file_size,
+                0,
+                false,
+                None,
             )
             .await
         })?[0];
@@ -403,4 +697,84 @@ mod tests {
         );
         Ok(())
     }
+
+    #[test]
+    fn test_data_merge_with_bottom_level() -> Result<()> {
+        let temp_dir = TempDir::new().expect("unable to create temporary working directory");
+
+        let config = Config::new(temp_dir.into_path());
+        let sst_factory = IoFactory::new(
+            config.dir_path.join(DEFAULT_SS_TABLE_PATH),
+            FileExtension::SSTable,
+        )?;
+        let cache = BlockCache::new(config.block_cache_size)?.handle();
+        let ss_table_1 = SSTable::new(
+            &sst_factory,
+            &config,
+            cache.clone(),
+            1,
+            vec![
+                (Bytes::from_static(b"1"), None),
+                (Bytes::from_static(b"2"), Some(Bytes::from_static(b"2"))),
+                (Bytes::from_static(b"3"), None),
+            ],
+            0,
+            IoType::Direct,
+        )?;
+        let ss_table_2 = SSTable::new(
+            &sst_factory,
+            &config,
+            cache.clone(),
+            2,
+            vec![
+                (Bytes::from_static(b"1"), Some(Bytes::from_static(b"11"))),
+                (Bytes::from_static(b"4"), Some(Bytes::from_static(b"4"))),
+            ],
+            1,
+            IoType::Direct,
+        )?;
+
+        let (vec_sharding, vec_sharding_bottom) = tokio_test::block_on(async {
+            Ok::<_, KernelError>((
+                Compactor::data_merge_and_sharding(
+                    vec![&ss_table_1],
+                    vec![&ss_table_2],
+                    config.sst_file_size,
+                    0,
+                    false,
+                    None,
+                )
+                .await?,
+                Compactor::data_merge_and_sharding(
+                    vec![&ss_table_1],
+                    vec![&ss_table_2],
+                    config.sst_file_size,
+                    0,
+                    true,
+                    None,
+                )
+                .await?,
+            ))
+        })?;
+
+        // 非最底层时删除标记需要保留以覆盖更底层的旧数据
+        assert_eq!(
+            vec_sharding[0].1,
+            vec![
+                (Bytes::from_static(b"1"), None),
+                (Bytes::from_static(b"2"), Some(Bytes::from_static(b"2"))),
+                (Bytes::from_static(b"3"), None),
+                (Bytes::from_static(b"4"), Some(Bytes::from_static(b"4"))),
+            ]
+        );
+        // 最底层时删除标记与被其覆盖的旧数据一并清除
+        assert_eq!(
+            vec_sharding_bottom[0].1,
+            vec![
+                (Bytes::from_static(b"2"), Some(Bytes::from_static(b"2"))),
+                (Bytes::from_static(b"4"), Some(Bytes::from_static(b"4"))),
+            ]
+        );
+        Ok(())
+    }
 }
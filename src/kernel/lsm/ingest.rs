@@ -0,0 +1,193 @@
+use crate::kernel::io::{FileExtension, IoFactory, IoType};
+use crate::kernel::lsm::compactor::LEVEL_0;
+use crate::kernel::lsm::mem_table::KeyValue;
+use crate::kernel::lsm::storage::{Config, Gen};
+use crate::kernel::lsm::table::meta::TableMeta;
+use crate::kernel::lsm::table::scope::Scope;
+use crate::kernel::lsm::table::ss_table::block::BlockCache;
+use crate::kernel::lsm::table::ss_table::{SSTable, SSTableBuilder};
+use crate::kernel::lsm::table::{scan_scope, Table};
+use crate::kernel::lsm::version::DEFAULT_SS_TABLE_PATH;
+use crate::kernel::Result;
+use crate::KernelError;
+use bytes::Bytes;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tracing::info;
+
+/// 外部SSTable构建器
+///
+/// 在Store之外由有序数据构建SSTable文件，之后可通过`LsmStore::ingest_external_files`导入，
+/// 适用于批量导入数据时跳过WAL、MemTable与压缩的开销
+/// - Key需以严格递增的顺序添加
+/// - 编码使用Config中的Block与Level 0的过滤器参数，应与导入的Store保持一致
+/// - 数据直接添加至Block中构建，数据量达到`Config::sst_file_size`时写入文件并切换至新的文件，
+///   之后的文件在path的文件名后追加序号，如`data.sst`、`data_1.sst`
+pub struct SstFileWriter {
+    config: Config,
+    path: PathBuf,
+    builder: Option<SSTableBuilder>,
+    last_key: Option<Bytes>,
+    len: usize,
+    vec_path: Vec<PathBuf>,
+}
+
+impl SstFileWriter {
+    #[inline]
+    pub fn new(config: &Config, path: impl Into<PathBuf>) -> Self {
+        SstFileWriter {
+            config: config.clone(),
+            path: path.into(),
+            builder: None,
+            last_key: None,
+            len: 0,
+            vec_path: Vec::new(),
+        }
+    }
+
+    #[inline]
+    pub fn put(&mut self, key: impl Into<Bytes>, value: impl Into<Bytes>) -> Result<()> {
+        self.add((key.into(), Some(value.into())))
+    }
+
+    /// 添加删除标记，导入后会覆盖更底层中该Key的旧数据
+    #[inline]
+    pub fn delete(&mut self, key: impl Into<Bytes>) -> Result<()> {
+        self.add((key.into(), None))
+    }
+
+    /// 已添加的数据数量
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// 将剩余的数据写入至文件中，返回所有写入的文件路径，文件已存在时会被覆盖
+    #[inline]
+    pub fn finish(mut self) -> Result<Vec<PathBuf>> {
+        if self.is_empty() {
+            return Err(KernelError::DataEmpty);
+        }
+        self.roll()?;
+
+        Ok(self.vec_path)
+    }
+
+    fn add(&mut self, data: KeyValue) -> Result<()> {
+        if self
+            .last_key
+            .as_ref()
+            .is_some_and(|last_key| last_key >= &data.0)
+        {
+            return Err(KernelError::KeyOutOfOrder);
+        }
+        self.last_key = Some(data.0.clone());
+
+        let config = &self.config;
+        let builder = self
+            .builder
+            .get_or_insert_with(|| SSTableBuilder::new(config, LEVEL_0, 0));
+        builder.add(data);
+        self.len += 1;
+
+        if builder.data_size() >= config.sst_file_size {
+            self.roll()?;
+        }
+        Ok(())
+    }
+
+    /// 将当前构建的SSTable写入至新的文件中
+    fn roll(&mut self) -> Result<()> {
+        let Some(builder) = self.builder.take() else {
+            return Ok(());
+        };
+        let len = builder.len();
+        let (_, _, bytes) = builder.finish()?;
+        let path = self.file_path(self.vec_path.len());
+        let mut file = File::create(&path)?;
+
+        file.write_all(&bytes)?;
+        file.sync_all()?;
+        info!(
+            "[SstFileWriter][roll]: {:?}, Len: {}, Size of Disk: {}",
+            path,
+            len,
+            bytes.len()
+        );
+        self.vec_path.push(path);
+
+        Ok(())
+    }
+
+    /// 第index个文件的路径，首个文件即为path
+    fn file_path(&self, index: usize) -> PathBuf {
+        if index == 0 {
+            return self.path.clone();
+        }
+        let mut file_name = self.path.file_stem().unwrap_or_default().to_os_string();
+        file_name.push(format!("_{index}"));
+        if let Some(extension) = self.path.extension() {
+            file_name.push(".");
+            file_name.push(extension);
+        }
+        self.path.with_file_name(file_name)
+    }
+}
+
+/// 已复制至`ss_table`目录且校验通过，等待导入的外部SSTable
+#[derive(Debug)]
+pub(crate) struct ExternalTable {
+    pub(crate) scope: Scope,
+    pub(crate) meta: TableMeta,
+}
+
+impl ExternalTable {
+    /// 将外部SSTable复制至`ss_table`目录中，并完整遍历以校验其数据与计算Scope
+    ///
+    /// 复制而非移动以保证导入失败时外部文件不受影响
+    pub(crate) fn prepare(config: &Config, path: &Path) -> Result<Self> {
+        let sst_factory = IoFactory::new(
+            config.path().join(DEFAULT_SS_TABLE_PATH),
+            FileExtension::SSTable,
+        )?;
+        let gen = Gen::create();
+        let _ = fs::copy(
+            path,
+            FileExtension::SSTable.path_with_gen(sst_factory.get_path(), gen),
+        )?;
+
+        let result = Self::check(config, &sst_factory, gen);
+        if result.is_err() {
+            sst_factory.clean(gen)?;
+        }
+        result
+    }
+
+    fn check(config: &Config, sst_factory: &IoFactory, gen: i64) -> Result<Self> {
+        // 使用独立的Block缓存，避免校验时的Block以临时的Gen残留在Store的缓存中
+        let cache = BlockCache::new(config.block_cache_size)?.handle();
+        let reader = sst_factory.reader(gen, IoType::Direct)?;
+        let file_size = reader.file_size()?;
+        let ss_table = SSTable::load_from_file(reader, cache)?;
+        let table: &dyn Table = &ss_table;
+
+        if table.size_of_disk() != file_size {
+            return Err(KernelError::TableCorrupted(gen));
+        }
+
+        Ok(ExternalTable {
+            scope: scan_scope(table)?,
+            meta: TableMeta::from(table),
+        })
+    }
+
+    pub(crate) fn gen(&self) -> i64 {
+        self.scope.get_gen()
+    }
+}
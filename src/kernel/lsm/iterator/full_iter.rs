@@ -1,6 +1,9 @@
 use crate::kernel::lsm::iterator::merging_iter::MergingIter;
+use crate::kernel::lsm::iterator::prefix_iter::PrefixIter;
 use crate::kernel::lsm::iterator::{Iter, Seek};
 use crate::kernel::lsm::mem_table::{KeyValue, MemMapIter, TableInner};
+use crate::kernel::lsm::prefix::PrefixExtractor;
+use crate::kernel::lsm::storage::ReadOptions;
 use crate::kernel::lsm::version::iter::VersionIter;
 use crate::kernel::lsm::version::Version;
 use crate::kernel::Result;
@@ -13,19 +16,45 @@ pub struct FullIter<'a> {
 
 impl<'a> FullIter<'a> {
     #[allow(dead_code)]
-    pub(crate) fn new(mem_table: &'a TableInner, version: &'a Version) -> Result<FullIter<'a>> {
+    pub(crate) fn new(
+        mem_table: &'a TableInner,
+        version: &'a Version,
+        options: ReadOptions,
+    ) -> Result<FullIter<'a>> {
+        let mut vec_iter = Self::merging_with_mem_table(mem_table);
+        vec_iter.append(&mut VersionIter::merging_with_version(version, options)?);
+
+        Ok(Self {
+            merge_iter: MergingIter::new(vec_iter)?,
+        })
+    }
+
+    /// MemTable + Version的前缀迭代器，Version中仅迭代可能存在该前缀的Table
+    pub(crate) fn new_with_prefix(
+        mem_table: &'a TableInner,
+        version: &'a Version,
+        extractor: Option<&dyn PrefixExtractor>,
+        prefix: &[u8],
+        options: ReadOptions,
+    ) -> Result<PrefixIter<'a>> {
+        let mut vec_iter = Self::merging_with_mem_table(mem_table);
+        vec_iter.append(&mut VersionIter::merging_with_prefix(
+            version, extractor, prefix, options,
+        )?);
+
+        PrefixIter::new(vec_iter, prefix)
+    }
+
+    fn merging_with_mem_table(
+        mem_table: &'a TableInner,
+    ) -> Vec<Box<dyn Iter<'a, Item = KeyValue> + 'a>> {
         let mut vec_iter: Vec<Box<dyn Iter<'a, Item = KeyValue> + 'a>> =
             vec![Box::new(MemMapIter::new(&mem_table._mem))];
 
         if let Some(immut_map) = &mem_table._immut {
             vec_iter.push(Box::new(MemMapIter::new(immut_map)));
         }
-
-        vec_iter.append(&mut VersionIter::merging_with_version(version)?);
-
-        Ok(Self {
-            merge_iter: MergingIter::new(vec_iter)?,
-        })
+        vec_iter
     }
 }
 
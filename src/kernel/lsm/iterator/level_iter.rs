@@ -1,6 +1,7 @@
 use crate::kernel::lsm::compactor::LEVEL_0;
 use crate::kernel::lsm::iterator::{Iter, Seek};
 use crate::kernel::lsm::mem_table::KeyValue;
+use crate::kernel::lsm::storage::ReadOptions;
 use crate::kernel::lsm::version::Version;
 use crate::kernel::Result;
 use crate::KernelError;
@@ -11,6 +12,7 @@ pub(crate) struct LevelIter<'a> {
     version: &'a Version,
     level: usize,
     level_len: usize,
+    options: ReadOptions,
 
     offset: usize,
     child_iter: Box<dyn Iter<'a, Item = KeyValue> + 'a>,
@@ -18,25 +20,39 @@ pub(crate) struct LevelIter<'a> {
 
 impl<'a> LevelIter<'a> {
     #[allow(dead_code)]
-    pub(crate) fn new(version: &'a Version, level: usize) -> Result<LevelIter<'a>> {
+    pub(crate) fn new(
+        version: &'a Version,
+        level: usize,
+        options: ReadOptions,
+    ) -> Result<LevelIter<'a>> {
         let table = version.table(level, 0).ok_or(KernelError::DataEmpty)?;
-        let child_iter = table.iter()?;
+        let child_iter = table.iter_with_options(options)?;
         let level_len = version.level_len(level);
 
         Ok(Self {
             version,
             level,
             level_len,
+            options,
             offset: 0,
             child_iter,
         })
     }
 
     fn child_iter_seek(&mut self, seek: Seek<'_>, offset: usize) -> Result<Option<KeyValue>> {
+        self.child_iter_seek_with_options(seek, offset, self.options)
+    }
+
+    fn child_iter_seek_with_options(
+        &mut self,
+        seek: Seek<'_>,
+        offset: usize,
+        options: ReadOptions,
+    ) -> Result<Option<KeyValue>> {
         self.offset = offset;
         if self.is_valid() {
             if let Some(table) = self.version.table(self.level, offset) {
-                self.child_iter = table.iter()?;
+                self.child_iter = table.iter_with_options(options)?;
                 return self.child_iter.seek(seek);
             }
         }
@@ -59,7 +75,12 @@ impl<'a> Iter<'a> for LevelIter<'a> {
 
     fn next_err(&mut self) -> Result<Option<Self::Item>> {
         match self.child_iter.next_err()? {
-            None => self.child_iter_seek(Seek::First, self.offset + 1),
+            // 顺序切换至下一个Table时延续预读，避免其开头的Data Block被逐个读取
+            None => self.child_iter_seek_with_options(
+                Seek::First,
+                self.offset + 1,
+                self.options.sequential(true),
+            ),
             Some(item) => Ok(Some(item)),
         }
     }
@@ -86,7 +107,7 @@ mod tests {
     use crate::kernel::lsm::iterator::{Iter, Seek};
     use crate::kernel::lsm::log::LogLoader;
     use crate::kernel::lsm::mem_table::DEFAULT_WAL_PATH;
-    use crate::kernel::lsm::storage::Config;
+    use crate::kernel::lsm::storage::{Config, ReadOptions};
     use crate::kernel::lsm::table::meta::TableMeta;
     use crate::kernel::lsm::table::TableType;
     use crate::kernel::lsm::version::edit::VersionEdit;
@@ -128,14 +149,14 @@ mod tests {
             }
             let (slice_1, slice_2) = vec_data.split_at(2000);
 
-            let (scope_1, meta_1) =
-                ver_status
-                    .loader()
-                    .create(1, slice_1.to_vec(), 1, TableType::SortedString)?;
-            let (scope_2, meta_2) =
-                ver_status
-                    .loader()
-                    .create(2, slice_2.to_vec(), 1, TableType::Skip)?;
+            let (scope_1, meta_1) = ver_status
+                .loader()
+                .create(1, slice_1.to_vec(), 1, TableType::SortedString)
+                .await?;
+            let (scope_2, meta_2) = ver_status
+                .loader()
+                .create(2, slice_2.to_vec(), 1, TableType::Skip)
+                .await?;
             let fusion_meta = TableMeta::fusion(&vec![meta_1, meta_2]);
 
             let vec_edit = vec![
@@ -155,7 +176,7 @@ mod tests {
 
             let version = ver_status.current().await;
 
-            let mut iterator = LevelIter::new(&version, 1)?;
+            let mut iterator = LevelIter::new(&version, 1, ReadOptions::default())?;
             for i in 0..times {
                 assert_eq!(iterator.next_err()?.unwrap(), vec_data[i]);
             }
@@ -174,7 +195,7 @@ mod tests {
 
             assert_eq!(iterator.seek(Seek::Last)?.unwrap(), vec_data[3999]);
 
-            let mut iterator_level_0 = LevelIter::new(&version, 0)?;
+            let mut iterator_level_0 = LevelIter::new(&version, 0, ReadOptions::default())?;
 
             assert!(iterator_level_0
                 .seek(Seek::Backward(&vec_data[3333].0))
@@ -141,15 +141,13 @@ mod tests {
     use crate::kernel::lsm::iterator::merging_iter::MergingIter;
     use crate::kernel::lsm::iterator::{Iter, Seek};
     use crate::kernel::lsm::mem_table::{InternalKey, KeyValue, MemMap, MemMapIter};
-    use crate::kernel::lsm::storage::Config;
+    use crate::kernel::lsm::storage::{Config, ReadOptions};
+    use crate::kernel::lsm::table::ss_table::block::BlockCache;
     use crate::kernel::lsm::table::ss_table::iter::SSTableIter;
     use crate::kernel::lsm::table::ss_table::SSTable;
     use crate::kernel::lsm::version::DEFAULT_SS_TABLE_PATH;
-    use crate::kernel::utils::lru_cache::ShardingLruCache;
     use crate::kernel::Result;
     use bytes::Bytes;
-    use std::collections::hash_map::RandomState;
-    use std::sync::Arc;
     use tempfile::TempDir;
 
     #[test]
@@ -250,16 +248,12 @@ mod tests {
             config.dir_path.join(DEFAULT_SS_TABLE_PATH),
             FileExtension::SSTable,
         )?;
-        let cache = Arc::new(ShardingLruCache::new(
-            config.table_cache_size,
-            16,
-            RandomState::default(),
-        )?);
+        let cache = BlockCache::new(config.block_cache_size)?.handle();
 
         let ss_table = SSTable::new(
             &sst_factory,
             &config,
-            Arc::clone(&cache),
+            cache.clone(),
             1,
             data_2,
             0,
@@ -268,7 +262,7 @@ mod tests {
 
         let map_iter = MemMapIter::new(&map);
 
-        let sst_iter = SSTableIter::new(&ss_table)?;
+        let sst_iter = SSTableIter::new(&ss_table, ReadOptions::default())?;
 
         let mut sequence_iter = sequence.into_iter();
 
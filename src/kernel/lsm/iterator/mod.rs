@@ -1,6 +1,7 @@
 pub(crate) mod full_iter;
 pub(crate) mod level_iter;
 pub(crate) mod merging_iter;
+pub(crate) mod prefix_iter;
 
 use crate::kernel::Result;
 
@@ -0,0 +1,69 @@
+use crate::kernel::lsm::iterator::merging_iter::MergingIter;
+use crate::kernel::lsm::iterator::{Iter, Seek};
+use crate::kernel::lsm::mem_table::KeyValue;
+use crate::kernel::Result;
+use crate::KernelError;
+use bytes::Bytes;
+
+const PREFIX_SEEK_LAST_MESSAGE: &str = "prefix iterator cannot seek last";
+
+/// 前缀键值对迭代器
+///
+/// 仅迭代以prefix为前缀的键值对，创建时即定位至第一个满足前缀的元素
+pub struct PrefixIter<'a> {
+    merge_iter: MergingIter<'a>,
+    prefix: Bytes,
+    /// 定位时读取到的第一个元素
+    seek_item: Option<KeyValue>,
+}
+
+impl<'a> PrefixIter<'a> {
+    pub(crate) fn new(
+        vec_iter: Vec<Box<dyn Iter<'a, Item = KeyValue> + 'a>>,
+        prefix: &[u8],
+    ) -> Result<PrefixIter<'a>> {
+        let mut iter = PrefixIter {
+            merge_iter: MergingIter::new(vec_iter)?,
+            prefix: Bytes::copy_from_slice(prefix),
+            seek_item: None,
+        };
+        iter.seek_item = iter.seek(Seek::First)?;
+
+        Ok(iter)
+    }
+
+    fn filter_prefix(&self, option_item: Option<KeyValue>) -> Option<KeyValue> {
+        option_item.filter(|(key, _)| key.starts_with(&self.prefix))
+    }
+}
+
+impl<'a> Iter<'a> for PrefixIter<'a> {
+    type Item = KeyValue;
+
+    fn next_err(&mut self) -> Result<Option<Self::Item>> {
+        let option_item = match self.seek_item.take() {
+            Some(item) => Some(item),
+            None => self.merge_iter.next_err()?,
+        };
+
+        Ok(self.filter_prefix(option_item))
+    }
+
+    fn is_valid(&self) -> bool {
+        self.merge_iter.is_valid()
+    }
+
+    /// Tips: 不支持Seek::Last，因为前缀之后的元素需要逐个跳过
+    fn seek(&mut self, seek: Seek<'_>) -> Result<Option<Self::Item>> {
+        self.seek_item = None;
+        let option_item = match seek {
+            Seek::First => self.merge_iter.seek(Seek::Backward(&self.prefix))?,
+            Seek::Backward(key) => self
+                .merge_iter
+                .seek(Seek::Backward(key.max(self.prefix.as_ref())))?,
+            Seek::Last => return Err(KernelError::NotSupport(PREFIX_SEEK_LAST_MESSAGE)),
+        };
+
+        Ok(self.filter_prefix(option_item))
+    }
+}
@@ -7,7 +7,7 @@ use std::cmp::min;
 /// dermesser/leveldb-rs crates.io: v1.0.6
 /// https://github.com/dermesser/leveldb-rs/blob/master/src/log.rs
 /// The MIT License (MIT)
-use std::io::{Read, Seek, SeekFrom, Write};
+use std::io::{self, Read, Seek, SeekFrom, Write};
 use std::path::Path;
 use std::sync::Arc;
 
@@ -187,6 +187,10 @@ impl<W: Write> LogWriter<W> {
         self.dst.flush()?;
         Ok(())
     }
+
+    pub(crate) fn get_mut(&mut self) -> &mut W {
+        &mut self.dst
+    }
 }
 
 pub(crate) struct LogReader<R: Read + Seek> {
@@ -223,10 +227,14 @@ impl<R: Read + Seek> LogReader<R> {
                 self.offset = 0;
             }
 
-            head_pos += self.src.read(&mut self.head_scratch[head_pos..])?;
+            let read_len = self.src.read(&mut self.head_scratch[head_pos..])?;
+            head_pos += read_len;
             // EOF
             if head_pos == 0 {
                 return Ok(dst_offset);
+            } else if read_len == 0 {
+                // 宕机时末尾可能存在不完整的Header，避免无限循环
+                return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
             } else if head_pos != HEADER_SIZE {
                 continue;
             } else {
@@ -255,6 +263,17 @@ impl<R: Read + Seek> LogReader<R> {
             }
         }
     }
+
+    /// 是否已读取至末尾
+    ///
+    /// 用于判断读取失败的记录是否为宕机时末尾写入不完整的记录
+    pub(crate) fn is_eof(&mut self) -> Result<bool> {
+        let position = self.src.stream_position()?;
+        let end = self.src.seek(SeekFrom::End(0))?;
+        let _ = self.src.seek(SeekFrom::Start(position))?;
+
+        Ok(position == end)
+    }
 }
 
 #[cfg(test)]
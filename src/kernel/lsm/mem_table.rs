@@ -1,10 +1,12 @@
 use crate::kernel::io::IoWriter;
 use crate::kernel::lsm::iterator::{Iter, Seek};
 use crate::kernel::lsm::log::{LogLoader, LogWriter};
-use crate::kernel::lsm::storage::{Config, Gen, Sequence};
+use crate::kernel::lsm::storage::{Config, Gen, Sequence, READ_ONLY_MESSAGE};
+use crate::kernel::lsm::table::scope::Scope;
 use crate::kernel::lsm::table::ss_table::block::{Entry, Value};
 use crate::kernel::lsm::trigger::{Trigger, TriggerFactory};
 use crate::kernel::Result;
+use crate::KernelError;
 use bytes::Bytes;
 use itertools::Itertools;
 use parking_lot::{Mutex, MutexGuard};
@@ -12,6 +14,7 @@ use skiplist::{skipmap, SkipMap};
 use std::cmp::Ordering;
 use std::collections::Bound;
 use std::io::Cursor;
+use std::iter;
 use std::mem;
 use std::sync::atomic::AtomicUsize;
 use std::sync::atomic::Ordering::Acquire;
@@ -157,7 +160,9 @@ pub(crate) struct TableInner {
     /// 用于异常停机时MemTable的恢复
     /// 同时当Level 0的SSTable异常时，可以尝试恢复
     log_loader: LogLoader,
-    log_writer: (LogWriter<Box<dyn IoWriter>>, i64),
+    /// 只读模式下不创建WAL的写入器
+    log_writer: Option<LogWriter<Box<dyn IoWriter>>>,
+    log_gen: i64,
     trigger: Box<dyn Trigger + Send>,
 }
 
@@ -169,7 +174,11 @@ impl MemTable {
             config.wal_io_type,
             |bytes| Ok(mem::take(bytes)),
         )?;
-        let log_writer = (log_loader.writer(log_gen)?, log_gen);
+        let log_writer = if config.read_only {
+            None
+        } else {
+            Some(log_loader.writer(log_gen)?)
+        };
         // Q: 为什么INIT_SEQ作为Seq id?
         // A: 因为此处是当存在有停机异常时使用wal恢复数据,此处也不存在有Version(VersionStatus的初始化在此代码之后)
         // 因此不会影响Version的读取顺序
@@ -184,6 +193,7 @@ impl MemTable {
                 _immut: None,
                 log_loader,
                 log_writer,
+                log_gen,
                 trigger: TriggerFactory::create(trigger_type, threshold),
             }),
             tx_count: AtomicUsize::new(0),
@@ -199,7 +209,7 @@ impl MemTable {
 
         inner.trigger.item_process(&data);
 
-        let _ = inner.log_writer.0.add_record(&data_to_bytes(data)?)?;
+        let _ = inner.writer()?.add_record(&data_to_bytes(data)?)?;
         let _ = inner._mem.insert(InternalKey::new(key), value);
 
         Ok(inner.trigger.is_exceeded())
@@ -208,6 +218,7 @@ impl MemTable {
     /// Tips: 当数据在插入mem_table中停机，则不会存入日志中
     pub(crate) fn insert_batch_data(&self, vec_data: Vec<KeyValue>, seq_id: i64) -> Result<bool> {
         let mut inner = self.inner.lock();
+        let _ = inner.writer()?;
 
         let mut buf = Vec::new();
         for item in vec_data {
@@ -219,7 +230,7 @@ impl MemTable {
                 .insert(InternalKey::new_with_seq(key, seq_id), value);
             buf.append(&mut data_to_bytes(item)?);
         }
-        let _ = inner.log_writer.0.add_record(&buf)?;
+        let _ = inner.writer()?.add_record(&buf)?;
 
         Ok(inner.trigger.is_exceeded())
     }
@@ -232,6 +243,11 @@ impl MemTable {
         self.inner.lock()._mem.len()
     }
 
+    /// 当前MemTable所使用的WAL的Gen
+    pub(crate) fn log_gen(&self) -> i64 {
+        self.inner.lock().log_gen
+    }
+
     pub(crate) fn log_loader_clone(&self) -> LogLoader {
         self.inner.lock().log_loader.clone()
     }
@@ -266,9 +282,10 @@ impl MemTable {
                     inner._immut = Some(mem::replace(&mut inner._mem, SkipMap::new()));
 
                     let new_gen = Gen::create();
-                    let new_writer = (inner.log_loader.writer(new_gen)?, new_gen);
-                    let (mut old_writer, old_gen) = mem::replace(&mut inner.log_writer, new_writer);
-                    old_writer.flush()?;
+                    let new_writer = inner.log_loader.writer(new_gen)?;
+                    inner.writer()?.flush()?;
+                    inner.log_writer = Some(new_writer);
+                    let old_gen = mem::replace(&mut inner.log_gen, new_gen);
 
                     Ok(Some((old_gen, vec_data)))
                 } else {
@@ -279,6 +296,37 @@ impl MemTable {
         }
     }
 
+    /// 在数据已持久化至Version后丢弃immutable
+    ///
+    /// 与swap相同，需等待当前事务结束，避免事务读取不到其seq所对应的数据
+    pub(crate) fn clear_immut(&self) {
+        loop {
+            if 0 == self.tx_count.load(Acquire) {
+                let mut inner = self.inner.lock();
+                if 0 != self.tx_count.load(Acquire) {
+                    continue;
+                }
+                inner._immut = None;
+                return;
+            }
+            std::hint::spin_loop();
+        }
+    }
+
+    /// 判断mem与immutable中是否存在与scope相交的数据
+    pub(crate) fn is_overlapped(&self, scope: &Scope) -> bool {
+        let inner = self.inner.lock();
+        let min_key = InternalKey::new_with_seq(scope.start.clone(), i64::MIN);
+
+        iter::once(&inner._mem)
+            .chain(inner._immut.as_ref())
+            .any(|mem_map| {
+                mem_map
+                    .lower_bound(Bound::Included(&min_key))
+                    .is_some_and(|(internal_key, _)| internal_key.key <= scope.end)
+            })
+    }
+
     pub(crate) fn find(&self, key: &[u8]) -> Option<Bytes> {
         // 填充SEQ_MAX使其变为最高位以尽可能获取最新数据
         let internal_key = InternalKey::new_with_seq(Bytes::copy_from_slice(key), SEQ_MAX);
@@ -382,6 +430,14 @@ impl MemTable {
     }
 }
 
+impl TableInner {
+    fn writer(&mut self) -> Result<&mut LogWriter<Box<dyn IoWriter>>> {
+        self.log_writer
+            .as_mut()
+            .ok_or(KernelError::NotSupport(READ_ONLY_MESSAGE))
+    }
+}
+
 pub(crate) fn logs_decode(log_bytes: Vec<Vec<u8>>) -> Result<IntoIter<(Bytes, Option<Bytes>)>> {
     let flatten_bytes = log_bytes.into_iter().flatten().collect_vec();
     Entry::<Value>::batch_decode(&mut Cursor::new(flatten_bytes)).map(|vec| {
@@ -415,7 +471,7 @@ mod tests {
             let (key, value) = data.clone();
             let mut inner = self.inner.lock();
 
-            let _ = inner.log_writer.0.add_record(&data_to_bytes(data)?)?;
+            let _ = inner.writer()?.add_record(&data_to_bytes(data)?)?;
             let _ = inner
                 ._mem
                 .insert(InternalKey::new_with_seq(key, seq), value);
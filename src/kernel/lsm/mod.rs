@@ -2,14 +2,21 @@ use crate::kernel::lsm::compactor::MergeShardingVec;
 use crate::kernel::lsm::mem_table::{key_value_bytes_len, KeyValue};
 use crate::kernel::lsm::storage::Gen;
 
-mod compactor;
+pub mod backup;
+mod checkpoint;
+pub mod compactor;
+pub mod ingest;
 mod iterator;
 mod log;
 mod mem_table;
 mod mvcc;
+pub mod prefix;
+pub mod repair;
+mod row_cache;
 pub mod storage;
 mod table;
 mod trigger;
+pub mod verify;
 mod version;
 
 /// KeyValue数据分片，尽可能将数据按给定的分片大小：file_size，填满一片（可能会溢出一些）
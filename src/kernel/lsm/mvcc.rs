@@ -1,7 +1,9 @@
 use crate::kernel::lsm::compactor::CompactTask;
+use crate::kernel::lsm::iterator::prefix_iter::PrefixIter;
 use crate::kernel::lsm::iterator::{Iter, Seek};
 use crate::kernel::lsm::mem_table::{KeyValue, MemTable};
-use crate::kernel::lsm::storage::{Sequence, StoreInner};
+use crate::kernel::lsm::prefix;
+use crate::kernel::lsm::storage::{ReadOptions, Sequence, StoreInner};
 use crate::kernel::lsm::version::iter::VersionIter;
 use crate::kernel::lsm::version::Version;
 use crate::kernel::Result;
@@ -43,11 +45,7 @@ impl Transaction {
             return Ok(Some(value));
         }
 
-        if let Some(value) = self.version.query(key)? {
-            return Ok(Some(value));
-        }
-
-        Ok(None)
+        self.store_inner.query_version(&self.version, key)
     }
 
     pub fn set(&mut self, key: &[u8], value: Bytes) {
@@ -65,21 +63,75 @@ impl Transaction {
     }
 
     pub fn range_scan(&self, min: Bound<&[u8]>, max: Bound<&[u8]>) -> Result<Vec<KeyValue>> {
-        let version_range = self.version_range(min, max)?;
+        self.range_scan_with_options(min, max, ReadOptions::default())
+    }
+
+    pub fn range_scan_with_options(
+        &self,
+        min: Bound<&[u8]>,
+        max: Bound<&[u8]>,
+        options: ReadOptions,
+    ) -> Result<Vec<KeyValue>> {
+        let version_range = self.version_range(min, max, options)?;
+
+        Ok(self.merge_range(min, max, version_range))
+    }
+
+    /// 获取所有以prefix为前缀的键值对
+    ///
+    /// 设置了Config::prefix_extractor时，将跳过前缀过滤器排除了该前缀的SSTable
+    pub fn prefix_scan(&self, prefix: &[u8]) -> Result<Vec<KeyValue>> {
+        self.prefix_scan_with_options(prefix, ReadOptions::default())
+    }
+
+    pub fn prefix_scan_with_options(
+        &self,
+        prefix: &[u8],
+        options: ReadOptions,
+    ) -> Result<Vec<KeyValue>> {
+        let extractor = self.store_inner.config.prefix_extractor.as_deref();
+        let mut iter = PrefixIter::new(
+            VersionIter::merging_with_prefix(&self.version, extractor, prefix, options)?,
+            prefix,
+        )?;
+        let mut version_range = Vec::new();
+
+        while let Some(item) = iter.next_err()? {
+            version_range.push(item);
+        }
+        let upper_bound = prefix::upper_bound(prefix);
+        let max = upper_bound
+            .as_deref()
+            .map_or(Bound::Unbounded, Bound::Excluded);
+
+        Ok(self.merge_range(Bound::Included(prefix), max, version_range))
+    }
+
+    /// 合并事务写入、MemTable与Version中的数据，优先级依次降低
+    fn merge_range(
+        &self,
+        min: Bound<&[u8]>,
+        max: Bound<&[u8]>,
+        version_range: Vec<KeyValue>,
+    ) -> Vec<KeyValue> {
         let mem_table_range = self.mem_table().range_scan(min, max, Some(self.seq_id));
 
-        Ok(self
-            ._mem_range(min, max)
+        self._mem_range(min, max)
             .chain(mem_table_range)
             .chain(version_range)
             .unique_by(|(key, _)| key.clone())
             .sorted_by_key(|(key, _)| key.clone())
-            .collect_vec())
+            .collect_vec()
     }
 
-    fn version_range(&self, min: Bound<&[u8]>, max: Bound<&[u8]>) -> Result<Vec<KeyValue>> {
+    fn version_range(
+        &self,
+        min: Bound<&[u8]>,
+        max: Bound<&[u8]>,
+        options: ReadOptions,
+    ) -> Result<Vec<KeyValue>> {
         let mut version_range = Vec::new();
-        let mut iter = VersionIter::new(&self.version)?;
+        let mut iter = VersionIter::with_options(&self.version, options)?;
 
         match min {
             Bound::Included(key) => {
@@ -116,8 +168,14 @@ impl Transaction {
             .map(|(key, value)| (key.clone(), value.clone()))
             .collect_vec();
 
+        let keys = batch_data.iter().map(|(key, _)| key.clone()).collect_vec();
         let mem_table = self.mem_table();
-        if mem_table.insert_batch_data(batch_data, Sequence::create())? {
+        let is_full = mem_table.insert_batch_data(batch_data, Sequence::create())?;
+
+        for key in keys {
+            self.store_inner.invalidate_row(&key);
+        }
+        if is_full {
             if let Err(TrySendError::Closed(_)) =
                 self.compactor_tx.try_send(CompactTask::Flush(None))
             {
@@ -153,6 +211,10 @@ impl Transaction {
         VersionIter::new(&self.version)
     }
 
+    pub fn disk_iter_with_options(&self, options: ReadOptions) -> Result<VersionIter> {
+        VersionIter::with_options(&self.version, options)
+    }
+
     fn mem_table(&self) -> &MemTable {
         &self.store_inner.mem_table
     }
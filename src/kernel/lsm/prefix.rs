@@ -0,0 +1,91 @@
+use bytes::Bytes;
+use std::fmt::Debug;
+
+/// Key前缀提取器
+///
+/// 设置于`Config::prefix_extractor`后，每个SSTable会额外记录其中所有Key前缀的过滤器，
+/// 其类型与该Level的FilterPolicy一致，前缀迭代时将跳过过滤器排除了该前缀的SSTable
+///
+/// Tips:
+/// - 若prefix(p)为Some(p)，则所有以p为前缀的Key提取出的前缀必须同样为p，否则前缀迭代会遗漏数据
+/// - 前缀迭代所使用的前缀不满足prefix(p) == Some(p)时不会使用前缀过滤器
+/// - name会记录于SSTable中，提取规则改变时必须同时修改name，使旧SSTable中的前缀过滤器不被误用
+pub trait PrefixExtractor: Debug + Send + Sync {
+    /// 提取Key的前缀，返回None时该Key不加入前缀过滤器
+    fn prefix<'a>(&self, key: &'a [u8]) -> Option<&'a [u8]>;
+
+    /// 提取器的名称
+    fn name(&self) -> &str;
+}
+
+/// 以Key的前len个字节作为前缀，长度不足的Key不加入前缀过滤器
+#[derive(Debug, Clone)]
+pub struct FixedPrefix {
+    len: usize,
+    name: String,
+}
+
+impl FixedPrefix {
+    #[inline]
+    pub fn new(len: usize) -> Self {
+        FixedPrefix {
+            len,
+            name: format!("kip.FixedPrefix.{len}"),
+        }
+    }
+}
+
+impl PrefixExtractor for FixedPrefix {
+    #[inline]
+    fn prefix<'a>(&self, key: &'a [u8]) -> Option<&'a [u8]> {
+        key.get(..self.len)
+    }
+
+    #[inline]
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// 前缀迭代时是否可以使用前缀过滤器进行判断
+pub(crate) fn is_in_domain(extractor: &dyn PrefixExtractor, prefix: &[u8]) -> bool {
+    extractor.prefix(prefix) == Some(prefix)
+}
+
+/// 所有以prefix为前缀的Key的上界(不包含)，prefix全部由0xFF组成时不存在上界
+pub(crate) fn upper_bound(prefix: &[u8]) -> Option<Bytes> {
+    let len = prefix.iter().rposition(|byte| *byte != u8::MAX)?;
+    let mut upper = prefix[..=len].to_vec();
+    upper[len] += 1;
+
+    Some(Bytes::from(upper))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::kernel::lsm::prefix::{is_in_domain, upper_bound, FixedPrefix, PrefixExtractor};
+    use bytes::Bytes;
+
+    #[test]
+    fn test_fixed_prefix() {
+        let extractor = FixedPrefix::new(3);
+
+        assert_eq!(extractor.prefix(b"user:1"), Some(&b"use"[..]));
+        assert_eq!(extractor.prefix(b"us"), None);
+        assert!(is_in_domain(&extractor, b"use"));
+        assert!(!is_in_domain(&extractor, b"us"));
+        assert!(!is_in_domain(&extractor, b"user"));
+        assert_ne!(extractor.name(), FixedPrefix::new(4).name());
+    }
+
+    #[test]
+    fn test_upper_bound() {
+        assert_eq!(upper_bound(b"abc"), Some(Bytes::from_static(b"abd")));
+        assert_eq!(
+            upper_bound(&[1, u8::MAX, u8::MAX]),
+            Some(Bytes::from_static(&[2]))
+        );
+        assert_eq!(upper_bound(&[u8::MAX]), None);
+        assert_eq!(upper_bound(b""), None);
+    }
+}
@@ -0,0 +1,264 @@
+use crate::kernel::io::{FileExtension, IoFactory, IoType};
+use crate::kernel::lsm::compactor::LEVEL_0;
+use crate::kernel::lsm::log::LogLoader;
+use crate::kernel::lsm::mem_table::{logs_decode, DEFAULT_WAL_PATH};
+use crate::kernel::lsm::storage::Config;
+use crate::kernel::lsm::table::meta::TableMeta;
+use crate::kernel::lsm::table::scope::Scope;
+use crate::kernel::lsm::table::ss_table::block::{BlockCache, BlockCacheHandle};
+use crate::kernel::lsm::table::ss_table::SSTable;
+use crate::kernel::lsm::table::{scan_scope, Table};
+use crate::kernel::lsm::version::edit::VersionEdit;
+use crate::kernel::lsm::version::manifest::Manifest;
+use crate::kernel::lsm::version::{DEFAULT_SS_TABLE_PATH, DEFAULT_VERSION_PATH};
+use crate::kernel::{sorted_gen_list, Result};
+use crate::KernelError;
+use itertools::Itertools;
+use std::fs;
+use std::mem;
+use tracing::{info, warn};
+
+/// 修复时无法恢复的SSTable所移动至的目录
+pub(crate) const DEFAULT_LOST_PATH: &str = "lost";
+
+/// 修复结果
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RepairReport {
+    /// 写入新Version中的Table数量
+    pub tables: usize,
+    /// 因与同Level或已放置至Level 0的Table相交(或Level超出层数)而被放置至Level 0的Table数量
+    pub moved_to_level_0: usize,
+    /// SSTable损坏而通过同Gen的WAL重建的Table数量
+    pub recovered_from_wal: usize,
+    /// 无法恢复而被移动至lost目录的SSTable的Gen
+    pub lost: Vec<i64>,
+}
+
+/// 离线修复器
+///
+/// 当Version丢失或损坏时，以`ss_table`目录下的SSTable为准重建Version
+/// - 每个SSTable均会完整遍历校验，损坏时尝试通过同Gen的WAL(Level 0的备份)重建，否则移动至`lost`目录
+/// - SSTable以Footer中记录的Level进行放置，与同Level中其他Table相交时放置至Level 0
+/// - Table按数据的新旧程度由旧至新依次放置，Level 0中Gen顺序与新旧程度不一致的Table会被重新分配Gen，
+///   使Level 0中较新的Table能够覆盖较旧的数据
+/// - 没有对应SSTable的WAL不会被转换为Table：无法区分其为未Flush的MemTable还是已被压缩的Table的残留，
+///   转换后可能使旧数据覆盖新数据，MemTable所使用的最新WAL会在启动时正常重放
+pub(crate) struct Repairer {
+    config: Config,
+    sst_factory: IoFactory,
+    wal: LogLoader,
+    cache: BlockCacheHandle,
+}
+
+/// 校验通过的Table信息
+struct TableInfo {
+    level: usize,
+    scope: Scope,
+    meta: TableMeta,
+}
+
+impl Repairer {
+    pub(crate) fn new(config: Config) -> Result<Self> {
+        let sst_factory = IoFactory::new(
+            config.path().join(DEFAULT_SS_TABLE_PATH),
+            FileExtension::SSTable,
+        )?;
+        let (wal, _, _) = LogLoader::reload(
+            config.path(),
+            (DEFAULT_WAL_PATH, None),
+            config.wal_io_type,
+            |_| Ok(()),
+        )?;
+        let cache = BlockCache::new(config.block_cache_size)?.handle();
+
+        Ok(Repairer {
+            config,
+            sst_factory,
+            wal,
+            cache,
+        })
+    }
+
+    pub(crate) fn run(self) -> Result<RepairReport> {
+        let mut report = RepairReport::default();
+        let mut vec_info = Vec::new();
+
+        for gen in sorted_gen_list(self.sst_factory.get_path(), FileExtension::SSTable)? {
+            match self.check_table(gen) {
+                Ok(info) => vec_info.push(info),
+                Err(err) => {
+                    warn!("[Repairer][SSTable: {}][check error]: {:?}", gen, err);
+                    // 旧文件需先移走，以免重建时覆盖
+                    self.move_to_lost(gen)?;
+
+                    match self.reload_with_wal(gen) {
+                        Ok(info) => {
+                            report.recovered_from_wal += 1;
+                            vec_info.push(info);
+                        }
+                        Err(err) => {
+                            warn!("[Repairer][SSTable: {}][lost]: {:?}", gen, err);
+                            report.lost.push(gen);
+                        }
+                    }
+                }
+            }
+        }
+
+        let vec_edit = self.build_edits(vec_info, &mut report)?;
+        let _ = Manifest::create(&self.config.path().join(DEFAULT_VERSION_PATH), &vec_edit)?;
+        info!("[Repairer][run]: {:?}", report);
+
+        Ok(report)
+    }
+
+    /// 完整遍历SSTable以校验其数据，并计算其Scope
+    fn check_table(&self, gen: i64) -> Result<TableInfo> {
+        let reader = self.sst_factory.reader(gen, IoType::Direct)?;
+        let file_size = reader.file_size()?;
+        let ss_table = SSTable::load_from_file(reader, self.cache.clone())?;
+
+        if ss_table.size_of_disk() != file_size {
+            return Err(KernelError::TableCorrupted(gen));
+        }
+        Self::table_info(&ss_table)
+    }
+
+    fn reload_with_wal(&self, gen: i64) -> Result<TableInfo> {
+        // WAL中的数据以写入顺序排列，需要以Key排序并保留每个Key最新的数据
+        let reload_data = logs_decode(self.wal.load(gen, |bytes| Ok(mem::take(bytes)))?)?
+            .rev()
+            .unique_by(|(key, _)| key.clone())
+            .sorted_by(|(key_a, _), (key_b, _)| key_a.cmp(key_b))
+            .collect_vec();
+        if reload_data.is_empty() {
+            return Err(KernelError::DataEmpty);
+        }
+        let ss_table = SSTable::new(
+            &self.sst_factory,
+            &self.config,
+            self.cache.clone(),
+            gen,
+            reload_data,
+            LEVEL_0,
+            IoType::Direct,
+        )?;
+
+        Self::table_info(&ss_table)
+    }
+
+    fn table_info(table: &dyn Table) -> Result<TableInfo> {
+        Ok(TableInfo {
+            level: table.level(),
+            scope: scan_scope(table)?,
+            meta: TableMeta::from(table),
+        })
+    }
+
+    fn move_to_lost(&self, gen: i64) -> Result<()> {
+        let lost_path = self.config.path().join(DEFAULT_LOST_PATH);
+        fs::create_dir_all(&lost_path)?;
+        fs::rename(
+            FileExtension::SSTable.path_with_gen(self.sst_factory.get_path(), gen),
+            FileExtension::SSTable.path_with_gen(&lost_path, gen),
+        )?;
+
+        Ok(())
+    }
+
+    /// 将Table按Level分组，Level N中与已放置的Table相交的Table会被放置至Level 0
+    ///
+    /// Table由旧至新依次放置，新旧程度以Footer中的Level与Gen进行估计：
+    /// - Level越大的Table数据越旧：压缩总是将数据由上层向下层转移
+    /// - 同一Level中相交的Table，Gen较大者较新：Gen较小者在Gen较大者创建时必然已不在该Level
+    ///   (否则会一同参与压缩)，而只可能被移动至更底层
+    ///
+    /// 因此Level N中保留的总是较旧的Table，较新的Table被放置至Level 0并按由旧至新的顺序排列，
+    /// 与已放置至Level 0的Table相交的Table同样需放置至Level 0，以免被Level 0中较旧的数据覆盖
+    ///
+    /// Tips: 不能仅以Gen判断新旧，压缩至下层的Table会以新的Gen写入旧的数据
+    fn build_edits(
+        &self,
+        vec_info: Vec<TableInfo>,
+        report: &mut RepairReport,
+    ) -> Result<Vec<VersionEdit>> {
+        let num_levels = self.config.num_levels;
+        let mut levels: Vec<Vec<TableInfo>> = (0..num_levels).map(|_| Vec::new()).collect();
+
+        for info in vec_info.into_iter().sorted_by(|info_a, info_b| {
+            info_b
+                .level
+                .cmp(&info_a.level)
+                .then_with(|| info_a.scope.get_gen().cmp(&info_b.scope.get_gen()))
+        }) {
+            let is_meet = |vec_placed: &Vec<TableInfo>| {
+                vec_placed
+                    .iter()
+                    .any(|placed| placed.scope.meet(&info.scope))
+            };
+            let is_overlapped = info.level >= num_levels
+                || (info.level != LEVEL_0
+                    && (is_meet(&levels[info.level]) || is_meet(&levels[LEVEL_0])));
+
+            if is_overlapped {
+                report.moved_to_level_0 += 1;
+                levels[LEVEL_0].push(info);
+            } else {
+                levels[info.level].push(info);
+            }
+        }
+        report.tables = levels.iter().map(Vec::len).sum();
+        self.regen_level_0(&mut levels[LEVEL_0])?;
+
+        Ok(levels
+            .into_iter()
+            .enumerate()
+            .filter(|(_, vec_info)| !vec_info.is_empty())
+            .map(|(level, vec_info)| {
+                let meta = TableMeta::fusion(&vec_info.iter().map(|info| info.meta).collect_vec());
+                let vec_scope = vec_info.into_iter().map(|info| info.scope).collect_vec();
+
+                VersionEdit::NewFile((vec_scope, level), 0, meta)
+            })
+            .collect_vec())
+    }
+
+    /// Level 0中的Table以Gen作为新旧顺序，因此由新至旧遍历，对Gen不小于其后Table的Table重新分配较小的Gen
+    ///
+    /// 原属于Level 0的Table位于末尾且Gen有序，因此仅有较旧的、由Level 1-N放置至Level 0的Table会被重新分配，
+    /// 新的Gen不会大于MemTable所使用的WAL，且需要避开已存在的SSTable与WAL(以免被误用为该Table的备份)
+    fn regen_level_0(&self, vec_info: &mut [TableInfo]) -> Result<()> {
+        let sst_path = self.sst_factory.get_path();
+        let wal_path = self.config.path().join(DEFAULT_WAL_PATH);
+        let mut upper_gen = i64::MAX;
+
+        for info in vec_info.iter_mut().rev() {
+            let gen = info.scope.get_gen();
+
+            if gen < upper_gen {
+                upper_gen = gen;
+                continue;
+            }
+            let new_gen = (i64::MIN..upper_gen)
+                .rev()
+                .find(|new_gen| {
+                    !FileExtension::SSTable
+                        .path_with_gen(sst_path, *new_gen)
+                        .exists()
+                        && !FileExtension::Log
+                            .path_with_gen(&wal_path, *new_gen)
+                            .exists()
+                })
+                .ok_or(KernelError::DataEmpty)?;
+            fs::rename(
+                FileExtension::SSTable.path_with_gen(sst_path, gen),
+                FileExtension::SSTable.path_with_gen(sst_path, new_gen),
+            )?;
+            info.scope.set_gen(new_gen);
+            upper_gen = new_gen;
+            info!("[Repairer][SSTable: {}][regen]: {}", gen, new_gen);
+        }
+
+        Ok(())
+    }
+}
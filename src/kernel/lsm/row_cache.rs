@@ -0,0 +1,93 @@
+use crate::kernel::lsm::version::Version;
+use crate::kernel::utils::cache_counter::{CacheCounter, CacheRecorder};
+use crate::kernel::utils::charge_cache::{CachePolicy, ShardingChargeCache};
+use crate::kernel::Result;
+use bytes::Bytes;
+use std::collections::hash_map::RandomState;
+use std::mem::size_of;
+use std::sync::Arc;
+
+pub(crate) const DEFAULT_ROW_CACHE_SHARDING: usize = 16;
+
+/// 点查结果的行缓存
+///
+/// 仅缓存MemTable未命中时Version的查询结果(包括Key不存在)，并记录查询所使用的Version编号
+/// - Version不可变，因此同一编号下的查询结果固定；读取时编号与所用Version不一致即视为失效，
+///   flush、压缩与导入等切换Version的操作都会使旧的结果失效
+/// - set、remove与事务提交时写入的数据位于MemTable中，读取时优先于行缓存，
+///   同时会直接移除对应的Key以尽早释放空间
+pub(crate) struct RowCache {
+    inner: ShardingChargeCache<Bytes, RowEntry>,
+    recorder: Arc<CacheRecorder>,
+}
+
+#[derive(Clone)]
+struct RowEntry {
+    version_num: u64,
+    value: Option<Bytes>,
+    /// 包含Key在内的内存占用估算
+    charge: usize,
+}
+
+impl RowCache {
+    pub(crate) fn new(capacity: usize) -> Result<Self> {
+        let recorder = Arc::new(CacheRecorder::default());
+        let listener = Arc::clone(&recorder);
+
+        Ok(RowCache {
+            inner: ShardingChargeCache::new(
+                capacity,
+                DEFAULT_ROW_CACHE_SHARDING,
+                RandomState::default(),
+                |entry: &RowEntry| entry.charge,
+                CachePolicy::Lru,
+            )?
+            .listener(listener),
+            recorder,
+        })
+    }
+
+    /// 通过行缓存查询Version中的数据，未命中时查询Version并缓存其结果
+    pub(crate) fn query(&self, version: &Version, key: &[u8]) -> Result<Option<Bytes>> {
+        let key = Bytes::copy_from_slice(key);
+
+        if let Some(entry) = self.inner.get(&key) {
+            if entry.version_num == version.version_num {
+                self.recorder.record_access(true);
+                return Ok(entry.value);
+            }
+        }
+        self.recorder.record_access(false);
+
+        let value = version.query(&key)?;
+        let charge =
+            key.len() + value.as_ref().map_or(0, Bytes::len) + size_of::<(Bytes, RowEntry)>();
+        let _ = self.inner.put(
+            key,
+            RowEntry {
+                version_num: version.version_num,
+                value: value.clone(),
+                charge,
+            },
+        );
+
+        Ok(value)
+    }
+
+    /// 移除Key对应的缓存，用于Key被写入时
+    pub(crate) fn invalidate(&self, key: &[u8]) {
+        let _ = self.inner.remove(&Bytes::copy_from_slice(key));
+    }
+
+    pub(crate) fn counter(&self) -> CacheCounter {
+        self.recorder.counter()
+    }
+
+    pub(crate) fn usage(&self) -> usize {
+        self.inner.usage()
+    }
+
+    pub(crate) fn capacity(&self) -> usize {
+        self.inner.capacity()
+    }
+}
@@ -1,14 +1,29 @@
-use crate::kernel::io::IoType;
-use crate::kernel::lsm::compactor::{CompactTask, Compactor};
+use crate::kernel::io::{FileExtension, IoType};
+use crate::kernel::lsm::checkpoint;
+use crate::kernel::lsm::compactor::{CompactTask, CompactionFilter, CompactionPri, Compactor};
+use crate::kernel::lsm::ingest::ExternalTable;
 use crate::kernel::lsm::iterator::full_iter::FullIter;
+use crate::kernel::lsm::iterator::prefix_iter::PrefixIter;
 use crate::kernel::lsm::mem_table::{KeyValue, MemTable, TableInner};
 use crate::kernel::lsm::mvcc::Transaction;
+use crate::kernel::lsm::prefix::PrefixExtractor;
+use crate::kernel::lsm::repair::{RepairReport, Repairer};
+use crate::kernel::lsm::row_cache::RowCache;
 use crate::kernel::lsm::table::ss_table::block;
+pub use crate::kernel::lsm::table::ss_table::block::{BlockCache, PinIndexBlocks};
+pub use crate::kernel::lsm::table::ss_table::filter::{FilterPolicy, FilterStats};
+pub use crate::kernel::lsm::table::ss_table::secondary_cache::SecondaryCache;
 use crate::kernel::lsm::table::TableType;
 use crate::kernel::lsm::trigger::TriggerType;
+use crate::kernel::lsm::verify;
+use crate::kernel::lsm::verify::VerifyReport;
 use crate::kernel::lsm::version;
+use crate::kernel::lsm::version::cleaner::Cleaner;
 use crate::kernel::lsm::version::status::VersionStatus;
-use crate::kernel::lsm::version::Version;
+use crate::kernel::lsm::version::{Version, DEFAULT_SS_TABLE_PATH};
+use crate::kernel::utils::cache_counter::CacheCounter;
+use crate::kernel::utils::charge_cache::CachePolicy;
+use crate::kernel::utils::rate_limiter::{RateLimiter, RateLimiterStats};
 use crate::kernel::Result;
 use crate::kernel::{lock_or_time_out, Storage, DEFAULT_LOCK_FILE};
 use crate::KernelError;
@@ -19,13 +34,13 @@ use fslock::LockFile;
 use parking_lot::MutexGuard;
 use skiplist::SkipMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicI64, Ordering};
 use std::sync::Arc;
 use tokio::sync::mpsc::error::TrySendError;
 use tokio::sync::mpsc::{channel, Sender};
 use tokio::sync::oneshot;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
 pub(crate) const BANNER: &str = "
 █████   ████  ███            ██████████   ███████████
@@ -51,9 +66,31 @@ pub(crate) const DEFAULT_MAJOR_SELECT_FILE_SIZE: usize = 3;
 
 pub(crate) const DEFAULT_LEVEL_SST_MAGNIFICATION: usize = 5;
 
-pub(crate) const DEFAULT_DESIRED_ERROR_PROB: f64 = 0.05;
+pub(crate) const DEFAULT_MAX_BYTES_FOR_LEVEL_BASE: u64 = 100 * 1024 * 1024;
 
-pub(crate) const DEFAULT_BLOCK_CACHE_SIZE: usize = 3200;
+pub(crate) const DEFAULT_NUM_LEVELS: usize = 7;
+
+pub(crate) const MIN_NUM_LEVELS: usize = 2;
+
+pub(crate) const MAX_NUM_LEVELS: usize = 10;
+
+pub(crate) const DEFAULT_COMPACTION_PRI: CompactionPri = CompactionPri::MinOverlappingRatio;
+
+pub(crate) const DEFAULT_TOMBSTONE_COMPACTION_PERCENT: usize = 50;
+
+pub(crate) const DEFAULT_BITS_PER_KEY: usize = 10;
+
+pub(crate) const DEFAULT_FILTER_POLICY: FilterPolicy = FilterPolicy::Bloom(DEFAULT_BITS_PER_KEY);
+
+pub(crate) const DEFAULT_BLOCK_CACHE_SIZE: usize = 16 * 1024 * 1024;
+
+pub(crate) const DEFAULT_BLOCK_CACHE_POLICY: CachePolicy = CachePolicy::Lru;
+
+pub(crate) const DEFAULT_PIN_INDEX_BLOCKS: PinIndexBlocks = PinIndexBlocks::None;
+
+pub(crate) const DEFAULT_ROW_CACHE_SIZE: usize = 0;
+
+pub(crate) const DEFAULT_READAHEAD_SIZE: usize = 256 * 1024;
 
 pub(crate) const DEFAULT_TABLE_CACHE_SIZE: usize = 1024;
 
@@ -61,6 +98,8 @@ pub(crate) const DEFAULT_WAL_THRESHOLD: usize = 20;
 
 pub(crate) const DEFAULT_WAL_IO_TYPE: IoType = IoType::Buf;
 
+pub(crate) const READ_ONLY_MESSAGE: &str = "the store is opened in read-only mode";
+
 static SEQ_COUNT: AtomicI64 = AtomicI64::new(1);
 
 static GEN_BUF: AtomicI64 = AtomicI64::new(0);
@@ -70,8 +109,8 @@ static GEN_BUF: AtomicI64 = AtomicI64::new(0);
 pub struct LsmStore {
     inner: Arc<StoreInner>,
     /// 多进程文件锁
-    /// 避免多进程进行数据读写
-    lock_file: LockFile,
+    /// 避免多进程进行数据读写，只读模式下不持有
+    lock_file: Option<LockFile>,
     /// Compactor 通信器
     compactor_tx: Sender<CompactTask>,
 }
@@ -85,6 +124,8 @@ pub(crate) struct StoreInner {
     pub(crate) ver_status: VersionStatus,
     /// LSM全局参数配置
     pub(crate) config: Config,
+    /// 点查结果的行缓存，row_cache_size为0时不启用
+    pub(crate) row_cache: Option<RowCache>,
 }
 
 impl StoreInner {
@@ -94,13 +135,48 @@ impl StoreInner {
         // 初始化wal日志
         let ver_status =
             VersionStatus::load_with_path(config.clone(), mem_table.log_loader_clone())?;
+        if !config.read_only {
+            ver_status
+                .recover_unflushed_logs(&config, mem_table.log_gen())
+                .await?;
+
+            if ver_status.is_manifest_torn() {
+                warn!("[LsmStore][open]: Skip cleaning obsolete files since the manifest is torn");
+            } else {
+                Cleaner::clean_obsolete_files(
+                    &config,
+                    ver_status.current().await.as_ref(),
+                    mem_table.log_gen(),
+                )?;
+            }
+        }
+
+        let row_cache = (config.row_cache_size > 0)
+            .then(|| RowCache::new(config.row_cache_size))
+            .transpose()?;
 
         Ok(StoreInner {
             mem_table,
             ver_status,
             config,
+            row_cache,
         })
     }
+
+    /// 查询Version中的数据，启用行缓存时优先从中读取
+    pub(crate) fn query_version(&self, version: &Version, key: &[u8]) -> Result<Option<Bytes>> {
+        match &self.row_cache {
+            Some(row_cache) => row_cache.query(version, key),
+            None => version.query(key),
+        }
+    }
+
+    /// Key被写入MemTable后使行缓存中对应的数据失效
+    pub(crate) fn invalidate_row(&self, key: &[u8]) {
+        if let Some(row_cache) = &self.row_cache {
+            row_cache.invalidate(key);
+        }
+    }
 }
 
 #[async_trait]
@@ -120,6 +196,7 @@ impl Storage for LsmStore {
 
     #[inline]
     async fn flush(&self) -> Result<()> {
+        self.check_writable()?;
         let (tx, rx) = oneshot::channel();
 
         self.compactor_tx.send(CompactTask::Flush(Some(tx))).await?;
@@ -141,15 +218,13 @@ impl Storage for LsmStore {
             return Ok(Some(value));
         }
 
-        if let Some(value) = self.current_version().await.query(key)? {
-            return Ok(Some(value));
-        }
-
-        Ok(None)
+        let version = self.current_version().await;
+        self.inner.query_version(&version, key)
     }
 
     #[inline]
     async fn remove(&self, key: &[u8]) -> Result<()> {
+        self.check_writable()?;
         match self.get(key).await? {
             Some(_) => {
                 self.append_cmd_data((Bytes::copy_from_slice(key), None))
@@ -179,14 +254,23 @@ impl Drop for LsmStore {
     #[inline]
     #[allow(clippy::expect_used)]
     fn drop(&mut self) {
-        self.lock_file.unlock().expect("LockFile unlock failed!");
+        // 归还固定的Index Block所占用的Block缓存容量，避免共享缓存时影响其余Store
+        self.inner.ver_status.loader().unpin_all();
+        if let Some(lock_file) = &mut self.lock_file {
+            lock_file.unlock().expect("LockFile unlock failed!");
+        }
     }
 }
 
 impl LsmStore {
     /// 追加数据
     async fn append_cmd_data(&self, data: KeyValue) -> Result<()> {
-        if self.mem_table().insert_data(data)? {
+        self.check_writable()?;
+        let key = data.0.clone();
+        let is_full = self.mem_table().insert_data(data)?;
+
+        self.inner.invalidate_row(&key);
+        if is_full {
             if let Err(TrySendError::Closed(_)) =
                 self.compactor_tx.try_send(CompactTask::Flush(None))
             {
@@ -213,28 +297,217 @@ impl LsmStore {
         let (task_tx, mut task_rx) = channel(1);
 
         let _ignore = tokio::spawn(async move {
-            while let Some(CompactTask::Flush(option_tx)) = task_rx.recv().await {
-                if let Err(err) = compactor.check_then_compaction(option_tx).await {
-                    error!("[Compactor][compaction][error happen]: {:?}", err);
+            while let Some(task) = task_rx.recv().await {
+                match task {
+                    CompactTask::Flush(option_tx) => {
+                        if let Err(err) = compactor.check_then_compaction(option_tx).await {
+                            error!("[Compactor][compaction][error happen]: {:?}", err);
+                        }
+                    }
+                    CompactTask::Ingest(vec_table, tx) => {
+                        if tx.send(compactor.ingest(vec_table).await).is_err() {
+                            error!("[Compactor][ingest][error happen]: receiver dropped");
+                        }
+                    }
                 }
             }
         });
 
         Ok(LsmStore {
             inner,
-            lock_file,
+            lock_file: Some(lock_file),
+            compactor_tx: task_tx,
+        })
+    }
+
+    /// 以只读模式打开LsmStore
+    ///
+    /// 用于在写入进程运行的同时读取其数据的快照副本或检查点
+    /// - 不获取文件锁，不启动Compactor，也不清理冗余文件
+    /// - WAL仅重放至内存中，不会创建新的WAL与Manifest
+    /// - 写入操作均返回`KernelError::NotSupport`
+    ///
+    /// Tips: 打开后对目录的修改不可见，直接读取正在写入的目录时可能因文件被压缩删除而读取失败
+    #[inline]
+    pub async fn open_read_only(mut config: Config) -> Result<Self> {
+        if !config.dir_path.exists() {
+            return Err(KernelError::FileNotFound);
+        }
+        Gen::init();
+        config.read_only = true;
+        let inner = Arc::new(StoreInner::new(config).await?);
+        // 不存在Compactor，因此直接关闭接收端
+        let (task_tx, _) = channel(1);
+
+        Ok(LsmStore {
+            inner,
+            lock_file: None,
             compactor_tx: task_tx,
         })
     }
 
+    /// 离线修复：以`ss_table`目录中的SSTable重建Version
+    ///
+    /// 用于Version丢失或损坏时，调用时该路径不能被其他LsmStore打开
+    #[inline]
+    pub async fn repair(path: impl Into<PathBuf> + Send) -> Result<RepairReport> {
+        LsmStore::repair_with_config(Config::new(path.into())).await
+    }
+
+    /// 以指定的Config进行离线修复，Level层数等需与原先打开时一致
+    #[inline]
+    pub async fn repair_with_config(config: Config) -> Result<RepairReport> {
+        Gen::init();
+        fs::create_dir_all(&config.dir_path)?;
+        let _lock_file = lock_or_time_out(&config.path().join(DEFAULT_LOCK_FILE)).await?;
+
+        Repairer::new(config)?.run()
+    }
+
+    /// 在线创建检查点，dest_dir需不存在
+    ///
+    /// 会先进行flush使此前写入的数据均持久化至Table中，再将Version所引用的文件硬链接至dest_dir，
+    /// 期间持有该Version以避免其文件被删除，得到的检查点可直接通过`LsmStore::open`打开
+    #[inline]
+    pub async fn checkpoint(&self, dest_dir: impl Into<PathBuf> + Send) -> Result<()> {
+        self.flush().await?;
+        let version = self.current_version().await;
+
+        checkpoint::create_checkpoint(&self.inner.config, &version, &dest_dir.into())
+    }
+
+    /// 导入由`SstFileWriter`构建的外部SSTable
+    ///
+    /// 文件会被复制至Store中并校验，原文件不会被修改；
+    /// 每个SSTable会被放置至不与其Key范围相交的最底层，与MemTable中的数据相交时会先进行flush，
+    /// 因此导入的数据总是比此前写入的数据更新。导入的SSTable之间不允许相交
+    #[inline]
+    pub async fn ingest_external_files<P: AsRef<Path> + Sync>(&self, paths: &[P]) -> Result<()> {
+        self.check_writable()?;
+        let mut vec_table = Vec::with_capacity(paths.len());
+
+        for path in paths {
+            match ExternalTable::prepare(self.config(), path.as_ref()) {
+                Ok(table) => vec_table.push(table),
+                Err(err) => {
+                    // 回滚已复制的SSTable，失败时仅记录日志并返回导入失败的原因
+                    for table in vec_table {
+                        let path = FileExtension::SSTable.path_with_gen(
+                            &self.config().path().join(DEFAULT_SS_TABLE_PATH),
+                            table.gen(),
+                        );
+                        if let Err(remove_err) = fs::remove_file(&path) {
+                            warn!(
+                                "[LsmStore][ingest_external_files][rollback error]: {:?} {:?}",
+                                path, remove_err
+                            );
+                        }
+                    }
+                    return Err(err);
+                }
+            }
+        }
+        let (tx, rx) = oneshot::channel();
+        self.compactor_tx
+            .send(CompactTask::Ingest(vec_table, tx))
+            .await?;
+
+        rx.await.map_err(|_| KernelError::ChannelClose)?
+    }
+
+    /// 对当前Version中的所有Table进行完整性校验
+    ///
+    /// 会完整读取所有Table的数据，耗时与数据量成正比
+    #[inline]
+    pub async fn verify(&self) -> Result<VerifyReport> {
+        let version = self.current_version().await;
+
+        verify::verify_version(&self.inner.config, &version)
+    }
+
     fn mem_table(&self) -> &MemTable {
         &self.inner.mem_table
     }
 
+    fn check_writable(&self) -> Result<()> {
+        if self.inner.config.read_only {
+            return Err(KernelError::NotSupport(READ_ONLY_MESSAGE));
+        }
+        Ok(())
+    }
+
+    pub(crate) fn config(&self) -> &Config {
+        &self.inner.config
+    }
+
     pub(crate) async fn current_version(&self) -> Arc<Version> {
         self.inner.ver_status.current().await
     }
 
+    /// 获取IO限速器的运行统计，未设置限速器时返回None
+    #[inline]
+    pub fn rate_limiter_stats(&self) -> Option<RateLimiterStats> {
+        self.inner
+            .config
+            .rate_limiter
+            .as_ref()
+            .map(|rate_limiter| rate_limiter.stats())
+    }
+
+    /// 获取Table缓存与Block缓存的运行统计
+    ///
+    /// 可依据命中率与驱逐数调整`table_cache_size`与`block_cache_size`
+    #[inline]
+    pub fn cache_stats(&self) -> CacheStats {
+        let loader = self.inner.ver_status.loader();
+        let block_cache = loader.block_cache();
+        let row_cache = self.inner.row_cache.as_ref();
+
+        CacheStats {
+            table_cache: loader.table_cache_counter(),
+            table_cache_usage: loader.table_cache_len() as u64,
+            table_cache_capacity: self.inner.config.table_cache_size as u64,
+            index_block: block_cache.index_counter(),
+            data_block: block_cache.data_counter(),
+            block_cache_usage: block_cache.usage() as u64,
+            block_cache_capacity: block_cache.capacity() as u64,
+            index_block_pinned_usage: loader.pinned_index_usage() as u64,
+            filter_usage: loader.filter_usage() as u64,
+            row_cache: row_cache.map(RowCache::counter).unwrap_or_default(),
+            row_cache_usage: row_cache.map_or(0, |row_cache| row_cache.usage() as u64),
+            row_cache_capacity: row_cache.map_or(0, |row_cache| row_cache.capacity() as u64),
+        }
+    }
+
+    /// 获取各Level的过滤器统计，以Level为下标
+    ///
+    /// useful占checked的比例越高则过滤器越有效，false_positive较多时可增大bits_per_key，
+    /// 而useful极少的Level(如点查总是命中的最底层)可减小bits_per_key或不使用过滤器以节省内存
+    #[inline]
+    pub async fn filter_stats(&self) -> Vec<FilterStats> {
+        let loader = self.inner.ver_status.loader();
+        let filter_usage = loader.filter_usage_by_level(self.current_version().await.as_ref());
+
+        self.inner
+            .config
+            .filter_policies
+            .iter()
+            .take(self.inner.config.num_levels)
+            .enumerate()
+            .map(|(level, policy)| {
+                let (checked, useful, false_positive) = loader.filter_recorder().counter(level);
+
+                FilterStats {
+                    policy: *policy,
+                    checked,
+                    useful,
+                    false_positive,
+                    filter_usage: filter_usage.get(level).copied().unwrap_or(0) as u64,
+                }
+            })
+            .collect()
+    }
+
     /// 创建事务
     #[inline]
     pub async fn new_transaction(&self) -> Transaction {
@@ -257,6 +530,7 @@ impl LsmStore {
         Ok(Guard {
             _inner: self.mem_table().inner_with_lock(),
             _version: version,
+            prefix_extractor: self.config().prefix_extractor.clone(),
         })
     }
 }
@@ -264,12 +538,121 @@ impl LsmStore {
 pub struct Guard<'a> {
     _inner: MutexGuard<'a, TableInner>,
     _version: Arc<Version>,
+    prefix_extractor: Option<Arc<dyn PrefixExtractor>>,
 }
 
 impl<'a> Guard<'a> {
     #[inline]
     pub fn iter(&'a self) -> Result<FullIter<'a>> {
-        FullIter::new(&self._inner, &self._version)
+        self.iter_with_options(ReadOptions::default())
+    }
+
+    #[inline]
+    pub fn iter_with_options(&'a self, options: ReadOptions) -> Result<FullIter<'a>> {
+        FullIter::new(&self._inner, &self._version, options)
+    }
+
+    /// 迭代所有以prefix为前缀的键值对
+    ///
+    /// 设置了Config::prefix_extractor时，将跳过前缀过滤器排除了该前缀的SSTable
+    #[inline]
+    pub fn prefix_iter(&'a self, prefix: &[u8]) -> Result<PrefixIter<'a>> {
+        self.prefix_iter_with_options(prefix, ReadOptions::default())
+    }
+
+    #[inline]
+    pub fn prefix_iter_with_options(
+        &'a self,
+        prefix: &[u8],
+        options: ReadOptions,
+    ) -> Result<PrefixIter<'a>> {
+        FullIter::new_with_prefix(
+            &self._inner,
+            &self._version,
+            self.prefix_extractor.as_deref(),
+            prefix,
+            options,
+        )
+    }
+}
+
+/// 缓存运行统计
+///
+/// Tips: Block缓存被多个Store共享时，其统计为所有Store的合计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub table_cache: CacheCounter,
+    /// Table缓存中的Table数量
+    pub table_cache_usage: u64,
+    /// Table缓存的容量，单位为Table数量
+    pub table_cache_capacity: u64,
+    pub index_block: CacheCounter,
+    pub data_block: CacheCounter,
+    /// Block缓存的内存占用，单位为B
+    pub block_cache_usage: u64,
+    /// Block缓存的容量，单位为B
+    pub block_cache_capacity: u64,
+    /// 该Store固定于Block缓存中的Index Block的内存占用(已计入block_cache_usage)，单位为B
+    pub index_block_pinned_usage: u64,
+    /// Table缓存中各SSTable过滤器的内存占用(不计入Block缓存)，单位为B
+    pub filter_usage: u64,
+    /// 未启用行缓存时均为0
+    pub row_cache: CacheCounter,
+    /// 行缓存的内存占用，单位为B
+    pub row_cache_usage: u64,
+    /// 行缓存的容量，单位为B
+    pub row_cache_capacity: u64,
+}
+
+/// 读取选项
+#[derive(Debug, Clone, Copy)]
+pub struct ReadOptions {
+    /// 读取的Data Block是否放入Block缓存中
+    pub(crate) fill_cache: bool,
+    /// 顺序读取时预读窗口的上限，单位为B，为0时关闭预读
+    pub(crate) readahead_size: usize,
+    /// 是否延续之前的顺序读取，为true时迭代器会立即开始预读
+    ///
+    /// 仅由LevelIter在顺序切换至下一个Table时设置
+    pub(crate) sequential: bool,
+}
+
+impl Default for ReadOptions {
+    #[inline]
+    fn default() -> Self {
+        ReadOptions {
+            fill_cache: true,
+            readahead_size: DEFAULT_READAHEAD_SIZE,
+            sequential: false,
+        }
+    }
+}
+
+impl ReadOptions {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 设置为false时读取的Block不会放入缓存中，用于全量扫描等一次性的大量读取，
+    /// 避免热点数据被挤出缓存，已缓存的Block依旧会被复用
+    #[inline]
+    pub fn fill_cache(mut self, fill_cache: bool) -> Self {
+        self.fill_cache = fill_cache;
+        self
+    }
+
+    /// 迭代器检测到顺序读取时，以一次IO预读之后的多个Data Block，
+    /// 预读窗口逐渐增长至readahead_size，为0时关闭预读
+    #[inline]
+    pub fn readahead_size(mut self, readahead_size: usize) -> Self {
+        self.readahead_size = readahead_size;
+        self
+    }
+
+    pub(crate) fn sequential(mut self, sequential: bool) -> Self {
+        self.sequential = sequential;
+        self
     }
 }
 
@@ -277,29 +660,55 @@ impl<'a> Guard<'a> {
 pub struct Config {
     /// 数据目录地址
     pub(crate) dir_path: PathBuf,
-    /// 各层级对应Table类型
+    /// 各层级对应Table类型，长度固定为MAX_NUM_LEVELS，仅前num_levels个生效
     /// Tips: SkipTable仅可使用于Level 0之中，否则会因为Level 0外不支持WAL恢复而导致停机后丢失数据
-    pub(crate) level_table_type: [TableType; 7],
+    pub(crate) level_table_type: Vec<TableType>,
+    /// Level的层数，范围为[MIN_NUM_LEVELS, MAX_NUM_LEVELS]
+    /// 数据量较大时可适当增加层数，嵌入式等数据量较小的场景则可减少层数
+    pub(crate) num_levels: usize,
     /// WAL数量阈值
     pub(crate) wal_threshold: usize,
     /// SSTable文件大小
     pub(crate) sst_file_size: usize,
     /// Minor触发器与阈值
     pub(crate) minor_trigger_with_threshold: (TriggerType, usize),
-    /// Major压缩触发阈值
+    /// Level 0的Major压缩触发阈值(SSTable数量)
     pub(crate) major_threshold_with_sst_size: usize,
     /// Major压缩选定文件数
     /// Major压缩时通过选定个别SSTable(即该配置项)进行下一级的SSTable选定，
     /// 并将确定范围的下一级SSTable再次对当前等级的SSTable进行范围判定，
     /// 找到最合理的上下级数据范围并压缩
     pub(crate) major_select_file_size: usize,
-    /// 每级SSTable数量倍率
+    /// 每级数据大小倍率
     pub(crate) level_sst_magnification: usize,
-    /// 布隆过滤器 期望的错误概率
-    pub(crate) desired_error_prob: f64,
-    /// Block数据块缓存的数量
-    /// 由于使用ShardingCache作为并行，以16为单位
+    /// Level 1(动态时则为Base Level)的目标大小，单位为B
+    /// Level 1及以下的Level在数据大小超出目标大小时触发Major压缩
+    pub(crate) max_bytes_for_level_base: u64,
+    /// 是否依据最底层的实际大小动态推算各Level的目标大小
+    pub(crate) level_compaction_dynamic_level_bytes: bool,
+    /// Major压缩时Level 1及以下Level的Table选取策略
+    pub(crate) compaction_pri: CompactionPri,
+    /// 删除标记占比触发压缩的百分比阈值
+    /// 当Level中存在删除标记占比达到该阈值的Table时，即使Table数量未达到阈值也会优先对其进行压缩
+    /// 为0时则关闭该触发
+    pub(crate) tombstone_compaction_percent: usize,
+    /// 各层级SSTable所使用的Key过滤器
+    pub(crate) filter_policies: Vec<FilterPolicy>,
+    /// Key前缀提取器，设置后SSTable会额外记录前缀过滤器以供前缀迭代时跳过SSTable
+    pub(crate) prefix_extractor: Option<Arc<dyn PrefixExtractor>>,
+    /// Block数据块缓存的容量，单位为B
+    /// 以Block解码后的内存占用计算，设置了共享的block_cache时无效
     pub(crate) block_cache_size: usize,
+    /// 多个LsmStore之间共享的Block缓存
+    pub(crate) block_cache: Option<Arc<BlockCache>>,
+    /// Block缓存的驱逐策略
+    pub(crate) block_cache_policy: CachePolicy,
+    /// 基于本地文件的二级Block缓存
+    pub(crate) secondary_cache: Option<Arc<SecondaryCache>>,
+    /// 常驻于Block缓存中的Index Block范围
+    pub(crate) pin_index_blocks: PinIndexBlocks,
+    /// 点查结果的行缓存容量，单位为B，为0时不启用
+    pub(crate) row_cache_size: usize,
     /// 用于缓存SSTable
     pub(crate) table_cache_size: usize,
     /// WAL写入类型
@@ -314,6 +723,14 @@ pub struct Config {
     pub(crate) index_restart_interval: usize,
     /// VersionLog触发快照化的运行时计量阈值
     pub(crate) ver_log_snapshot_threshold: usize,
+    /// Major压缩时使用的压缩过滤器
+    pub(crate) compaction_filter: Option<Arc<dyn CompactionFilter>>,
+    /// flush与压缩时使用的IO限速器
+    pub(crate) rate_limiter: Option<Arc<RateLimiter>>,
+    /// 启动时清理的冗余文件的隔离目录，为None时直接删除
+    pub(crate) quarantine_dir: Option<PathBuf>,
+    /// 是否以只读模式打开，仅由`LsmStore::open_read_only`设置
+    pub(crate) read_only: bool,
 }
 
 impl Config {
@@ -321,7 +738,8 @@ impl Config {
     pub fn new(path: impl Into<PathBuf> + Send) -> Config {
         Config {
             dir_path: path.into(),
-            level_table_type: [TableType::SortedString; 7],
+            level_table_type: vec![TableType::SortedString; MAX_NUM_LEVELS],
+            num_levels: DEFAULT_NUM_LEVELS,
             wal_threshold: DEFAULT_WAL_THRESHOLD,
             sst_file_size: DEFAULT_SST_FILE_SIZE,
             minor_trigger_with_threshold: (
@@ -331,14 +749,28 @@ impl Config {
             major_threshold_with_sst_size: DEFAULT_MAJOR_THRESHOLD_WITH_SST_SIZE,
             major_select_file_size: DEFAULT_MAJOR_SELECT_FILE_SIZE,
             level_sst_magnification: DEFAULT_LEVEL_SST_MAGNIFICATION,
-            desired_error_prob: DEFAULT_DESIRED_ERROR_PROB,
+            max_bytes_for_level_base: DEFAULT_MAX_BYTES_FOR_LEVEL_BASE,
+            level_compaction_dynamic_level_bytes: false,
+            compaction_pri: DEFAULT_COMPACTION_PRI,
+            tombstone_compaction_percent: DEFAULT_TOMBSTONE_COMPACTION_PERCENT,
+            filter_policies: vec![DEFAULT_FILTER_POLICY; MAX_NUM_LEVELS],
+            prefix_extractor: None,
             block_cache_size: DEFAULT_BLOCK_CACHE_SIZE,
+            block_cache: None,
+            block_cache_policy: DEFAULT_BLOCK_CACHE_POLICY,
+            secondary_cache: None,
+            pin_index_blocks: DEFAULT_PIN_INDEX_BLOCKS,
+            row_cache_size: DEFAULT_ROW_CACHE_SIZE,
             table_cache_size: DEFAULT_TABLE_CACHE_SIZE,
             wal_io_type: DEFAULT_WAL_IO_TYPE,
             block_size: block::DEFAULT_BLOCK_SIZE,
             data_restart_interval: block::DEFAULT_DATA_RESTART_INTERVAL,
             index_restart_interval: block::DEFAULT_INDEX_RESTART_INTERVAL,
             ver_log_snapshot_threshold: version::DEFAULT_VERSION_LOG_THRESHOLD,
+            compaction_filter: None,
+            rate_limiter: None,
+            quarantine_dir: None,
+            read_only: false,
         }
     }
 
@@ -358,9 +790,23 @@ impl Config {
         self
     }
 
+    /// 设置Level的层数，超出[2, 10]时会被限制在该范围内
+    ///
+    /// Tips: 已有数据的Level层数大于该值时将无法打开
+    #[inline]
+    pub fn num_levels(mut self, num_levels: usize) -> Self {
+        self.num_levels = num_levels.clamp(MIN_NUM_LEVELS, MAX_NUM_LEVELS);
+        self
+    }
+
+    /// 设置指定层级的Table类型，与num_levels的设置顺序无关
+    ///
+    /// Tips: level不小于num_levels时该设置不生效，不小于MAX_NUM_LEVELS时会被忽略
     #[inline]
     pub fn level_table_type(mut self, level: usize, table_type: TableType) -> Self {
-        self.level_table_type[level] = table_type;
+        if let Some(level_table_type) = self.level_table_type.get_mut(level) {
+            *level_table_type = table_type;
+        }
         self
     }
 
@@ -423,17 +869,106 @@ impl Config {
     }
 
     #[inline]
-    pub fn desired_error_prob(mut self, desired_error_prob: f64) -> Self {
-        self.desired_error_prob = desired_error_prob;
+    pub fn max_bytes_for_level_base(mut self, max_bytes_for_level_base: u64) -> Self {
+        self.max_bytes_for_level_base = max_bytes_for_level_base;
+        self
+    }
+
+    #[inline]
+    pub fn enable_level_compaction_dynamic_level_bytes(mut self) -> Self {
+        self.level_compaction_dynamic_level_bytes = true;
+        self
+    }
+
+    #[inline]
+    pub fn compaction_pri(mut self, compaction_pri: CompactionPri) -> Self {
+        self.compaction_pri = compaction_pri;
+        self
+    }
+
+    #[inline]
+    pub fn tombstone_compaction_percent(mut self, tombstone_compaction_percent: usize) -> Self {
+        self.tombstone_compaction_percent = tombstone_compaction_percent;
+        self
+    }
+
+    /// 设置所有层级SSTable所使用的Key过滤器，仅对之后写入的SSTable生效
+    #[inline]
+    pub fn filter_policy(mut self, policy: FilterPolicy) -> Self {
+        self.filter_policies.fill(policy);
+        self
+    }
+
+    /// 设置指定层级SSTable所使用的Key过滤器，与num_levels的设置顺序无关
+    ///
+    /// 可依据`LsmStore::filter_stats`中各Level的过滤效果在内存与IO之间取舍
+    ///
+    /// Tips: level不小于num_levels时该设置不生效，不小于MAX_NUM_LEVELS时会被忽略
+    #[inline]
+    pub fn level_filter_policy(mut self, level: usize, policy: FilterPolicy) -> Self {
+        if let Some(filter_policy) = self.filter_policies.get_mut(level) {
+            *filter_policy = policy;
+        }
+        self
+    }
+
+    /// 设置Key前缀提取器，仅对之后写入的SSTable生效
+    ///
+    /// 前缀迭代时将跳过前缀过滤器排除了该前缀的SSTable
+    #[inline]
+    pub fn prefix_extractor(mut self, extractor: impl PrefixExtractor + 'static) -> Self {
+        self.prefix_extractor = Some(Arc::new(extractor));
         self
     }
 
+    /// Block缓存的容量，单位为B
     #[inline]
     pub fn block_cache_size(mut self, cache_size: usize) -> Self {
         self.block_cache_size = cache_size;
         self
     }
 
+    /// Block缓存的驱逐策略，设置了共享的block_cache时无效
+    #[inline]
+    pub fn block_cache_policy(mut self, policy: CachePolicy) -> Self {
+        self.block_cache_policy = policy;
+        self
+    }
+
+    /// 使用基于本地文件的二级Block缓存，从Block缓存中被驱逐的Block会写入其中，
+    /// 设置了共享的block_cache时无效，此时应通过`BlockCache::with_secondary_cache`创建共享的缓存
+    #[inline]
+    pub fn secondary_cache(mut self, secondary_cache: Arc<SecondaryCache>) -> Self {
+        self.secondary_cache = Some(secondary_cache);
+        self
+    }
+
+    /// 将Index Block固定于Block缓存中，直至SSTable被删除或Store关闭
+    ///
+    /// 固定的Index Block计入Block缓存的容量，但不会被驱逐
+    #[inline]
+    pub fn pin_index_blocks(mut self, pin_index_blocks: PinIndexBlocks) -> Self {
+        self.pin_index_blocks = pin_index_blocks;
+        self
+    }
+
+    /// 使用共享的Block缓存，使同一进程中的多个LsmStore受同一个内存上限约束
+    #[inline]
+    pub fn block_cache(mut self, block_cache: Arc<BlockCache>) -> Self {
+        self.block_cache = Some(block_cache);
+        self
+    }
+
+    /// 点查结果的行缓存容量，单位为B，默认为0即不启用
+    ///
+    /// 行缓存独立于Block缓存，适用于少量热点Key被频繁读取的场景，
+    /// flush与压缩会使其中的数据失效
+    #[inline]
+    pub fn row_cache_size(mut self, cache_size: usize) -> Self {
+        self.row_cache_size = cache_size;
+        self
+    }
+
     #[inline]
     pub fn table_cache_size(mut self, cache_size: usize) -> Self {
         self.table_cache_size = cache_size;
@@ -451,6 +986,38 @@ impl Config {
         self.ver_log_snapshot_threshold = ver_log_snapshot_threshold;
         self
     }
+
+    /// 限制flush与压缩时的IO速率，单位为B/s
+    ///
+    /// auto_tuned为true时将根据压缩债务在速率上限之下自动调节
+    #[inline]
+    pub fn rate_limiter(mut self, bytes_per_sec: u64, auto_tuned: bool) -> Self {
+        self.rate_limiter = Some(Arc::new(RateLimiter::new(bytes_per_sec, auto_tuned)));
+        self
+    }
+
+    /// 使用已有的限速器，可用于在多个LsmStore之间共享IO速率
+    #[inline]
+    pub fn shared_rate_limiter(mut self, rate_limiter: Arc<RateLimiter>) -> Self {
+        self.rate_limiter = Some(rate_limiter);
+        self
+    }
+
+    /// Major压缩时对每个键值对进行过滤
+    ///
+    /// 设置后压缩不再进行Trivial Move，以保证每个Table在向下层移动时都会经过过滤器
+    #[inline]
+    pub fn compaction_filter(mut self, filter: impl CompactionFilter + 'static) -> Self {
+        self.compaction_filter = Some(Arc::new(filter));
+        self
+    }
+
+    /// 启动时将未被Version引用的冗余文件移动至该目录，而非直接删除
+    #[inline]
+    pub fn quarantine_dir(mut self, quarantine_dir: impl Into<PathBuf>) -> Self {
+        self.quarantine_dir = Some(quarantine_dir.into());
+        self
+    }
 }
 
 /// 插入时Sequence id生成器
@@ -483,10 +1050,30 @@ impl Gen {
 
 #[cfg(test)]
 mod tests {
-    use crate::kernel::lsm::storage::{Config, Gen, LsmStore, Sequence};
-    use crate::kernel::{Result, Storage};
+    use crate::kernel::io::{FileExtension, IoType};
+    use crate::kernel::lsm::compactor::{CompactionDecision, CompactionFilter, LEVEL_0};
+    use crate::kernel::lsm::ingest::SstFileWriter;
+    use crate::kernel::lsm::iterator::Iter;
+    use crate::kernel::lsm::mem_table::DEFAULT_WAL_PATH;
+    use crate::kernel::lsm::prefix::FixedPrefix;
+    use crate::kernel::lsm::repair::DEFAULT_LOST_PATH;
+    use crate::kernel::lsm::storage::{
+        BlockCache, Config, FilterPolicy, Gen, LsmStore, PinIndexBlocks, ReadOptions,
+        SecondaryCache, Sequence, MAX_NUM_LEVELS,
+    };
+    use crate::kernel::lsm::table::TableType;
+    use crate::kernel::lsm::verify::VerifyIssue;
+    use crate::kernel::lsm::version::{DEFAULT_SS_TABLE_PATH, DEFAULT_VERSION_PATH};
+    use crate::kernel::utils::cache_counter::CacheCounter;
+    use crate::kernel::utils::charge_cache::CachePolicy;
+    use crate::kernel::{sorted_gen_list, Result, Storage};
+    use crate::KernelError;
     use bytes::Bytes;
     use itertools::Itertools;
+    use rand::Rng;
+    use std::fs::{self, OpenOptions};
+    use std::io::Write;
+    use std::sync::Arc;
     use std::thread::sleep;
     use std::time::{Duration, Instant};
     use tempfile::TempDir;
@@ -568,4 +1155,1228 @@ mod tests {
             Ok(())
         })
     }
+
+    #[test]
+    fn test_lsm_trivial_move() -> Result<()> {
+        let temp_dir = TempDir::new().expect("unable to create temporary working directory");
+
+        tokio_test::block_on(async move {
+            let config =
+                Config::new(temp_dir.path().to_str().unwrap()).major_threshold_with_sst_size(2);
+            let kv_store = LsmStore::open_with_config(config).await?;
+
+            // 两批键值范围互不重叠的数据分别生成Level 0的SSTable
+            for range in [0_u32..100, 100..200] {
+                for i in range {
+                    let key = Bytes::from(i.to_be_bytes().to_vec());
+                    kv_store.set(&key, key.clone()).await?;
+                }
+                kv_store.flush().await?;
+            }
+
+            let version = kv_store.current_version().await;
+            assert!(version.level_slice[0].is_empty());
+            assert_eq!(version.level_slice[1].len(), 2);
+            // 被移动的SSTable并未重写，因此Level 1中应按Key顺序保留原有的gen
+            assert!(version.level_slice[1][0].get_gen() < version.level_slice[1][1].get_gen());
+            assert!(version.level_slice[1][0].end < version.level_slice[1][1].start);
+            drop(version);
+
+            for i in 0_u32..200 {
+                let key = Bytes::from(i.to_be_bytes().to_vec());
+                assert_eq!(kv_store.get(&key).await?, Some(key));
+            }
+            // 过滤器的内存占用以Version中的Level进行统计
+            let stats = kv_store.filter_stats().await;
+            assert_eq!(stats[0].filter_usage, 0);
+            assert!(stats[1].filter_usage > 0);
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_lsm_remove_after_compaction() -> Result<()> {
+        let temp_dir = TempDir::new().expect("unable to create temporary working directory");
+
+        tokio_test::block_on(async move {
+            // 关闭删除标记占比触发的压缩，使删除标记保留于Level 0
+            let config = Config::new(temp_dir.path().to_str().unwrap())
+                .major_threshold_with_sst_size(3)
+                .tombstone_compaction_percent(0);
+            let kv_store = LsmStore::open_with_config(config).await?;
+
+            for range in [0_u32..100, 100..200, 200..300] {
+                for i in range {
+                    let key = Bytes::from(i.to_be_bytes().to_vec());
+                    kv_store.set(&key, key.clone()).await?;
+                }
+                kv_store.flush().await?;
+            }
+            assert!(kv_store.current_version().await.level_slice[0].is_empty());
+
+            // 删除标记位于Level 0，而被删除的旧数据已被压缩至Level 1
+            let removed_key = Bytes::from(50_u32.to_be_bytes().to_vec());
+            kv_store.remove(&removed_key).await?;
+            kv_store.flush().await?;
+            // 再次flush使删除标记从immutable中移出，确保读取的数据来自SSTable
+            let key = Bytes::from(1000_u32.to_be_bytes().to_vec());
+            kv_store.set(&key, key.clone()).await?;
+            kv_store.flush().await?;
+
+            let version = kv_store.current_version().await;
+            assert_eq!(version.level_slice[0].len(), 2);
+            assert!(!version.level_slice[1].is_empty());
+            drop(version);
+
+            assert_eq!(kv_store.get(&removed_key).await?, None);
+            assert!(matches!(
+                kv_store.remove(&removed_key).await,
+                Err(KernelError::KeyNotFound)
+            ));
+
+            Ok(())
+        })
+    }
+
+    /// 移除偶数Key，并将3的倍数的Key的Value改写
+    #[derive(Debug)]
+    struct TestFilter;
+
+    impl CompactionFilter for TestFilter {
+        fn filter(&self, _level: usize, key: &[u8], _value: &[u8]) -> CompactionDecision {
+            let i = u32::from_be_bytes([key[0], key[1], key[2], key[3]]);
+
+            if i % 2 == 0 {
+                CompactionDecision::Remove
+            } else if i % 3 == 0 {
+                CompactionDecision::ChangeValue(Bytes::from_static(b"changed"))
+            } else {
+                CompactionDecision::Keep
+            }
+        }
+    }
+
+    #[test]
+    fn test_lsm_compaction_filter() -> Result<()> {
+        let temp_dir = TempDir::new().expect("unable to create temporary working directory");
+
+        tokio_test::block_on(async move {
+            let config = Config::new(temp_dir.path().to_str().unwrap())
+                .major_threshold_with_sst_size(2)
+                .compaction_filter(TestFilter);
+            let kv_store = LsmStore::open_with_config(config).await?;
+
+            // 两批键值范围重叠的数据，使Major压缩时必须进行归并
+            for _ in 0..2 {
+                for i in 0_u32..100 {
+                    let key = Bytes::from(i.to_be_bytes().to_vec());
+                    kv_store.set(&key, key.clone()).await?;
+                }
+                kv_store.flush().await?;
+            }
+            // 再次flush使第二批数据从immutable中移出，确保读取的数据来自压缩后的SSTable
+            let key = Bytes::from(1000_u32.to_be_bytes().to_vec());
+            kv_store.set(&key, key.clone()).await?;
+            kv_store.flush().await?;
+
+            let version = kv_store.current_version().await;
+            assert_eq!(version.level_slice[0].len(), 1);
+            assert!(!version.level_slice[1].is_empty());
+            drop(version);
+
+            for i in 0_u32..100 {
+                let key = Bytes::from(i.to_be_bytes().to_vec());
+                let expect = if i % 2 == 0 {
+                    None
+                } else if i % 3 == 0 {
+                    Some(Bytes::from_static(b"changed"))
+                } else {
+                    Some(key.clone())
+                };
+                assert_eq!(kv_store.get(&key).await?, expect);
+            }
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_lsm_compaction_filter_without_trivial_move() -> Result<()> {
+        let temp_dir = TempDir::new().expect("unable to create temporary working directory");
+
+        tokio_test::block_on(async move {
+            let config = Config::new(temp_dir.path().to_str().unwrap())
+                .major_threshold_with_sst_size(2)
+                .compaction_filter(TestFilter);
+            let kv_store = LsmStore::open_with_config(config).await?;
+
+            // 两批键值范围互不重叠的数据，设置了压缩过滤器时仍需重写以经过过滤器
+            for range in [0_u32..100, 100..200] {
+                for i in range {
+                    let key = Bytes::from(i.to_be_bytes().to_vec());
+                    kv_store.set(&key, key.clone()).await?;
+                }
+                kv_store.flush().await?;
+            }
+            let key = Bytes::from(1000_u32.to_be_bytes().to_vec());
+            kv_store.set(&key, key.clone()).await?;
+            kv_store.flush().await?;
+
+            let version = kv_store.current_version().await;
+            assert!(!version.level_slice[1].is_empty());
+            drop(version);
+
+            for i in 0_u32..200 {
+                let key = Bytes::from(i.to_be_bytes().to_vec());
+                let expect = if i % 2 == 0 {
+                    None
+                } else if i % 3 == 0 {
+                    Some(Bytes::from_static(b"changed"))
+                } else {
+                    Some(key.clone())
+                };
+                assert_eq!(kv_store.get(&key).await?, expect);
+            }
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_lsm_rate_limiter_stats() -> Result<()> {
+        let temp_dir = TempDir::new().expect("unable to create temporary working directory");
+
+        tokio_test::block_on(async move {
+            let bytes_per_sec = 256 * 1024;
+            let config = Config::new(temp_dir.path().to_str().unwrap())
+                .major_threshold_with_sst_size(2)
+                .rate_limiter(bytes_per_sec, false);
+            let kv_store = LsmStore::open_with_config(config).await?;
+            assert_eq!(
+                kv_store
+                    .rate_limiter_stats()
+                    .map(|stats| stats.total_requests),
+                Some(0)
+            );
+
+            // 随机的Value使SSTable不会被压缩得过小
+            let mut rng = rand::thread_rng();
+            let start = Instant::now();
+            for _ in 0..2 {
+                for i in 0_u32..100 {
+                    let key = Bytes::from(i.to_be_bytes().to_vec());
+                    let value = (0..1024).map(|_| rng.gen::<u8>()).collect_vec();
+                    kv_store.set(&key, Bytes::from(value)).await?;
+                }
+                kv_store.flush().await?;
+            }
+            let elapsed = start.elapsed();
+
+            // 两次flush的写入、一次压缩的读取与写入，写入时按块请求令牌
+            let stats = kv_store.rate_limiter_stats().unwrap();
+            assert!(stats.total_requests > 4);
+            assert!(stats.total_throttled_requests > 0);
+            assert!(stats.total_bytes_through > bytes_per_sec);
+            // 除令牌桶初始的一秒容量外，通过的数据量不超过设定的速率
+            assert!(
+                stats.total_bytes_through * 1000
+                    <= bytes_per_sec * (elapsed.as_millis() as u64 + 1000)
+            );
+            assert_eq!(stats.bytes_per_sec, bytes_per_sec);
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_lsm_num_levels() -> Result<()> {
+        let temp_dir = TempDir::new().expect("unable to create temporary working directory");
+
+        assert_eq!(Config::new(temp_dir.path()).num_levels(1).num_levels, 2);
+        assert_eq!(Config::new(temp_dir.path()).num_levels(100).num_levels, 10);
+
+        // 层级相关的设置与num_levels的先后顺序无关，超出范围时不会panic
+        let config = Config::new(temp_dir.path())
+            .level_filter_policy(4, FilterPolicy::None)
+            .num_levels(3)
+            .level_table_type(MAX_NUM_LEVELS, TableType::SortedString)
+            .level_filter_policy(MAX_NUM_LEVELS, FilterPolicy::None)
+            .num_levels(5);
+        assert!(matches!(config.filter_policies[4], FilterPolicy::None));
+        assert!(matches!(config.filter_policies[3], FilterPolicy::Bloom(_)));
+
+        tokio_test::block_on(async move {
+            let config = Config::new(temp_dir.path().to_str().unwrap())
+                .num_levels(3)
+                .major_threshold_with_sst_size(2)
+                .max_bytes_for_level_base(4 * 1024)
+                .level_sst_magnification(2);
+            let kv_store = LsmStore::open_with_config(config).await?;
+
+            for batch in 0_u32..12 {
+                for i in batch * 100..(batch + 1) * 100 {
+                    let key = Bytes::from(i.to_be_bytes().to_vec());
+                    kv_store.set(&key, key.clone()).await?;
+                }
+                kv_store.flush().await?;
+            }
+
+            let version = kv_store.current_version().await;
+            assert_eq!(version.num_levels(), 3);
+            assert!(!version.level_slice[2].is_empty());
+            drop(version);
+
+            for i in 0_u32..1200 {
+                let key = Bytes::from(i.to_be_bytes().to_vec());
+                assert_eq!(kv_store.get(&key).await?, Some(key));
+            }
+            drop(kv_store);
+
+            // 已有数据的层数大于配置的层数时无法打开
+            let config = Config::new(temp_dir.path().to_str().unwrap()).num_levels(2);
+            assert!(LsmStore::open_with_config(config).await.is_err());
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_lsm_dynamic_level_bytes() -> Result<()> {
+        let temp_dir = TempDir::new().expect("unable to create temporary working directory");
+
+        tokio_test::block_on(async move {
+            let config = Config::new(temp_dir.path().to_str().unwrap())
+                .major_threshold_with_sst_size(2)
+                .max_bytes_for_level_base(16 * 1024)
+                .level_sst_magnification(2)
+                .enable_level_compaction_dynamic_level_bytes();
+            let kv_store = LsmStore::open_with_config(config.clone()).await?;
+
+            // 最底层数据量不足max_bytes_for_level_base时Level 0直接压缩至最底层
+            for i in 0_u32..200 {
+                let key = Bytes::from(i.to_be_bytes().to_vec());
+                kv_store.set(&key, key.clone()).await?;
+                if i % 100 == 99 {
+                    kv_store.flush().await?;
+                }
+            }
+            let version = kv_store.current_version().await;
+            assert_eq!(version.level_targets(&config).0, 6);
+            assert!(version.level_slice[1..6].iter().all(Vec::is_empty));
+            assert!(!version.level_slice[6].is_empty());
+            drop(version);
+
+            for batch in 2_u32..60 {
+                for i in batch * 100..(batch + 1) * 100 {
+                    let key = Bytes::from(i.to_be_bytes().to_vec());
+                    kv_store.set(&key, key.clone()).await?;
+                }
+                kv_store.flush().await?;
+            }
+
+            // 最底层超出max_bytes_for_level_base后Base Level上移，且最底层始终占有大部分数据
+            let version = kv_store.current_version().await;
+            let (base_level, targets) = version.level_targets(&config);
+            assert!(base_level < 6);
+            assert!(targets[..base_level].iter().all(|target| *target == 0));
+            assert!(version.level_slice[1..base_level].iter().all(Vec::is_empty));
+            assert!(version.level_metas[6].size_of_disk * 2 > version.size_of_disk());
+            drop(version);
+
+            for i in 0_u32..6000 {
+                let key = Bytes::from(i.to_be_bytes().to_vec());
+                assert_eq!(kv_store.get(&key).await?, Some(key));
+            }
+
+            Ok(())
+        })
+    }
+    #[test]
+    fn test_lsm_clean_obsolete_files() -> Result<()> {
+        let temp_dir = TempDir::new().expect("unable to create temporary working directory");
+        let quarantine_dir = TempDir::new().expect("unable to create temporary working directory");
+
+        tokio_test::block_on(async move {
+            let config = Config::new(temp_dir.path().to_str().unwrap());
+            let kv_store = LsmStore::open_with_config(config.clone()).await?;
+
+            for i in 0_u32..100 {
+                let key = Bytes::from(i.to_be_bytes().to_vec());
+                kv_store.set(&key, key.clone()).await?;
+            }
+            kv_store.flush().await?;
+            drop(kv_store);
+
+            // 模拟log_and_apply后未能删除的冗余文件
+            let sst_path = FileExtension::SSTable
+                .path_with_gen(&temp_dir.path().join(DEFAULT_SS_TABLE_PATH), 1);
+            let wal_path =
+                FileExtension::Log.path_with_gen(&temp_dir.path().join(DEFAULT_WAL_PATH), 1);
+            fs::write(&sst_path, b"obsolete")?;
+            fs::write(&wal_path, b"obsolete")?;
+
+            let kv_store = LsmStore::open_with_config(config.clone()).await?;
+            assert!(!sst_path.exists());
+            assert!(!wal_path.exists());
+            for i in 0_u32..100 {
+                let key = Bytes::from(i.to_be_bytes().to_vec());
+                assert_eq!(kv_store.get(&key).await?, Some(key));
+            }
+            drop(kv_store);
+
+            // 设置隔离目录时移动而非删除
+            fs::write(&sst_path, b"obsolete")?;
+            let kv_store = LsmStore::open_with_config(
+                config
+                    .clone()
+                    .quarantine_dir(quarantine_dir.path().to_path_buf()),
+            )
+            .await?;
+            assert!(!sst_path.exists());
+            assert!(FileExtension::SSTable
+                .path_with_gen(&quarantine_dir.path().join(DEFAULT_SS_TABLE_PATH), 1)
+                .exists());
+            for i in 0_u32..100 {
+                let key = Bytes::from(i.to_be_bytes().to_vec());
+                assert_eq!(kv_store.get(&key).await?, Some(key));
+            }
+            drop(kv_store);
+
+            // Manifest末尾存在不完整的记录时Version不可信，不进行清理
+            let manifest_dir = temp_dir.path().join(DEFAULT_VERSION_PATH);
+            let manifest_gen = sorted_gen_list(&manifest_dir, FileExtension::Manifest)?[0];
+            OpenOptions::new()
+                .append(true)
+                .open(FileExtension::Manifest.path_with_gen(&manifest_dir, manifest_gen))?
+                .write_all(&[1, 2, 3])?;
+            fs::write(&sst_path, b"obsolete")?;
+            let kv_store = LsmStore::open_with_config(config).await?;
+            assert!(sst_path.exists());
+            for i in 0_u32..100 {
+                let key = Bytes::from(i.to_be_bytes().to_vec());
+                assert_eq!(kv_store.get(&key).await?, Some(key));
+            }
+
+            Ok(())
+        })
+    }
+    #[test]
+    fn test_lsm_recover_unflushed_log() -> Result<()> {
+        let temp_dir = TempDir::new().expect("unable to create temporary working directory");
+
+        tokio_test::block_on(async move {
+            let config = Config::new(temp_dir.path().to_str().unwrap());
+            let kv_store = LsmStore::open_with_config(config.clone()).await?;
+
+            for i in 0_u32..100 {
+                let key = Bytes::from(i.to_be_bytes().to_vec());
+                kv_store.set(&key, key.clone()).await?;
+            }
+            kv_store.flush().await?;
+
+            // 模拟在MemTable::swap与log_and_apply之间宕机：immutable的WAL既未被重放也未被Version引用
+            for i in 100_u32..200 {
+                let key = Bytes::from(i.to_be_bytes().to_vec());
+                kv_store.set(&key, key.clone()).await?;
+            }
+            let (unflushed_gen, _) = kv_store.mem_table().swap()?.unwrap();
+            drop(kv_store);
+
+            let kv_store = LsmStore::open_with_config(config).await?;
+            assert!(kv_store
+                .current_version()
+                .await
+                .gens()
+                .contains(&unflushed_gen));
+            assert!(FileExtension::Log
+                .path_with_gen(&temp_dir.path().join(DEFAULT_WAL_PATH), unflushed_gen)
+                .exists());
+            for i in 0_u32..200 {
+                let key = Bytes::from(i.to_be_bytes().to_vec());
+                assert_eq!(kv_store.get(&key).await?, Some(key));
+            }
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_lsm_repair() -> Result<()> {
+        let temp_dir = TempDir::new().expect("unable to create temporary working directory");
+
+        tokio_test::block_on(async move {
+            let config = Config::new(temp_dir.path().to_str().unwrap());
+            let kv_store = LsmStore::open_with_config(config.clone()).await?;
+
+            for range in [0_u32..100, 50..150] {
+                for i in range {
+                    let key = Bytes::from(i.to_be_bytes().to_vec());
+                    kv_store.set(&key, key.clone()).await?;
+                }
+                kv_store.flush().await?;
+            }
+            // 两个相交的Level 1 Table，修复时较新的后者会被放置至Level 0(即使其Key较小)
+            // 以及Gen最大但数据最旧的Level 2 Table(如Level 1压缩至Level 2的产物)
+            let loader = kv_store.inner.ver_status.loader();
+            for (range, level, value) in [
+                (205_u32..215, 1, "old"),
+                (200..210, 1, "new"),
+                (200..215, 2, "stale"),
+            ] {
+                let vec_data = range
+                    .map(|i| {
+                        let key = Bytes::from(i.to_be_bytes().to_vec());
+                        (key, Some(Bytes::from(value)))
+                    })
+                    .collect_vec();
+                let _ = loader
+                    .create(Gen::create(), vec_data, level, TableType::SortedString)
+                    .await?;
+            }
+            drop(kv_store);
+
+            // 模拟Version丢失与SSTable损坏
+            fs::remove_dir_all(temp_dir.path().join(DEFAULT_VERSION_PATH))?;
+            let broken_path = FileExtension::SSTable
+                .path_with_gen(&temp_dir.path().join(DEFAULT_SS_TABLE_PATH), 1);
+            fs::write(&broken_path, b"broken")?;
+
+            let report = LsmStore::repair(temp_dir.path()).await?;
+            assert_eq!(report.tables, 5);
+            assert_eq!(report.moved_to_level_0, 1);
+            assert_eq!(report.recovered_from_wal, 0);
+            assert_eq!(report.lost, vec![1]);
+            assert!(!broken_path.exists());
+            assert!(temp_dir
+                .path()
+                .join(DEFAULT_LOST_PATH)
+                .join("1.sst")
+                .exists());
+
+            let kv_store = LsmStore::open_with_config(config).await?;
+            let version = kv_store.current_version().await;
+            assert_eq!(version.level_len(0), 3);
+            assert_eq!(version.level_len(1), 1);
+            assert_eq!(version.level_len(2), 1);
+            drop(version);
+
+            for i in 0_u32..150 {
+                let key = Bytes::from(i.to_be_bytes().to_vec());
+                assert_eq!(kv_store.get(&key).await?, Some(key));
+            }
+            for i in 200_u32..215 {
+                let key = Bytes::from(i.to_be_bytes().to_vec());
+                let expect = if i < 210 { "new" } else { "old" };
+                assert_eq!(kv_store.get(&key).await?, Some(Bytes::from(expect)));
+            }
+
+            Ok(())
+        })
+    }
+    #[test]
+    fn test_lsm_verify() -> Result<()> {
+        let temp_dir = TempDir::new().expect("unable to create temporary working directory");
+
+        tokio_test::block_on(async move {
+            let kv_store = LsmStore::open(temp_dir.path().to_str().unwrap()).await?;
+
+            for i in 0_u32..1000 {
+                let key = Bytes::from(i.to_be_bytes().to_vec());
+                kv_store.set(&key, key.clone()).await?;
+                if i % 10 == 0 {
+                    kv_store.remove(&key).await?;
+                }
+            }
+            kv_store.flush().await?;
+
+            let report = kv_store.verify().await?;
+            assert!(report.is_ok(), "{report:?}");
+            assert_eq!(report.tables, 1);
+            assert_eq!(report.entries, 1000);
+
+            // 破坏SSTable中的数据Block
+            let version = kv_store.current_version().await;
+            let gen = version.level_slice[0][0].get_gen();
+            drop(version);
+            let sst_path = FileExtension::SSTable
+                .path_with_gen(&temp_dir.path().join(DEFAULT_SS_TABLE_PATH), gen);
+            let mut bytes = fs::read(&sst_path)?;
+            bytes[16] = !bytes[16];
+            fs::write(&sst_path, bytes)?;
+
+            let report = kv_store.verify().await?;
+            assert!(!report.is_ok());
+            assert!(matches!(
+                report.issues[0],
+                VerifyIssue::TableCorrupted { level: 0, gen: issue_gen, .. } if issue_gen == gen
+            ));
+
+            fs::remove_file(&sst_path)?;
+            let report = kv_store.verify().await?;
+            assert_eq!(
+                report.issues,
+                vec![VerifyIssue::TableMissing { level: 0, gen }]
+            );
+
+            Ok(())
+        })
+    }
+    #[test]
+    fn test_lsm_checkpoint() -> Result<()> {
+        let temp_dir = TempDir::new().expect("unable to create temporary working directory");
+        let checkpoint_dir = TempDir::new().expect("unable to create temporary working directory");
+        let checkpoint_path = checkpoint_dir.path().join("checkpoint");
+
+        tokio_test::block_on(async move {
+            let kv_store = Arc::new(LsmStore::open(temp_dir.path().to_str().unwrap()).await?);
+
+            for i in 0_u32..2000 {
+                let key = Bytes::from(i.to_be_bytes().to_vec());
+                kv_store.set(&key, key.clone()).await?;
+            }
+
+            // 创建检查点时仍有写入在进行
+            let writer_store = Arc::clone(&kv_store);
+            let writer = tokio::spawn(async move {
+                for i in 2000_u32..6000 {
+                    let key = Bytes::from(i.to_be_bytes().to_vec());
+                    writer_store.set(&key, key.clone()).await?;
+                    if i % 500 == 0 {
+                        tokio::task::yield_now().await;
+                    }
+                }
+                Ok::<(), KernelError>(())
+            });
+            tokio::task::yield_now().await;
+            kv_store.checkpoint(&checkpoint_path).await?;
+            writer.await.expect("writer panicked")?;
+            assert!(kv_store.checkpoint(&checkpoint_path).await.is_err());
+
+            let checkpoint_store = LsmStore::open(&checkpoint_path).await?;
+            assert!(checkpoint_store.verify().await?.is_ok());
+            for i in 0_u32..6000 {
+                let key = Bytes::from(i.to_be_bytes().to_vec());
+                match checkpoint_store.get(&key).await? {
+                    Some(value) => assert_eq!(value, key),
+                    // 检查点创建前的写入必然存在
+                    None => assert!(i >= 2000),
+                }
+            }
+            // 原Store不受影响
+            for i in 0_u32..6000 {
+                let key = Bytes::from(i.to_be_bytes().to_vec());
+                assert_eq!(kv_store.get(&key).await?, Some(key));
+            }
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_lsm_ingest_external_files() -> Result<()> {
+        let temp_dir = TempDir::new().expect("unable to create temporary working directory");
+        let external_dir = TempDir::new().expect("unable to create temporary working directory");
+        let config = Config::new(temp_dir.path().to_str().unwrap());
+        let path_overlap = external_dir.path().join("overlap.sst");
+        let path_bottom = external_dir.path().join("bottom.sst");
+        let path_conflict = external_dir.path().join("conflict.sst");
+        let key = |i: u32| Bytes::from(i.to_be_bytes().to_vec());
+
+        // 与Version及MemTable均相交的Table
+        let mut writer = SstFileWriter::new(&config, &path_overlap);
+        for i in 500_u32..700 {
+            writer.put(key(i), Bytes::from_static(b"ingest"))?;
+        }
+        writer.delete(key(700))?;
+        assert!(matches!(
+            writer.put(key(600), Bytes::new()),
+            Err(KernelError::KeyOutOfOrder)
+        ));
+        assert_eq!(writer.finish()?, vec![path_overlap.clone()]);
+        // 不与任何数据相交的Table
+        let mut writer = SstFileWriter::new(&config, &path_bottom);
+        for i in 5000_u32..5100 {
+            writer.put(key(i), key(i))?;
+        }
+        let _ = writer.finish()?;
+        let mut writer = SstFileWriter::new(&config, &path_conflict);
+        writer.put(key(5050), key(5050))?;
+        let _ = writer.finish()?;
+
+        tokio_test::block_on(async move {
+            let kv_store = LsmStore::open_with_config(config.clone()).await?;
+
+            for i in 0_u32..1000 {
+                kv_store.set(&key(i), key(i)).await?;
+            }
+            kv_store.flush().await?;
+            for i in 600_u32..800 {
+                kv_store.set(&key(i), Bytes::from_static(b"mem")).await?;
+            }
+
+            // 导入的Table之间相交时失败，且不残留文件
+            let sst_gens = |store: &LsmStore| {
+                fs::read_dir(store.config().path().join(DEFAULT_SS_TABLE_PATH))
+                    .map(|dir| dir.count())
+            };
+            let sst_count = sst_gens(&kv_store)?;
+            assert!(matches!(
+                kv_store
+                    .ingest_external_files(&[&path_bottom, &path_conflict])
+                    .await,
+                Err(KernelError::ExternalTableOverlap)
+            ));
+            assert_eq!(sst_gens(&kv_store)?, sst_count);
+
+            kv_store
+                .ingest_external_files(&[&path_overlap, &path_bottom])
+                .await?;
+            assert!(path_overlap.exists());
+
+            let version = kv_store.current_version().await;
+            let last_level = config.num_levels - 1;
+            assert!(version.level_slice[last_level]
+                .iter()
+                .any(|scope| scope.start == key(5000) && scope.end == key(5099)));
+            drop(version);
+
+            let check = |kv_store: LsmStore| async move {
+                for i in 0_u32..500 {
+                    assert_eq!(kv_store.get(&key(i)).await?, Some(key(i)));
+                }
+                for i in 500_u32..700 {
+                    assert_eq!(
+                        kv_store.get(&key(i)).await?,
+                        Some(Bytes::from_static(b"ingest"))
+                    );
+                }
+                for i in 701_u32..800 {
+                    assert_eq!(
+                        kv_store.get(&key(i)).await?,
+                        Some(Bytes::from_static(b"mem"))
+                    );
+                }
+                for i in 5000_u32..5100 {
+                    assert_eq!(kv_store.get(&key(i)).await?, Some(key(i)));
+                }
+                assert!(kv_store.verify().await?.is_ok());
+
+                Ok::<(), KernelError>(())
+            };
+            check(kv_store).await?;
+            // 重启后导入的数据依旧生效
+            check(LsmStore::open_with_config(config).await?).await?;
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_lsm_ingest_rolled_files() -> Result<()> {
+        let temp_dir = TempDir::new().expect("unable to create temporary working directory");
+        let external_dir = TempDir::new().expect("unable to create temporary working directory");
+        let config = Config::new(temp_dir.path().to_str().unwrap()).sst_file_size(4 * 1024);
+        let key = |i: u32| Bytes::from(i.to_be_bytes().to_vec());
+
+        // 每个键值对为8B，数据量达到sst_file_size时切换至新的文件
+        let mut writer = SstFileWriter::new(&config, external_dir.path().join("data.sst"));
+        for i in 0_u32..1000 {
+            writer.put(key(i), key(i))?;
+        }
+        assert_eq!(writer.len(), 1000);
+        let vec_path = writer.finish()?;
+        assert_eq!(
+            vec_path,
+            vec![
+                external_dir.path().join("data.sst"),
+                external_dir.path().join("data_1.sst"),
+            ]
+        );
+
+        tokio_test::block_on(async move {
+            let kv_store = LsmStore::open_with_config(config).await?;
+            kv_store.ingest_external_files(&vec_path).await?;
+
+            let version = kv_store.current_version().await;
+            assert_eq!(version.level_slice[version.num_levels() - 1].len(), 2);
+            drop(version);
+            for i in 0_u32..1000 {
+                assert_eq!(kv_store.get(&key(i)).await?, Some(key(i)));
+            }
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_lsm_open_read_only() -> Result<()> {
+        let temp_dir = TempDir::new().expect("unable to create temporary working directory");
+        // 使WAL直接写入文件，以便只读打开时可以读取到未flush的数据
+        let config = Config::new(temp_dir.path().to_str().unwrap()).wal_io_type(IoType::Direct);
+        let key = |i: u32| Bytes::from(i.to_be_bytes().to_vec());
+        let list_files = |dir_path: &std::path::Path| -> Result<Vec<_>> {
+            Ok(fs::read_dir(dir_path)?
+                .map(|entry| entry.map(|entry| entry.path()))
+                .collect::<std::io::Result<Vec<_>>>()?
+                .into_iter()
+                .sorted()
+                .collect_vec())
+        };
+
+        tokio_test::block_on(async move {
+            assert!(
+                LsmStore::open_read_only(Config::new(temp_dir.path().join("none")))
+                    .await
+                    .is_err()
+            );
+
+            // 写入进程持有文件锁时依旧可以只读打开
+            let kv_store = LsmStore::open_with_config(config.clone()).await?;
+            for i in 0_u32..1000 {
+                kv_store.set(&key(i), key(i)).await?;
+            }
+            kv_store.flush().await?;
+            // 仅存在于WAL中的数据
+            for i in 1000_u32..1200 {
+                kv_store.set(&key(i), key(i)).await?;
+            }
+            let wal_files = list_files(&config.path().join(DEFAULT_WAL_PATH))?;
+            let version_files = list_files(&config.path().join(DEFAULT_VERSION_PATH))?;
+
+            let read_only = LsmStore::open_read_only(config.clone()).await?;
+            for i in 0_u32..1200 {
+                assert_eq!(read_only.get(&key(i)).await?, Some(key(i)));
+            }
+            assert!(read_only.verify().await?.is_ok());
+            assert!(matches!(
+                read_only.set(&key(0), Bytes::new()).await,
+                Err(KernelError::NotSupport(_))
+            ));
+            assert!(matches!(
+                read_only.remove(&key(0)).await,
+                Err(KernelError::NotSupport(_))
+            ));
+            assert!(matches!(
+                read_only.flush().await,
+                Err(KernelError::NotSupport(_))
+            ));
+            let mut transaction = read_only.new_transaction().await;
+            transaction.set(&key(0), Bytes::new());
+            assert!(matches!(
+                transaction.commit().await,
+                Err(KernelError::NotSupport(_))
+            ));
+            assert_eq!(read_only.get(&key(0)).await?, Some(key(0)));
+
+            // 只读打开不会创建新的WAL与Manifest
+            assert_eq!(
+                list_files(&config.path().join(DEFAULT_WAL_PATH))?,
+                wal_files
+            );
+            assert_eq!(
+                list_files(&config.path().join(DEFAULT_VERSION_PATH))?,
+                version_files
+            );
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_lsm_shared_block_cache() -> Result<()> {
+        let temp_dir = TempDir::new().expect("unable to create temporary working directory");
+        let block_cache = BlockCache::new(1024 * 1024)?;
+        let key = |i: u32| Bytes::from(i.to_be_bytes().to_vec());
+
+        tokio_test::block_on(async move {
+            let config_with = |name: &str| {
+                Config::new(temp_dir.path().join(name)).block_cache(Arc::clone(&block_cache))
+            };
+            for name in ["a", "b"] {
+                let kv_store = LsmStore::open_with_config(config_with(name)).await?;
+                for i in 0_u32..5000 {
+                    kv_store
+                        .set(&key(i), Bytes::from(format!("{name}_{i}")))
+                        .await?;
+                }
+                kv_store.flush().await?;
+            }
+            // 重新打开以使读取不经过MemTable
+            let mut vec_store = Vec::new();
+            for name in ["a", "b"] {
+                vec_store.push((name, LsmStore::open_with_config(config_with(name)).await?));
+            }
+
+            // 两个Store的SSTable Gen可能相同，共享缓存时不可读取到对方的Block
+            for (name, kv_store) in vec_store.iter() {
+                for i in 0_u32..5000 {
+                    assert_eq!(
+                        kv_store.get(&key(i)).await?,
+                        Some(Bytes::from(format!("{name}_{i}")))
+                    );
+                }
+            }
+            assert!(block_cache.usage() > 0);
+            assert!(block_cache.usage() <= block_cache.capacity());
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_lsm_scan_without_fill_cache() -> Result<()> {
+        let temp_dir = TempDir::new().expect("unable to create temporary working directory");
+        let block_cache = BlockCache::with_policy(4 * 1024 * 1024, CachePolicy::TinyLfu)?;
+        let config = Config::new(temp_dir.path()).block_cache(Arc::clone(&block_cache));
+        let key = |i: u32| Bytes::from(i.to_be_bytes().to_vec());
+        let scan = |kv_store: &LsmStore, options: ReadOptions| {
+            tokio_test::block_on(async {
+                let guard = kv_store.guard().await?;
+                let mut iter = guard.iter_with_options(options)?;
+                let mut count = 0;
+                while iter.next_err()?.is_some() {
+                    count += 1;
+                }
+                Ok::<_, KernelError>(count)
+            })
+        };
+
+        let kv_store = tokio_test::block_on(async {
+            let kv_store = LsmStore::open_with_config(config.clone()).await?;
+            for i in 0_u32..5000 {
+                kv_store.set(&key(i), key(i)).await?;
+            }
+            kv_store.flush().await?;
+            drop(kv_store);
+            // 重新打开以使数据仅存在于SSTable中
+            LsmStore::open_with_config(config).await
+        })?;
+
+        // 不填充缓存时仅有Index Block被缓存
+        assert_eq!(scan(&kv_store, ReadOptions::new().fill_cache(false))?, 5000);
+        let usage_without_fill = block_cache.usage();
+        assert_eq!(scan(&kv_store, ReadOptions::new())?, 5000);
+        let usage_with_fill = block_cache.usage();
+        assert!(usage_without_fill < usage_with_fill);
+        assert_eq!(scan(&kv_store, ReadOptions::new().fill_cache(false))?, 5000);
+        assert_eq!(block_cache.usage(), usage_with_fill);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_lsm_cache_stats() -> Result<()> {
+        let temp_dir = TempDir::new().expect("unable to create temporary working directory");
+        // 容量不足以容纳所有Data Block，以产生驱逐
+        let config = Config::new(temp_dir.path()).block_cache_size(1024 * 1024);
+        let key = |i: u32| Bytes::from(i.to_be_bytes().to_vec());
+
+        tokio_test::block_on(async move {
+            let kv_store = LsmStore::open_with_config(config.clone()).await?;
+            for i in 0_u32..20000 {
+                kv_store.set(&key(i), key(i)).await?;
+            }
+            kv_store.flush().await?;
+            drop(kv_store);
+
+            let kv_store = LsmStore::open_with_config(config).await?;
+            assert_eq!(kv_store.cache_stats().data_block, CacheCounter::default());
+            for _ in 0..2 {
+                for i in 0_u32..20000 {
+                    assert_eq!(kv_store.get(&key(i)).await?, Some(key(i)));
+                }
+            }
+            let stats = kv_store.cache_stats();
+
+            assert!(stats.table_cache.hits > 0);
+            assert!(stats.table_cache.inserts > 0);
+            assert!(stats.table_cache_usage <= stats.table_cache_capacity);
+            assert!(stats.index_block.hits > 0);
+            assert!(stats.index_block.inserts > 0);
+            assert!(stats.data_block.hits > 0);
+            assert!(stats.data_block.misses > 0);
+            assert!(stats.data_block.evictions > 0);
+            assert!(stats.data_block.inserts >= stats.data_block.evictions);
+            assert!(stats.block_cache_usage > 0);
+            assert!(stats.block_cache_usage <= stats.block_cache_capacity);
+            assert_eq!(stats.block_cache_capacity, 1024 * 1024);
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_lsm_pin_index_blocks() -> Result<()> {
+        let temp_dir = TempDir::new().expect("unable to create temporary working directory");
+        let block_cache = BlockCache::new(1024 * 1024)?;
+        let config = Config::new(temp_dir.path())
+            .block_cache(Arc::clone(&block_cache))
+            .pin_index_blocks(PinIndexBlocks::All);
+        let key = |i: u32| Bytes::from(i.to_be_bytes().to_vec());
+
+        tokio_test::block_on(async move {
+            let kv_store = LsmStore::open_with_config(config.clone()).await?;
+            for i in 0_u32..20000 {
+                kv_store.set(&key(i), key(i)).await?;
+            }
+            kv_store.flush().await?;
+            drop(kv_store);
+
+            let kv_store = LsmStore::open_with_config(config).await?;
+            for i in 0_u32..20000 {
+                assert_eq!(kv_store.get(&key(i)).await?, Some(key(i)));
+            }
+            let stats = kv_store.cache_stats();
+
+            // Data Block被驱逐时固定的Index Block仍然常驻，因此查找始终命中
+            assert!(stats.data_block.evictions > 0);
+            assert_eq!(stats.index_block.misses, 0);
+            assert!(stats.index_block_pinned_usage > 0);
+            assert!(stats.filter_usage > 0);
+            assert!(stats.block_cache_usage >= stats.index_block_pinned_usage);
+
+            // 关闭Store时归还固定的Index Block所占用的容量
+            let usage = block_cache.usage() as u64;
+            drop(kv_store);
+            assert!(block_cache.usage() as u64 <= usage - stats.index_block_pinned_usage);
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_lsm_pin_index_blocks_with_trivial_move() -> Result<()> {
+        let temp_dir = TempDir::new().expect("unable to create temporary working directory");
+        let config = Config::new(temp_dir.path())
+            .major_threshold_with_sst_size(2)
+            .pin_index_blocks(PinIndexBlocks::Level0);
+        let key = |i: u32| Bytes::from(i.to_be_bytes().to_vec());
+
+        tokio_test::block_on(async move {
+            let kv_store = LsmStore::open_with_config(config.clone()).await?;
+            for i in 0_u32..100 {
+                kv_store.set(&key(i), key(i)).await?;
+            }
+            kv_store.flush().await?;
+            assert!(kv_store.cache_stats().index_block_pinned_usage > 0);
+
+            // 键值范围互不重叠，两个SSTable均通过Trivial Move移动至Level 1并解除固定
+            for i in 100_u32..200 {
+                kv_store.set(&key(i), key(i)).await?;
+            }
+            kv_store.flush().await?;
+            let version = kv_store.current_version().await;
+            assert!(version.level_slice[0].is_empty());
+            assert_eq!(version.level_slice[1].len(), 2);
+            drop(version);
+            assert_eq!(kv_store.cache_stats().index_block_pinned_usage, 0);
+            drop(kv_store);
+
+            // 重新加载时以Version中的Level判断是否固定，而非Footer中创建时的Level 0
+            let kv_store = LsmStore::open_with_config(config).await?;
+            for i in 0_u32..200 {
+                assert_eq!(kv_store.get(&key(i)).await?, Some(key(i)));
+            }
+            assert_eq!(kv_store.cache_stats().index_block_pinned_usage, 0);
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_lsm_row_cache() -> Result<()> {
+        let temp_dir = TempDir::new().expect("unable to create temporary working directory");
+        let config = Config::new(temp_dir.path()).row_cache_size(1024 * 1024);
+        let key = |i: u32| Bytes::from(i.to_be_bytes().to_vec());
+
+        tokio_test::block_on(async move {
+            let kv_store = LsmStore::open_with_config(config.clone()).await?;
+            for i in 0_u32..1000 {
+                kv_store.set(&key(i), key(i)).await?;
+            }
+            kv_store.flush().await?;
+            drop(kv_store);
+
+            let kv_store = LsmStore::open_with_config(config).await?;
+            for _ in 0..2 {
+                for i in 0_u32..3 {
+                    assert_eq!(kv_store.get(&key(i)).await?, Some(key(i)));
+                }
+            }
+            let stats = kv_store.cache_stats();
+            assert_eq!(stats.row_cache.hits, 3);
+            assert_eq!(stats.row_cache.misses, 3);
+            assert!(stats.row_cache_usage > 0);
+            assert_eq!(stats.row_cache_capacity, 1024 * 1024);
+
+            // 写入与事务提交后读取到的为新数据
+            kv_store.set(&key(0), key(1000)).await?;
+            assert_eq!(kv_store.get(&key(0)).await?, Some(key(1000)));
+            let mut transaction = kv_store.new_transaction().await;
+            transaction.set(&key(1), key(1001));
+            transaction.commit().await?;
+            assert_eq!(kv_store.get(&key(1)).await?, Some(key(1001)));
+
+            // flush切换Version后旧的结果失效
+            kv_store.flush().await?;
+            assert_eq!(kv_store.get(&key(2)).await?, Some(key(2)));
+            assert_eq!(kv_store.cache_stats().row_cache.misses, 4);
+            assert_eq!(kv_store.get(&key(2)).await?, Some(key(2)));
+            assert_eq!(kv_store.cache_stats().row_cache.hits, 4);
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_lsm_secondary_cache() -> Result<()> {
+        let temp_dir = TempDir::new().expect("unable to create temporary working directory");
+        // 另一个临时目录模拟更快的缓存设备
+        let cache_dir = TempDir::new().expect("unable to create temporary working directory");
+        let secondary =
+            SecondaryCache::new(cache_dir.path().join("block_cache"), 64 * 1024 * 1024)?;
+        let config = Config::new(temp_dir.path())
+            .block_cache_size(1024 * 1024)
+            .secondary_cache(Arc::clone(&secondary));
+        let key = |i: u32| Bytes::from(i.to_be_bytes().to_vec());
+
+        tokio_test::block_on(async move {
+            let kv_store = LsmStore::open_with_config(config.clone()).await?;
+            for i in 0_u32..20000 {
+                kv_store.set(&key(i), key(i)).await?;
+            }
+            kv_store.flush().await?;
+            drop(kv_store);
+
+            let kv_store = LsmStore::open_with_config(config).await?;
+            for _ in 0..2 {
+                for i in 0_u32..20000 {
+                    assert_eq!(kv_store.get(&key(i)).await?, Some(key(i)));
+                }
+            }
+            let stats = kv_store.cache_stats();
+            let counter = secondary.counter();
+
+            // Block缓存驱逐的Block写入了二级缓存，并在第二次读取时命中
+            assert!(stats.data_block.evictions > 0);
+            assert!(counter.inserts > 0);
+            assert!(counter.hits > 0);
+            assert!(secondary.usage() > 0);
+            assert!(secondary.usage() <= secondary.capacity());
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_lsm_prefix_iter() -> Result<()> {
+        let temp_dir = TempDir::new().expect("unable to create temporary working directory");
+        let config = Config::new(temp_dir.path()).prefix_extractor(FixedPrefix::new(4));
+        let key = |prefix: &str, i: usize| Bytes::from(format!("{prefix}{i:03}"));
+
+        tokio_test::block_on(async move {
+            let kv_store = LsmStore::open_with_config(config.clone()).await?;
+            for i in 0..100 {
+                kv_store.set(&key("aaa_", i), key("aaa_", i)).await?;
+                kv_store.set(&key("ccc_", i), key("ccc_", i)).await?;
+            }
+            kv_store.flush().await?;
+            for i in 0..100 {
+                kv_store.set(&key("bbb_", i), key("bbb_", i)).await?;
+            }
+            kv_store.flush().await?;
+            drop(kv_store);
+
+            let kv_store = LsmStore::open_with_config(config).await?;
+            kv_store.set(&key("bbb_", 100), key("bbb_", 100)).await?;
+
+            // 第一个Table的Scope包含前缀bbb_，但其前缀过滤器排除了该前缀
+            let version = kv_store.current_version().await;
+            let extractor = FixedPrefix::new(4);
+            assert_eq!(
+                version
+                    .tables_by_prefix(LEVEL_0, Some(&extractor), b"bbb_")
+                    .len(),
+                1
+            );
+            // 前缀长度不符或提取器不同时无法使用前缀过滤器
+            assert_eq!(
+                version
+                    .tables_by_prefix(LEVEL_0, Some(&extractor), b"bb")
+                    .len(),
+                2
+            );
+            assert_eq!(
+                version
+                    .tables_by_prefix(LEVEL_0, Some(&FixedPrefix::new(3)), b"bbb")
+                    .len(),
+                2
+            );
+
+            let expected = (0..=100)
+                .map(|i| (key("bbb_", i), Some(key("bbb_", i))))
+                .collect_vec();
+            let guard = kv_store.guard().await?;
+            let mut iter = guard.prefix_iter(b"bbb_")?;
+            let mut items = Vec::new();
+            while let Some(item) = iter.next_err()? {
+                items.push(item);
+            }
+            assert_eq!(items, expected);
+            drop(iter);
+            drop(guard);
+
+            let transaction = kv_store.new_transaction().await;
+            assert_eq!(transaction.prefix_scan(b"bbb_")?, expected);
+            assert_eq!(transaction.prefix_scan(b"ccc_")?.len(), 100);
+            assert!(transaction.prefix_scan(b"ddd_")?.is_empty());
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_lsm_filter_stats() -> Result<()> {
+        let temp_dir = TempDir::new().expect("unable to create temporary working directory");
+        let key = |i: u32| Bytes::from(i.to_be_bytes().to_vec());
+
+        tokio_test::block_on(async move {
+            for (dir, policy) in [
+                ("ribbon", FilterPolicy::Ribbon(10)),
+                ("none", FilterPolicy::None),
+            ] {
+                let config = Config::new(temp_dir.path().join(dir)).filter_policy(policy);
+                let kv_store = LsmStore::open_with_config(config.clone()).await?;
+                for i in 0_u32..1000 {
+                    kv_store.set(&key(i * 2), key(i)).await?;
+                }
+                kv_store.flush().await?;
+                drop(kv_store);
+
+                let kv_store = LsmStore::open_with_config(config.clone()).await?;
+                for i in 0_u32..100 {
+                    assert_eq!(kv_store.get(&key(i * 2)).await?, Some(key(i)));
+                    assert_eq!(kv_store.get(&key(i * 2 + 1)).await?, None);
+                }
+                let stats = kv_store.filter_stats().await;
+                assert_eq!(stats.len(), config.num_levels);
+                assert!(stats.iter().all(|level_stats| level_stats.policy == policy));
+
+                let level_0 = stats[LEVEL_0];
+                if policy == FilterPolicy::None {
+                    assert_eq!(level_0.checked, 0);
+                    assert_eq!(level_0.filter_usage, 0);
+                } else {
+                    // 存在的Key不会被排除，不存在的Key大多被过滤器排除
+                    assert_eq!(level_0.checked, 200);
+                    assert_eq!(level_0.useful + level_0.false_positive, 100);
+                    assert!(level_0.false_positive < 10);
+                    assert!(level_0.filter_usage > 0);
+                }
+            }
+
+            Ok(())
+        })
+    }
 }
@@ -6,14 +6,18 @@ use crate::kernel::lsm::storage::Config;
 use crate::kernel::lsm::table::meta::TableMeta;
 use crate::kernel::lsm::table::scope::Scope;
 use crate::kernel::lsm::table::skip_table::SkipTable;
-use crate::kernel::lsm::table::ss_table::block::BlockCache;
+use crate::kernel::lsm::table::ss_table::block::{BlockCache, BlockCacheHandle};
+use crate::kernel::lsm::table::ss_table::filter::FilterRecorder;
 use crate::kernel::lsm::table::ss_table::SSTable;
 use crate::kernel::lsm::table::{BoxTable, Table, TableType};
+use crate::kernel::lsm::version::Version;
+use crate::kernel::utils::cache_counter::{CacheCounter, CacheRecorder};
 use crate::kernel::utils::lru_cache::ShardingLruCache;
 use crate::kernel::Result;
 use bytes::Bytes;
 use itertools::Itertools;
 use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
 use std::mem;
 use std::sync::Arc;
 use tracing::warn;
@@ -21,10 +25,12 @@ use tracing::warn;
 #[derive(Clone)]
 pub(crate) struct TableLoader {
     inner: Arc<ShardingLruCache<i64, BoxTable>>,
+    recorder: Arc<CacheRecorder>,
     factory: Arc<IoFactory>,
     config: Config,
     wal: LogLoader,
-    cache: Arc<BlockCache>,
+    cache: BlockCacheHandle,
+    filter_recorder: Arc<FilterRecorder>,
 }
 
 impl TableLoader {
@@ -34,21 +40,37 @@ impl TableLoader {
             16,
             RandomState::default(),
         )?);
-        let cache = Arc::new(ShardingLruCache::new(
-            config.block_cache_size,
-            16,
-            RandomState::default(),
-        )?);
+        // 未设置共享的Block缓存时使用独立的缓存
+        let cache = match &config.block_cache {
+            Some(block_cache) => block_cache.handle(),
+            None => match &config.secondary_cache {
+                Some(secondary) => BlockCache::with_secondary_cache(
+                    config.block_cache_size,
+                    config.block_cache_policy,
+                    Arc::clone(secondary),
+                )?,
+                None => {
+                    BlockCache::with_policy(config.block_cache_size, config.block_cache_policy)?
+                }
+            }
+            .handle(),
+        }
+        .pin_index_blocks(config.pin_index_blocks);
         Ok(TableLoader {
             inner,
+            recorder: Arc::new(CacheRecorder::default()),
             factory,
+            filter_recorder: Arc::new(FilterRecorder::new(config.num_levels)),
             config,
             wal,
             cache,
         })
     }
 
-    pub(crate) fn create(
+    /// 创建Table并放入Table缓存
+    ///
+    /// SSTable的写入受`Config::rate_limiter`限制
+    pub(crate) async fn create(
         &self,
         gen: i64,
         vec_data: Vec<KeyValue>,
@@ -58,43 +80,96 @@ impl TableLoader {
         // 获取数据的Key涵盖范围
         let scope = Scope::from_vec_data(gen, &vec_data)?;
         let table: Box<dyn Table> = match table_type {
-            TableType::SortedString => Box::new(self.create_ss_table(gen, vec_data, level)?),
+            TableType::SortedString => Box::new(
+                SSTable::new_with_rate_limiter(
+                    &self.factory,
+                    &self.config,
+                    self.cache.clone(),
+                    gen,
+                    vec_data,
+                    level,
+                    IoType::Direct,
+                )
+                .await?,
+            ),
             TableType::Skip => Box::new(SkipTable::new(level, gen, vec_data)),
         };
         let table_meta = TableMeta::from(table.as_ref());
         let _ = self.inner.put(gen, table);
+        self.recorder.record_insert();
 
         Ok((scope, table_meta))
     }
 
-    pub(crate) fn get(&self, gen: i64) -> Option<&dyn Table> {
-        self.inner
-            .get_or_insert(gen, |gen| {
-                let sst_factory = &self.factory;
-
-                let ss_table = match sst_factory
-                    .reader(*gen, IoType::Direct)
-                    .and_then(|reader| SSTable::load_from_file(reader, Arc::clone(&self.cache)))
-                {
-                    Ok(ss_table) => ss_table,
-                    Err(err) => {
-                        // 尝试恢复仅对Level 0的Table有效
-                        warn!(
-                            "[LSMStore][Load Table: {}][try to reload with wal]: {:?}",
-                            gen, err
-                        );
-                        let reload_data =
-                            logs_decode(self.wal.load(*gen, |bytes| Ok(mem::take(bytes)))?)?
-                                .collect_vec();
-
-                        self.create_ss_table(*gen, reload_data, LEVEL_0)?
+    /// 获取Table，未缓存时从文件中加载
+    ///
+    /// level为该Table于Version中所处的Level，用于判断加载时是否需要固定其Index Block
+    pub(crate) fn get(&self, gen: i64, level: usize) -> Option<&dyn Table> {
+        let mut is_hit = true;
+        let result = self.inner.get_or_insert(gen, |gen| {
+            is_hit = false;
+            let sst_factory = &self.factory;
+
+            let table: BoxTable = match sst_factory
+                .reader(*gen, IoType::Direct)
+                .and_then(|reader| SSTable::load_from_file(reader, self.cache.clone()))
+                .and_then(|ss_table| ss_table.pin_index_block(level))
+            {
+                Ok(ss_table) => Box::new(ss_table),
+                Err(err) => {
+                    // 尝试恢复仅对Level 0的Table有效
+                    warn!(
+                        "[LSMStore][Load Table: {}][try to reload with wal]: {:?}",
+                        gen, err
+                    );
+                    let reload_data =
+                        logs_decode(self.wal.load(*gen, |bytes| Ok(mem::take(bytes)))?)?
+                            .collect_vec();
+
+                    // 只读模式下不写入文件，仅在内存中重建
+                    if self.config.read_only {
+                        Box::new(SkipTable::new(LEVEL_0, *gen, reload_data))
+                    } else {
+                        Box::new(self.create_ss_table(*gen, reload_data, LEVEL_0)?)
                     }
-                };
+                }
+            };
+
+            Ok(table)
+        });
+
+        self.recorder.record_access(is_hit);
+        if !is_hit && result.is_ok() {
+            self.recorder.record_insert();
+        }
+        result.map(Box::as_ref).ok()
+    }
 
-                Ok(Box::new(ss_table))
-            })
-            .map(Box::as_ref)
-            .ok()
+    /// 通过同Gen的WAL重建Level 0的Table，用于恢复宕机前尚未持久化至Version的WAL
+    ///
+    /// 该Gen残留的Table文件会被替换，WAL中不存在数据时返回None
+    pub(crate) async fn create_with_wal(
+        &self,
+        gen: i64,
+        table_type: TableType,
+    ) -> Result<Option<(Scope, TableMeta)>> {
+        // WAL中的数据以写入顺序排列，需要以Key排序并保留每个Key最新的数据
+        let reload_data = logs_decode(self.wal.load(gen, |bytes| Ok(mem::take(bytes)))?)?
+            .rev()
+            .unique_by(|(key, _)| key.clone())
+            .sorted_by(|(key_a, _), (key_b, _)| key_a.cmp(key_b))
+            .collect_vec();
+        if reload_data.is_empty() {
+            return Ok(None);
+        }
+        if self.factory.exists(gen)? {
+            let _ = self.remove(&gen);
+            self.factory.clean(gen)?;
+        }
+
+        self.create(gen, reload_data, LEVEL_0, table_type)
+            .await
+            .map(Some)
     }
 
     fn create_ss_table(
@@ -106,7 +181,7 @@ impl TableLoader {
         SSTable::new(
             &self.factory,
             &self.config,
-            Arc::clone(&self.cache),
+            self.cache.clone(),
             gen,
             reload_data,
             level,
@@ -123,8 +198,76 @@ impl TableLoader {
         self.inner.is_empty()
     }
 
+    /// Table缓存的统计，驱逐数由缓存自身记录
+    pub(crate) fn table_cache_counter(&self) -> CacheCounter {
+        CacheCounter {
+            evictions: self.inner.evictions(),
+            ..self.recorder.counter()
+        }
+    }
+
+    /// Table缓存中的Table数量
+    pub(crate) fn table_cache_len(&self) -> usize {
+        self.inner.len()
+    }
+
+    pub(crate) fn block_cache(&self) -> &BlockCache {
+        self.cache.cache()
+    }
+
+    /// Table缓存中各Table过滤器的内存占用之和
+    pub(crate) fn filter_usage(&self) -> usize {
+        self.inner.sum_by(|table| table.filter_size())
+    }
+
+    /// Table缓存中各Level的Table过滤器的内存占用之和，以Level为下标
+    ///
+    /// Table所处的Level以Version为准，Trivial Move后Table中记录的Level并不会变更
+    pub(crate) fn filter_usage_by_level(&self, version: &Version) -> Vec<usize> {
+        let gen_levels: HashMap<i64, usize> = version
+            .level_slice
+            .iter()
+            .enumerate()
+            .flat_map(|(level, scopes)| scopes.iter().map(move |scope| (scope.get_gen(), level)))
+            .collect();
+        let mut usages = vec![0; self.config.num_levels];
+        for (level, usage) in usages.iter_mut().enumerate() {
+            *usage = self.inner.sum_by(|table| {
+                if gen_levels.get(&table.gen()) == Some(&level) {
+                    table.filter_size()
+                } else {
+                    0
+                }
+            });
+        }
+        usages
+    }
+
+    /// 点查时各Level过滤器的判断统计
+    pub(crate) fn filter_recorder(&self) -> &FilterRecorder {
+        &self.filter_recorder
+    }
+
+    /// 固定于Block缓存中的Index Block的内存占用
+    pub(crate) fn pinned_index_usage(&self) -> usize {
+        self.cache.pinned_usage()
+    }
+
+    /// Table通过Trivial Move移动至level后，若该Level无需固定Index Block则解除其固定
+    pub(crate) fn unpin_index_with_level(&self, gen: i64, level: usize) {
+        if !self.cache.is_pinned_level(level) {
+            self.cache.unpin_index(gen);
+        }
+    }
+
+    /// 解除所有Index Block的固定，使共享的Block缓存可回收其空间
+    pub(crate) fn unpin_all(&self) {
+        self.cache.unpin_all()
+    }
+
     pub(crate) fn clean(&self, gen: i64) -> Result<()> {
         let _ = self.remove(&gen);
+        self.cache.unpin_index(gen);
         self.factory.clean(gen)?;
         self.wal.clean(gen)?;
 
@@ -141,6 +284,7 @@ impl TableLoader {
 #[cfg(test)]
 mod tests {
     use crate::kernel::io::{FileExtension, IoFactory, IoType};
+    use crate::kernel::lsm::compactor::LEVEL_0;
     use crate::kernel::lsm::log::LogLoader;
     use crate::kernel::lsm::mem_table::{data_to_bytes, DEFAULT_WAL_PATH};
     use crate::kernel::lsm::storage::Config;
@@ -193,16 +337,27 @@ mod tests {
 
         let sst_loader = TableLoader::new(config, sst_factory.clone(), log_loader.clone())?;
 
-        let _ = sst_loader.create(1, vec_data.clone(), 0, TableType::SortedString)?;
+        let _ = tokio_test::block_on(sst_loader.create(
+            1,
+            vec_data.clone(),
+            0,
+            TableType::SortedString,
+        ))?;
 
         assert!(sst_loader.remove(&1).is_some());
         assert!(sst_loader.is_emtpy());
 
-        let ss_table_loaded = sst_loader.get(1).unwrap();
+        let ss_table_loaded = sst_loader.get(1, LEVEL_0).unwrap();
 
-        assert_eq!(ss_table_loaded.query(&repeat_data.0)?, repeat_data.1);
+        assert_eq!(
+            ss_table_loaded.query(&repeat_data.0)?,
+            Some(repeat_data.1.clone())
+        );
         for i in 1..times {
-            assert_eq!(ss_table_loaded.query(&vec_data[i].0)?, Some(value.clone()))
+            assert_eq!(
+                ss_table_loaded.query(&vec_data[i].0)?,
+                Some(Some(value.clone()))
+            )
         }
 
         // 模拟SSTable异常而使用Wal进行恢复的情况
@@ -211,11 +366,17 @@ mod tests {
         clean_sst(1, &sst_loader).unwrap();
         assert!(!sst_factory.exists(1).unwrap());
 
-        let ss_table_backup = sst_loader.get(1).unwrap();
+        let ss_table_backup = sst_loader.get(1, LEVEL_0).unwrap();
 
-        assert_eq!(ss_table_backup.query(&repeat_data.0)?, repeat_data.1);
+        assert_eq!(
+            ss_table_backup.query(&repeat_data.0)?,
+            Some(repeat_data.1.clone())
+        );
         for i in 1..times {
-            assert_eq!(ss_table_backup.query(&vec_data[i].0)?, Some(value.clone()))
+            assert_eq!(
+                ss_table_backup.query(&vec_data[i].0)?,
+                Some(Some(value.clone()))
+            )
         }
         Ok(())
     }
@@ -1,7 +1,11 @@
 use crate::kernel::lsm::iterator::Iter;
 use crate::kernel::lsm::mem_table::KeyValue;
+use crate::kernel::lsm::prefix::PrefixExtractor;
+use crate::kernel::lsm::storage::ReadOptions;
 use crate::kernel::lsm::table::meta::TableMeta;
+use crate::kernel::lsm::table::scope::Scope;
 use crate::kernel::Result;
+use crate::KernelError;
 use bytes::Bytes;
 use itertools::Itertools;
 
@@ -21,17 +25,51 @@ pub enum TableType {
 pub(crate) type BoxTable = Box<dyn Table>;
 
 pub(crate) trait Table: Sync + Send {
-    fn query(&self, key: &[u8]) -> Result<Option<Bytes>>;
+    /// 查询Key对应的数据
+    ///
+    /// 外层None表示该Table中不存在此Key，Some(None)表示命中删除标记
+    ///
+    /// Tips: 不会经过过滤器判断，调用方应先通过may_contain排除不存在的Key
+    fn query(&self, key: &[u8]) -> Result<Option<Option<Bytes>>>;
+
+    /// 通过过滤器判断Key是否可能存在，返回false时Key必然不存在
+    fn may_contain(&self, key: &[u8]) -> bool;
+
+    /// may_contain是否由过滤器进行判断，为false时不计入过滤器统计
+    fn has_filter(&self) -> bool {
+        false
+    }
+
+    /// 通过前缀过滤器判断是否可能存在以prefix为前缀的Key，返回false时必然不存在
+    fn may_contain_prefix(&self, _extractor: &dyn PrefixExtractor, _prefix: &[u8]) -> bool {
+        true
+    }
 
     fn len(&self) -> usize;
 
+    /// Table中删除标记的数量
+    fn tombstone_len(&self) -> usize;
+
+    /// 过滤器的内存占用，单位为B
+    fn filter_size(&self) -> usize;
+
     fn size_of_disk(&self) -> u64;
 
     fn gen(&self) -> i64;
 
+    /// 创建时所处的Level
+    ///
+    /// Tips: Trivial Move不会修改Table，因此所处的Level应以Version中的为准
     fn level(&self) -> usize;
 
-    fn iter<'a>(&'a self) -> Result<Box<dyn Iter<'a, Item = KeyValue> + 'a>>;
+    fn iter<'a>(&'a self) -> Result<Box<dyn Iter<'a, Item = KeyValue> + 'a>> {
+        self.iter_with_options(ReadOptions::default())
+    }
+
+    fn iter_with_options<'a>(
+        &'a self,
+        options: ReadOptions,
+    ) -> Result<Box<dyn Iter<'a, Item = KeyValue> + 'a>>;
 }
 
 /// 通过一组SSTable收集对应的Gen
@@ -43,3 +81,31 @@ pub(crate) fn collect_gen(vec_table: &[&dyn Table]) -> Result<(Vec<i64>, TableMe
         meta,
     ))
 }
+
+/// 完整遍历Table以校验其数据，并计算其Scope
+///
+/// 数据应以Key严格递增，且条数与Table的统计数据相符
+pub(crate) fn scan_scope(table: &dyn Table) -> Result<Scope> {
+    let mut iter = table.iter()?;
+    let mut option_first = None;
+    let mut option_last = None;
+    let mut len = 0;
+
+    while let Some(item) = iter.next_err()? {
+        if matches!(&option_last, Some((last_key, _)) if last_key >= &item.0) {
+            return Err(KernelError::TableCorrupted(table.gen()));
+        }
+        if option_first.is_none() {
+            option_first = Some(item.clone());
+        }
+        option_last = Some(item);
+        len += 1;
+    }
+
+    match (option_first, option_last) {
+        (Some(first), Some(last)) if len == table.len() => {
+            Ok(Scope::from_data(table.gen(), &first, &last))
+        }
+        _ => Err(KernelError::TableCorrupted(table.gen())),
+    }
+}
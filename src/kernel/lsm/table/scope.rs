@@ -20,6 +20,10 @@ impl Scope {
         self.gen
     }
 
+    pub(crate) fn set_gen(&mut self, gen: i64) {
+        self.gen = gen;
+    }
+
     /// 由KeyValue组成的Key构成scope
     pub(crate) fn from_data(gen: i64, first: &KeyValue, last: &KeyValue) -> Self {
         Scope {
@@ -52,9 +56,10 @@ impl Scope {
     }
 
     /// 判断scope之间是否相交
+    ///
+    /// Tips: 需要覆盖target完全包含self的情况，否则会漏判更底层的数据
     pub(crate) fn meet(&self, target: &Scope) -> bool {
-        (self.start.le(&target.start) && self.end.ge(&target.start))
-            || (self.start.le(&target.end) && self.end.ge(&target.end))
+        self.start.le(&target.end) && self.end.ge(&target.start)
     }
 
     /// 判断key与Scope是否相交
@@ -62,6 +67,12 @@ impl Scope {
         self.start.as_ref().le(key) && self.end.as_ref().ge(key)
     }
 
+    /// 判断Scope中是否可能存在以prefix为前缀的Key
+    pub(crate) fn meet_by_prefix(&self, prefix: &[u8]) -> bool {
+        self.end.as_ref().ge(prefix)
+            && (self.start.as_ref().le(prefix) || self.start.starts_with(prefix))
+    }
+
     #[allow(dead_code)]
     pub(crate) fn meet_bound(&self, min: Bound<&[u8]>, max: Bound<&[u8]>) -> bool {
         let is_min_inside = match min {
@@ -2,6 +2,7 @@ mod iter;
 
 use crate::kernel::lsm::iterator::Iter;
 use crate::kernel::lsm::mem_table::KeyValue;
+use crate::kernel::lsm::storage::ReadOptions;
 use crate::kernel::lsm::table::skip_table::iter::SkipTableIter;
 use crate::kernel::lsm::table::Table;
 use bytes::Bytes;
@@ -11,6 +12,7 @@ pub(crate) struct SkipTable {
     level: usize,
     gen: i64,
     len: usize,
+    tombstone_len: usize,
     inner: SkipMap<Bytes, Option<Bytes>>,
 }
 
@@ -20,20 +22,34 @@ impl SkipTable {
             level,
             gen,
             len: data.len(),
+            tombstone_len: data.iter().filter(|(_, value)| value.is_none()).count(),
             inner: SkipMap::from_iter(data),
         }
     }
 }
 
 impl Table for SkipTable {
-    fn query(&self, key: &[u8]) -> crate::kernel::Result<Option<Bytes>> {
-        Ok(self.inner.get(key).cloned().flatten())
+    fn query(&self, key: &[u8]) -> crate::kernel::Result<Option<Option<Bytes>>> {
+        Ok(self.inner.get(key).cloned())
+    }
+
+    fn may_contain(&self, key: &[u8]) -> bool {
+        self.inner.contains_key(key)
     }
 
     fn len(&self) -> usize {
         self.len
     }
 
+    fn tombstone_len(&self) -> usize {
+        self.tombstone_len
+    }
+
+    /// SkipTable直接通过数据判断Key是否存在，没有过滤器
+    fn filter_size(&self) -> usize {
+        0
+    }
+
     fn size_of_disk(&self) -> u64 {
         0
     }
@@ -46,7 +62,11 @@ impl Table for SkipTable {
         self.level
     }
 
-    fn iter<'a>(&'a self) -> crate::kernel::Result<Box<dyn Iter<'a, Item = KeyValue> + 'a>> {
+    /// SkipTable数据位于内存中，因此忽略读取选项
+    fn iter_with_options<'a>(
+        &'a self,
+        _options: ReadOptions,
+    ) -> crate::kernel::Result<Box<dyn Iter<'a, Item = KeyValue> + 'a>> {
         Ok(Box::new(SkipTableIter::new(&self)))
     }
 }
@@ -1,5 +1,9 @@
 use crate::kernel::lsm::storage::Config;
-use crate::kernel::utils::lru_cache::ShardingLruCache;
+use crate::kernel::lsm::table::ss_table::filter::Filter;
+use crate::kernel::lsm::table::ss_table::footer::{FORMAT_VERSION, FORMAT_VERSION_LEGACY};
+use crate::kernel::lsm::table::ss_table::secondary_cache::SecondaryCache;
+use crate::kernel::utils::cache_counter::{CacheCounter, CacheRecorder};
+use crate::kernel::utils::charge_cache::{CachePolicy, ChargeListener, ShardingChargeCache};
 use crate::kernel::Result;
 use crate::KernelError;
 use bytes::{Buf, BufMut, Bytes};
@@ -7,19 +11,342 @@ use growable_bloom_filter::GrowableBloom;
 use integer_encoding::{FixedInt, VarIntReader, VarIntWriter};
 use itertools::Itertools;
 use lz4::Decoder;
+use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
 use std::cmp::min;
-use std::collections::Bound;
+use std::collections::hash_map::RandomState;
+use std::collections::{Bound, HashMap};
+use std::fmt::{self, Debug, Formatter};
 use std::io::{Cursor, Read, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
 use std::{cmp, mem};
+use tracing::warn;
 
-/// BlockCache类型 可同时缓存两种类型
+/// Block缓存的Key，依次为缓存句柄的id、SSTable的gen与Data Block的索引
 ///
-/// Key为SSTable的gen且Index为None时返回Index类型
+/// Index为None时对应Index Block，为Some时对应Data Block
+pub(crate) type BlockCacheKey = (u64, i64, Option<Index>);
+
+/// 用于分配Block缓存句柄的id，进程内唯一，使共享同一二级缓存的不同Block缓存之间也不会冲突
+static NEXT_HANDLE_ID: AtomicU64 = AtomicU64::new(0);
+
+pub(crate) const DEFAULT_BLOCK_CACHE_SHARDING: usize = 16;
+
+/// 常驻于Block缓存中的Index Block范围
+///
+/// 被固定的Index Block在SSTable被删除或Store关闭前不会被驱逐，
+/// 其内存占用计入Block缓存的容量，以避免点查因Index Block被驱逐而产生额外的IO
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PinIndexBlocks {
+    /// 不固定，Index Block与Data Block一同参与驱逐
+    None,
+    /// 仅固定Level 0的Index Block
+    Level0,
+    /// 固定所有Level的Index Block
+    All,
+}
+
+/// 以字节为容量单位的Block缓存
+///
+/// 每个Block以其解码后的内存占用作为权重，所有Block的权重之和不超过容量，
+/// 可通过`Config::block_cache`使同一进程中的多个LsmStore共享同一个缓存以统一内存上限
+///
+/// Tips: 容量会均分至各分片中，解码后超出单个分片容量的Block将不会被缓存
+pub struct BlockCache {
+    inner: ShardingChargeCache<BlockCacheKey, BlockType>,
+    listener: Arc<BlockCacheListener>,
+    secondary: Option<Arc<SecondaryCache>>,
+}
+
+impl BlockCache {
+    /// 创建容量为capacity(单位为B)的Block缓存，使用Lru驱逐策略
+    #[inline]
+    pub fn new(capacity: usize) -> Result<Arc<Self>> {
+        Self::with_policy(capacity, CachePolicy::Lru)
+    }
+
+    /// 创建容量为capacity(单位为B)且使用指定驱逐策略的Block缓存
+    ///
+    /// 存在大范围扫描时可使用`CachePolicy::TinyLfu`避免热点Block被扫描挤出
+    #[inline]
+    pub fn with_policy(capacity: usize, policy: CachePolicy) -> Result<Arc<Self>> {
+        Self::build(capacity, policy, None)
+    }
+
+    /// 创建带有二级缓存的Block缓存，被驱逐的Block会写入secondary中
+    #[inline]
+    pub fn with_secondary_cache(
+        capacity: usize,
+        policy: CachePolicy,
+        secondary: Arc<SecondaryCache>,
+    ) -> Result<Arc<Self>> {
+        Self::build(capacity, policy, Some(secondary))
+    }
+
+    fn build(
+        capacity: usize,
+        policy: CachePolicy,
+        secondary: Option<Arc<SecondaryCache>>,
+    ) -> Result<Arc<Self>> {
+        let listener = Arc::new(BlockCacheListener {
+            demoted: secondary.as_ref().map(|_| Mutex::new(Vec::new())),
+            ..Default::default()
+        });
+        let cache_listener = Arc::clone(&listener);
+
+        Ok(Arc::new(BlockCache {
+            inner: ShardingChargeCache::new(
+                capacity,
+                DEFAULT_BLOCK_CACHE_SHARDING,
+                RandomState::default(),
+                BlockType::charge,
+                policy,
+            )?
+            .listener(cache_listener),
+            listener,
+            secondary,
+        }))
+    }
+
+    /// 缓存的容量，单位为B
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.inner.capacity()
+    }
+
+    /// 当前缓存的Block所占用的内存，单位为B
+    #[inline]
+    pub fn usage(&self) -> usize {
+        self.inner.usage()
+    }
+
+    /// Index Block的缓存统计
+    #[inline]
+    pub fn index_counter(&self) -> CacheCounter {
+        self.listener.index.counter()
+    }
+
+    /// Data Block的缓存统计
+    #[inline]
+    pub fn data_counter(&self) -> CacheCounter {
+        self.listener.data.counter()
+    }
+
+    #[inline]
+    pub fn secondary_cache(&self) -> Option<&Arc<SecondaryCache>> {
+        self.secondary.as_ref()
+    }
+
+    /// 分配一个独立id的句柄，每个Store(或独立的读取流程)使用各自的句柄
+    pub(crate) fn handle(self: &Arc<Self>) -> BlockCacheHandle {
+        BlockCacheHandle {
+            id: NEXT_HANDLE_ID.fetch_add(1, Ordering::Relaxed),
+            cache: Arc::clone(self),
+            pin_index_blocks: PinIndexBlocks::None,
+            pinned: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// 将被驱逐的Block写入二级缓存
+    ///
+    /// 驱逐发生于分片锁内，因此被驱逐的Block先暂存于监听器中，在缓存操作结束后再写入文件
+    fn demote(&self) {
+        if let (Some(secondary), Some(demoted)) = (&self.secondary, &self.listener.demoted) {
+            let vec_block = mem::take(&mut *demoted.lock());
+
+            for (key, block) in vec_block {
+                if let Err(err) = secondary.insert(key, &block) {
+                    warn!("[BlockCache][demote][SecondaryCache]: {:?}", err);
+                }
+            }
+        }
+    }
+
+    /// 先后从二级缓存与fn_once中加载Block
+    fn load<F>(&self, key: &BlockCacheKey, fn_once: F) -> Result<BlockType>
+    where
+        F: FnOnce() -> Result<BlockType>,
+    {
+        match self
+            .secondary
+            .as_ref()
+            .and_then(|secondary| secondary.lookup(key))
+        {
+            Some(block) => Ok(block),
+            None => fn_once(),
+        }
+    }
+}
+
+/// Block缓存的监听器
 ///
-/// Key为SSTable的gen且Index为Some时返回Data类型
-#[allow(dead_code)]
-pub(crate) type BlockCache = ShardingLruCache<(i64, Option<Index>), BlockType>;
+/// 以Block类型区分缓存统计，并在设置了二级缓存时暂存被驱逐的Block
+#[derive(Default)]
+struct BlockCacheListener {
+    index: CacheRecorder,
+    data: CacheRecorder,
+    demoted: Option<Mutex<Vec<(BlockCacheKey, BlockType)>>>,
+}
+
+impl BlockCacheListener {
+    fn recorder(&self, index: &Option<Index>) -> &CacheRecorder {
+        if index.is_some() {
+            &self.data
+        } else {
+            &self.index
+        }
+    }
+}
+
+impl ChargeListener<BlockCacheKey, BlockType> for BlockCacheListener {
+    fn on_insert(&self, (_, _, index): &BlockCacheKey, _: &BlockType) {
+        self.recorder(index).record_insert();
+    }
+
+    fn on_evict(&self, key: &BlockCacheKey, block: &BlockType) {
+        self.recorder(&key.2).record_evictions(1);
+        if let Some(demoted) = &self.demoted {
+            demoted.lock().push((*key, block.clone()));
+        }
+    }
+}
+
+impl Debug for BlockCache {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BlockCache")
+            .field("capacity", &self.capacity())
+            .field("usage", &self.usage())
+            .field("index_counter", &self.index_counter())
+            .field("data_counter", &self.data_counter())
+            .field("secondary", &self.secondary)
+            .finish()
+    }
+}
+
+/// Block缓存的句柄
+///
+/// 以句柄的id作为Key的前缀，使共享缓存的不同Store之间不会因gen相同而读取到错误的Block
+#[derive(Clone)]
+pub(crate) struct BlockCacheHandle {
+    id: u64,
+    cache: Arc<BlockCache>,
+    pin_index_blocks: PinIndexBlocks,
+    /// 已固定Index Block的SSTable gen及其内存占用，由同一句柄的克隆共享
+    pinned: Arc<Mutex<HashMap<i64, usize>>>,
+}
+
+impl BlockCacheHandle {
+    pub(crate) fn pin_index_blocks(mut self, pin_index_blocks: PinIndexBlocks) -> Self {
+        self.pin_index_blocks = pin_index_blocks;
+        self
+    }
+
+    pub(crate) fn cache(&self) -> &BlockCache {
+        &self.cache
+    }
+
+    /// 该Level的SSTable是否需要固定其Index Block
+    pub(crate) fn is_pinned_level(&self, level: usize) -> bool {
+        match self.pin_index_blocks {
+            PinIndexBlocks::None => false,
+            PinIndexBlocks::Level0 => level == 0,
+            PinIndexBlocks::All => true,
+        }
+    }
+
+    /// 固定SSTable的Index Block，已固定时不做处理
+    ///
+    /// 同一SSTable因Table缓存驱逐而被重新加载时，可复用已固定的Index Block
+    ///
+    /// Tips: fn_once在锁外执行，避免各Table加载时的磁盘IO被串行化；
+    /// 并发固定同一SSTable时可能重复执行fn_once，插入前会再次检查，以先固定的Block为准
+    pub(crate) fn pin_index<F>(&self, gen: i64, fn_once: F) -> Result<()>
+    where
+        F: FnOnce() -> Result<Block<Index>>,
+    {
+        if self.pinned.lock().contains_key(&gen) {
+            return Ok(());
+        }
+        let block_type = BlockType::Index(Arc::new(fn_once()?));
+        {
+            let mut pinned = self.pinned.lock();
+            if pinned.contains_key(&gen) {
+                return Ok(());
+            }
+            let _ = pinned.insert(gen, block_type.charge());
+            self.cache.inner.pin((self.id, gen, None), block_type);
+        }
+        self.cache.demote();
+
+        Ok(())
+    }
+
+    /// 解除SSTable的Index Block的固定，用于SSTable被删除或移动至无需固定的Level时
+    pub(crate) fn unpin_index(&self, gen: i64) {
+        if self.pinned.lock().remove(&gen).is_some() {
+            let _ = self.cache.inner.unpin(&(self.id, gen, None));
+        }
+    }
+
+    /// 解除该句柄所有的固定，用于Store关闭时归还共享缓存的容量
+    pub(crate) fn unpin_all(&self) {
+        for (gen, _) in self.pinned.lock().drain() {
+            let _ = self.cache.inner.unpin(&(self.id, gen, None));
+        }
+    }
+
+    /// 该句柄所固定的Index Block的内存占用，单位为B
+    pub(crate) fn pinned_usage(&self) -> usize {
+        self.pinned.lock().values().sum()
+    }
+
+    /// 仅获取已缓存的Block，不存在时不会从SSTable中加载
+    ///
+    /// Block缓存未命中时会查询二级缓存，但不会将其结果放入Block缓存中
+    pub(crate) fn get(&self, key: (i64, Option<Index>)) -> Option<BlockType> {
+        let (gen, index) = key;
+        let key = (self.id, gen, index);
+        let option_block = self.cache.inner.get(&key);
+
+        self.cache
+            .listener
+            .recorder(&index)
+            .record_access(option_block.is_some());
+        option_block.or_else(|| {
+            self.cache
+                .secondary
+                .as_ref()
+                .and_then(|secondary| secondary.lookup(&key))
+        })
+    }
+
+    pub(crate) fn get_or_insert<F>(
+        &self,
+        key: (i64, Option<Index>),
+        fn_once: F,
+    ) -> Result<BlockType>
+    where
+        F: FnOnce(&(i64, Option<Index>)) -> Result<BlockType>,
+    {
+        let (gen, index) = key;
+        let mut is_hit = true;
+        let result = self
+            .cache
+            .inner
+            .get_or_insert((self.id, gen, index), |cache_key| {
+                is_hit = false;
+                Ok(self.cache.load(cache_key, || fn_once(&key))?)
+            });
+
+        self.cache.listener.recorder(&index).record_access(is_hit);
+        if !is_hit {
+            self.cache.demote();
+        }
+        Ok(result?)
+    }
+}
 
 pub(crate) const DEFAULT_BLOCK_SIZE: usize = 4 * 1024;
 
@@ -32,9 +359,20 @@ const CRC_SIZE: usize = 4;
 
 pub(crate) type KeyValue<T> = (Bytes, T);
 
+#[derive(Clone)]
 pub(crate) enum BlockType {
-    Data(Block<Value>),
-    Index(Block<Index>),
+    Data(Arc<Block<Value>>),
+    Index(Arc<Block<Index>>),
+}
+
+impl BlockType {
+    /// Block解码后的内存占用估算，作为其在缓存中的权重
+    pub(crate) fn charge(&self) -> usize {
+        match self {
+            BlockType::Data(block) => block.charge(),
+            BlockType::Index(block) => block.charge(),
+        }
+    }
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -73,7 +411,7 @@ where
         let mut vec_entry = Vec::new();
         let mut index = 0;
 
-        while !cursor.is_empty() {
+        while (cursor.position() as usize) < cursor.get_ref().len() {
             vec_entry.push((index, Self::decode(cursor)?));
             index += 1;
         }
@@ -119,7 +457,7 @@ pub(crate) struct Index {
 }
 
 impl Index {
-    fn new(offset: u32, len: usize) -> Self {
+    pub(crate) fn new(offset: u32, len: usize) -> Self {
         Index { offset, len }
     }
 
@@ -139,6 +477,9 @@ pub(crate) trait BlockItem: Sized + Clone {
         T: Read + ?Sized;
 
     fn encode(&self) -> Result<Vec<u8>>;
+
+    /// 数据在堆上的内存占用
+    fn heap_size(&self) -> usize;
 }
 
 impl BlockItem for Value {
@@ -166,6 +507,10 @@ impl BlockItem for Value {
         }
         Ok(buf)
     }
+
+    fn heap_size(&self) -> usize {
+        self.value_len
+    }
 }
 
 impl BlockItem for Index {
@@ -186,6 +531,10 @@ impl BlockItem for Index {
 
         Ok(buf)
     }
+
+    fn heap_size(&self) -> usize {
+        0
+    }
 }
 
 #[derive(Clone, Copy)]
@@ -196,10 +545,55 @@ pub(crate) enum CompressType {
 
 #[derive(Serialize, Deserialize, Debug)]
 pub(crate) struct MetaBlock {
-    pub(crate) filter: GrowableBloom,
+    pub(crate) filter: Filter,
     pub(crate) len: usize,
+    pub(crate) tombstone_len: usize,
     pub(crate) index_restart_interval: usize,
     pub(crate) data_restart_interval: usize,
+    /// 由Config::prefix_extractor提取的Key前缀的过滤器，未设置提取器或该Level不使用过滤器时为None
+    pub(crate) prefix_filter: Option<PrefixFilter>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct PrefixFilter {
+    /// 生成该过滤器的PrefixExtractor名称
+    pub(crate) extractor: String,
+    pub(crate) filter: Filter,
+}
+
+impl MetaBlock {
+    /// 依照Footer中记录的格式版本解码MetaBlock
+    pub(crate) fn decode(bytes: &[u8], version: u32) -> Result<Self> {
+        match version {
+            FORMAT_VERSION_LEGACY => Ok(bincode::deserialize::<LegacyMetaBlock>(bytes)?.into()),
+            FORMAT_VERSION => Ok(bincode::deserialize(bytes)?),
+            version => Err(KernelError::UnsupportedTableFormat(version)),
+        }
+    }
+}
+
+/// 旧格式(FORMAT_VERSION_LEGACY)的MetaBlock
+#[derive(Serialize, Deserialize, Debug)]
+struct LegacyMetaBlock {
+    filter: GrowableBloom,
+    len: usize,
+    index_restart_interval: usize,
+    data_restart_interval: usize,
+}
+
+impl From<LegacyMetaBlock> for MetaBlock {
+    /// 旧格式未记录删除标记数量，因此视为0，即不会因删除标记密集而被优先压缩；
+    /// 同样不存在前缀过滤器，前缀查询时不进行过滤
+    fn from(meta: LegacyMetaBlock) -> Self {
+        MetaBlock {
+            filter: Filter::Growable(meta.filter),
+            len: meta.len,
+            tombstone_len: 0,
+            index_restart_interval: meta.index_restart_interval,
+            data_restart_interval: meta.data_restart_interval,
+            prefix_filter: None,
+        }
+    }
 }
 
 /// Block SSTable最小的存储单位
@@ -388,15 +782,14 @@ impl BlockBuilder {
 
 impl Block<Value> {
     /// 通过Key查询对应Value
-    pub(crate) fn find(&self, key: &[u8]) -> Option<Bytes> {
-        self.binary_search(key)
-            .ok()
-            .and_then(|index| {
-                self.vec_entry
-                    .get(index)
-                    .map(|(_, entry)| entry.item.bytes.clone())
-            })
-            .flatten()
+    ///
+    /// 命中删除标记时返回Some(None)
+    pub(crate) fn find(&self, key: &[u8]) -> Option<Option<Bytes>> {
+        self.binary_search(key).ok().and_then(|index| {
+            self.vec_entry
+                .get(index)
+                .map(|(_, entry)| entry.item.bytes.clone())
+        })
     }
 }
 
@@ -414,6 +807,14 @@ impl<T> Block<T> {
         &self.vec_entry[index - index % self.restart_interval].1.key[0..shared_len]
     }
 
+    /// 与`Block::shared_key_prefix`相同，但返回共享所有权的Bytes以便在Block之外持有
+    pub(crate) fn shared_key_prefix_bytes(&self, index: usize, shared_len: usize) -> Bytes {
+        self.vec_entry[index - index % self.restart_interval]
+            .1
+            .key
+            .slice(0..shared_len)
+    }
+
     pub(crate) fn restart_interval(&self) -> usize {
         self.restart_interval
     }
@@ -441,6 +842,18 @@ impl<T> Block<T>
 where
     T: BlockItem,
 {
+    /// 估算该Block解码后的内存占用
+    pub(crate) fn charge(&self) -> usize {
+        size_of::<Self>()
+            + self
+                .vec_entry
+                .iter()
+                .map(|(_, entry)| {
+                    size_of::<(usize, Entry<T>)>() + entry.key.len() + entry.item.heap_size()
+                })
+                .sum::<usize>()
+    }
+
     /// 新建Block，同时Block会进行前缀压缩
     pub(crate) fn new(vec_kv: Vec<KeyValue<T>>, restart_interval: usize) -> Block<T> {
         let vec_sharding_len = sharding_shared_len(&vec_kv, restart_interval);
@@ -571,8 +984,11 @@ where
 
     /// 读取Bytes进行Block的反序列化
     pub(crate) fn from_raw(mut buf: Vec<u8>, restart_interval: usize) -> Result<Self> {
-        let date_bytes_len = buf.len() - CRC_SIZE;
-        if crc32fast::hash(&buf) == u32::decode_fixed(&buf[date_bytes_len..]) {
+        let date_bytes_len = buf
+            .len()
+            .checked_sub(CRC_SIZE)
+            .ok_or(KernelError::CrcMisMatch)?;
+        if crc32fast::hash(&buf[..date_bytes_len]) != u32::decode_fixed(&buf[date_bytes_len..]) {
             return Err(KernelError::CrcMisMatch);
         }
         buf.truncate(date_bytes_len);
@@ -744,7 +1160,7 @@ mod tests {
                 )?;
                 Ok(target_block)
             })?;
-            assert_eq!(data_block.find(key), Some(value.clone()))
+            assert_eq!(data_block.find(key), Some(Some(value.clone())))
         }
 
         test_block_serialization_(
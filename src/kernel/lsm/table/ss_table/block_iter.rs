@@ -2,28 +2,30 @@ use crate::kernel::lsm::iterator::{ForwardIter, Iter, Seek};
 use crate::kernel::lsm::table::ss_table::block::{Block, BlockItem, Entry};
 use crate::kernel::Result;
 use bytes::Bytes;
+use std::sync::Arc;
 
 /// Block迭代器
 ///
 /// Tips: offset偏移会额外向上偏移一位以使用0作为迭代的下界判断是否向前溢出了
-pub(crate) struct BlockIter<'a, T> {
-    block: &'a Block<T>,
+/// 迭代器持有Block的所有权，因此Block被缓存驱逐后依旧可以安全地迭代
+pub(crate) struct BlockIter<T> {
+    block: Arc<Block<T>>,
     entry_len: usize,
 
     offset: usize,
-    buf_shared_key: &'a [u8],
+    buf_shared_key: Bytes,
 }
 
-impl<'a, T> BlockIter<'a, T>
+impl<T> BlockIter<T>
 where
     T: BlockItem,
 {
-    pub(crate) fn new(block: &'a Block<T>) -> BlockIter<'a, T> {
-        let buf_shared_key = block.shared_key_prefix(0, block.restart_shared_len(0));
+    pub(crate) fn new(block: Arc<Block<T>>) -> BlockIter<T> {
+        let buf_shared_key = block.shared_key_prefix_bytes(0, block.restart_shared_len(0));
 
         BlockIter {
-            block,
             entry_len: block.entry_len(),
+            block,
             offset: 0,
             buf_shared_key,
         }
@@ -33,7 +35,7 @@ where
         let offset = self.offset - 1;
         let Entry { key, item, .. } = self.block.get_entry(offset);
         let item_key = if offset % self.block.restart_interval() != 0 {
-            Bytes::from([self.buf_shared_key, &key[..]].concat())
+            Bytes::from([&self.buf_shared_key[..], &key[..]].concat())
         } else {
             key.clone()
         };
@@ -42,8 +44,7 @@ where
     }
 
     fn offset_move(&mut self, offset: usize) -> Option<(Bytes, T)> {
-        let block = self.block;
-        let restart_interval = block.restart_interval();
+        let restart_interval = self.block.restart_interval();
 
         let old_offset = self.offset;
         self.offset = offset;
@@ -51,15 +52,17 @@ where
         (offset > 0).then(|| {
             let real_offset = offset - 1;
             if old_offset - 1 / restart_interval != real_offset / restart_interval {
-                self.buf_shared_key =
-                    block.shared_key_prefix(real_offset, block.restart_shared_len(real_offset));
+                self.buf_shared_key = self.block.shared_key_prefix_bytes(
+                    real_offset,
+                    self.block.restart_shared_len(real_offset),
+                );
             }
             self.item()
         })
     }
 }
 
-impl<'a, V> ForwardIter<'a> for BlockIter<'a, V>
+impl<V> ForwardIter<'_> for BlockIter<V>
 where
     V: Sync + Send + BlockItem,
 {
@@ -70,7 +73,7 @@ where
     }
 }
 
-impl<'a, V> Iter<'a> for BlockIter<'a, V>
+impl<V> Iter<'_> for BlockIter<V>
 where
     V: Sync + Send + BlockItem,
 {
@@ -107,6 +110,7 @@ mod tests {
     use crate::kernel::Result;
     use bincode::Options;
     use bytes::Bytes;
+    use std::sync::Arc;
     use std::vec;
 
     #[test]
@@ -121,7 +125,7 @@ mod tests {
         ];
         let block = Block::new(data, DEFAULT_DATA_RESTART_INTERVAL);
 
-        let mut iterator = BlockIter::new(&block);
+        let mut iterator = BlockIter::new(Arc::new(block));
 
         assert!(!iterator.is_valid());
 
@@ -202,7 +206,7 @@ mod tests {
         let block = Block::new(vec_data.clone(), DEFAULT_DATA_RESTART_INTERVAL);
 
         tokio_test::block_on(async move {
-            let mut iterator = BlockIter::new(&block);
+            let mut iterator = BlockIter::new(Arc::new(block));
 
             for i in 0..times {
                 assert_eq!(iterator.next_err()?.unwrap(), vec_data[i]);
@@ -0,0 +1,411 @@
+use growable_bloom_filter::GrowableBloom;
+use serde::{Deserialize, Serialize};
+use std::mem::size_of;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Ribbon过滤器中每个Key的系数宽度
+const RIBBON_WIDTH: usize = 64;
+/// Ribbon过滤器构建失败时更换种子重试的次数，全部失败后扩大槽位数
+const RIBBON_SEED_RETRIES: u64 = 4;
+
+/// SSTable中Key过滤器的类型
+///
+/// bits_per_key为每个Key平均占用的内存位数，越大则误判率越低
+/// - Bloom: 整Key布隆过滤器，bits_per_key为10时误判率约为1%
+/// - Ribbon: bits_per_key相同时内存占用与布隆过滤器相近而误判率更低(10时约为0.2%)，
+///   即相同误判率下更节省内存，但构建时的CPU开销更大，适用于数据量最大的底层Level
+/// - None: 不使用过滤器，点查时总是读取Block，适用于点查命中率极高的Level
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterPolicy {
+    None,
+    Bloom(usize),
+    Ribbon(usize),
+}
+
+impl FilterPolicy {
+    pub(crate) fn builder(self, len: usize) -> FilterBuilder {
+        FilterBuilder {
+            policy: self,
+            hashes: Vec::with_capacity(len),
+        }
+    }
+}
+
+/// 收集Key的哈希值，在数据全部写入后构建过滤器
+pub(crate) struct FilterBuilder {
+    policy: FilterPolicy,
+    hashes: Vec<u64>,
+}
+
+impl FilterBuilder {
+    pub(crate) fn add(&mut self, key: &[u8]) {
+        if !matches!(self.policy, FilterPolicy::None) {
+            self.hashes.push(hash(key));
+        }
+    }
+
+    pub(crate) fn build(mut self) -> Filter {
+        self.hashes.sort_unstable();
+        self.hashes.dedup();
+
+        match self.policy {
+            FilterPolicy::None => Filter::None,
+            FilterPolicy::Bloom(bits_per_key) => {
+                Filter::Bloom(BloomFilter::new(&self.hashes, bits_per_key))
+            }
+            FilterPolicy::Ribbon(bits_per_key) => {
+                Filter::Ribbon(RibbonFilter::new(&self.hashes, bits_per_key))
+            }
+        }
+    }
+}
+
+/// 持久化于MetaBlock中的过滤器
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) enum Filter {
+    None,
+    Bloom(BloomFilter),
+    Ribbon(RibbonFilter),
+    /// 旧格式(FORMAT_VERSION_LEGACY)SSTable的过滤器，仅用于读取，不会再被写入
+    Growable(GrowableBloom),
+}
+
+impl Filter {
+    /// 返回false时Key必然不存在，未使用过滤器时总是返回true
+    pub(crate) fn may_contain(&self, key: &[u8]) -> bool {
+        match self {
+            Filter::None => true,
+            Filter::Bloom(filter) => filter.may_contain(hash(key)),
+            Filter::Ribbon(filter) => filter.may_contain(hash(key)),
+            Filter::Growable(filter) => filter.contains(key),
+        }
+    }
+
+    pub(crate) fn is_none(&self) -> bool {
+        matches!(self, Filter::None)
+    }
+
+    /// 过滤器的内存占用，单位为B
+    pub(crate) fn size(&self) -> usize {
+        match self {
+            Filter::None => 0,
+            Filter::Bloom(filter) => filter.bits.len() * size_of::<u64>(),
+            Filter::Ribbon(filter) => filter.solution.len() * size_of::<u64>(),
+            // GrowableBloom未提供内存占用，以其序列化长度近似
+            Filter::Growable(filter) => {
+                bincode::serialized_size(filter).map_or(0, |size| size as usize)
+            }
+        }
+    }
+}
+
+/// 以双重哈希模拟多个哈希函数的布隆过滤器
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct BloomFilter {
+    bits: Vec<u64>,
+    num_probes: u32,
+}
+
+impl BloomFilter {
+    fn new(hashes: &[u64], bits_per_key: usize) -> Self {
+        let bits_per_key = bits_per_key.max(1);
+        // 最优的哈希函数数量为bits_per_key * ln2
+        let num_probes = (bits_per_key * 69 / 100).clamp(1, 30) as u32;
+        let num_bits = (hashes.len() * bits_per_key).max(RIBBON_WIDTH);
+        let mut filter = BloomFilter {
+            bits: vec![0; num_bits.div_ceil(64)],
+            num_probes,
+        };
+
+        for hash in hashes {
+            for bit in filter.probes(*hash) {
+                filter.bits[bit / 64] |= 1 << (bit & 63);
+            }
+        }
+        filter
+    }
+
+    fn may_contain(&self, hash: u64) -> bool {
+        self.probes(hash)
+            .all(|bit| self.bits[bit / 64] & (1 << (bit & 63)) != 0)
+    }
+
+    fn probes(&self, hash: u64) -> impl Iterator<Item = usize> {
+        let num_bits = self.bits.len() * 64;
+        let delta = hash.rotate_right(33) | 1;
+
+        (0..u64::from(self.num_probes))
+            .map(move |i| fast_range(hash.wrapping_add(delta.wrapping_mul(i)), num_bits))
+    }
+}
+
+/// Standard Ribbon过滤器
+///
+/// 每个Key对应从start开始的64位系数与fingerprint_bits位的指纹，
+/// 构建时通过高斯消元求解使各Key所选中槽位的异或值等于其指纹，
+/// 查询时异或值与指纹一致即视为可能存在，误判率约为2^-fingerprint_bits
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct RibbonFilter {
+    seed: u64,
+    num_slots: usize,
+    fingerprint_bits: u32,
+    /// 各槽位的解，每个槽位占用fingerprint_bits位
+    solution: Vec<u64>,
+}
+
+impl RibbonFilter {
+    fn new(hashes: &[u64], bits_per_key: usize) -> Self {
+        // 槽位数约为Key数量的1.07倍，因此指纹位数略小于bits_per_key
+        let fingerprint_bits = (bits_per_key * 15 / 16).clamp(1, 32) as u32;
+        let mut num_slots = hashes.len() + hashes.len() / 16 + RIBBON_WIDTH;
+
+        loop {
+            for seed in 0..RIBBON_SEED_RETRIES {
+                let mut filter = RibbonFilter {
+                    seed,
+                    num_slots,
+                    fingerprint_bits,
+                    solution: Vec::new(),
+                };
+                if filter.solve(hashes) {
+                    return filter;
+                }
+            }
+            num_slots += num_slots / 16;
+        }
+    }
+
+    fn may_contain(&self, hash: u64) -> bool {
+        let (start, coeff, fingerprint) = self.row(hash);
+
+        self.dot(start, coeff) == fingerprint
+    }
+
+    /// 由哈希值得到Key的起始槽位、系数与指纹
+    fn row(&self, hash: u64) -> (usize, u64, u64) {
+        let hash = mix(hash ^ self.seed.wrapping_mul(0x9E37_79B9_7F4A_7C15));
+        let start = fast_range(hash, self.num_slots - RIBBON_WIDTH + 1);
+        // 系数的最低位固定为1，保证起始槽位为该行的主元
+        let coeff = mix(hash.wrapping_add(1)) | 1;
+        let fingerprint = mix(hash.wrapping_add(2)) & self.fingerprint_mask();
+
+        (start, coeff, fingerprint)
+    }
+
+    fn solve(&mut self, hashes: &[u64]) -> bool {
+        let mut coeffs = vec![0_u64; self.num_slots];
+        let mut results = vec![0_u64; self.num_slots];
+
+        // 增量高斯消元，使每行的主元位于互不相同的槽位
+        for hash in hashes {
+            let (mut start, mut coeff, mut result) = self.row(*hash);
+            loop {
+                if coeffs[start] == 0 {
+                    coeffs[start] = coeff;
+                    results[start] = result;
+                    break;
+                }
+                coeff ^= coeffs[start];
+                result ^= results[start];
+                if coeff == 0 {
+                    // 与已有的行线性相关，结果不一致时无解
+                    if result != 0 {
+                        return false;
+                    }
+                    break;
+                }
+                let shift = coeff.trailing_zeros();
+                start += shift as usize;
+                coeff >>= shift;
+            }
+        }
+
+        // 由后至前回代求解
+        self.solution = vec![0; (self.num_slots * self.fingerprint_bits as usize).div_ceil(64)];
+        for slot in (0..self.num_slots).rev() {
+            let coeff = coeffs[slot];
+            let value = if coeff == 0 {
+                0
+            } else {
+                results[slot] ^ self.dot(slot + 1, coeff >> 1)
+            };
+            self.set_slot(slot, value);
+        }
+        true
+    }
+
+    /// 系数所选中的槽位的异或值
+    fn dot(&self, start: usize, mut coeff: u64) -> u64 {
+        let mut value = 0;
+
+        while coeff != 0 {
+            let offset = coeff.trailing_zeros() as usize;
+            value ^= self.slot(start + offset);
+            coeff &= coeff - 1;
+        }
+        value
+    }
+
+    fn slot(&self, slot: usize) -> u64 {
+        let bits = self.fingerprint_bits as usize;
+        let pos = slot * bits;
+        let (index, offset) = (pos / 64, pos & 63);
+        let mut value = self.solution[index] >> offset;
+        if offset + bits > 64 {
+            value |= self.solution[index + 1] << (64 - offset);
+        }
+
+        value & self.fingerprint_mask()
+    }
+
+    fn set_slot(&mut self, slot: usize, value: u64) {
+        let bits = self.fingerprint_bits as usize;
+        let pos = slot * bits;
+        let (index, offset) = (pos / 64, pos & 63);
+        self.solution[index] |= value << offset;
+        if offset + bits > 64 {
+            self.solution[index + 1] |= value >> (64 - offset);
+        }
+    }
+
+    fn fingerprint_mask(&self) -> u64 {
+        (1 << self.fingerprint_bits) - 1
+    }
+}
+
+/// 单个Level中过滤器的点查统计
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FilterStats {
+    /// 该Level当前的过滤器类型，已存在的SSTable保持其写入时的类型
+    pub policy: FilterPolicy,
+    /// 点查时进行过滤器判断的次数
+    pub checked: u64,
+    /// 过滤器排除了Key，从而避免读取Block的次数
+    pub useful: u64,
+    /// 过滤器判断Key可能存在，但读取Block后未找到数据的次数
+    pub false_positive: u64,
+    /// Table缓存中该Level的SSTable过滤器的内存占用，单位为B
+    pub filter_usage: u64,
+}
+
+/// 可并发记录的各Level过滤器判断次数
+#[derive(Debug)]
+pub(crate) struct FilterRecorder {
+    levels: Vec<[AtomicU64; 3]>,
+}
+
+impl FilterRecorder {
+    const CHECKED: usize = 0;
+    const USEFUL: usize = 1;
+    const FALSE_POSITIVE: usize = 2;
+
+    pub(crate) fn new(num_levels: usize) -> Self {
+        FilterRecorder {
+            levels: (0..num_levels).map(|_| Default::default()).collect(),
+        }
+    }
+
+    /// 记录一次过滤器判断，found为过滤器通过后是否读取到了数据
+    pub(crate) fn record(&self, level: usize, may_contain: bool, found: bool) {
+        if let Some(counters) = self.levels.get(level) {
+            let _ = counters[Self::CHECKED].fetch_add(1, Ordering::Relaxed);
+            match (may_contain, found) {
+                (false, _) => {
+                    let _ = counters[Self::USEFUL].fetch_add(1, Ordering::Relaxed);
+                }
+                (true, false) => {
+                    let _ = counters[Self::FALSE_POSITIVE].fetch_add(1, Ordering::Relaxed);
+                }
+                (true, true) => (),
+            }
+        }
+    }
+
+    /// 返回(checked, useful, false_positive)
+    pub(crate) fn counter(&self, level: usize) -> (u64, u64, u64) {
+        self.levels.get(level).map_or((0, 0, 0), |counters| {
+            (
+                counters[Self::CHECKED].load(Ordering::Relaxed),
+                counters[Self::USEFUL].load(Ordering::Relaxed),
+                counters[Self::FALSE_POSITIVE].load(Ordering::Relaxed),
+            )
+        })
+    }
+}
+
+/// 将哈希值均匀地映射至[0, range)，避免取模运算
+fn fast_range(hash: u64, range: usize) -> usize {
+    ((u128::from(hash) * range as u128) >> 64) as usize
+}
+
+/// 过滤器所使用的哈希函数
+///
+/// 过滤器会被持久化，因此不使用结果可能随Rust版本变化的DefaultHasher
+fn hash(key: &[u8]) -> u64 {
+    // FNV-1a
+    let hash = key.iter().fold(0xCBF2_9CE4_8422_2325_u64, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01B3)
+    });
+
+    mix(hash)
+}
+
+/// MurmurHash3的fmix64，使哈希值的每一位都充分混合
+fn mix(mut hash: u64) -> u64 {
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xFF51_AFD7_ED55_8CCD);
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xC4CE_B9FE_1A85_EC53);
+    hash ^ (hash >> 33)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::kernel::lsm::table::ss_table::filter::{Filter, FilterPolicy};
+
+    fn build(policy: FilterPolicy, len: u32) -> Filter {
+        let mut builder = policy.builder(len as usize);
+        for i in 0..len {
+            builder.add(&i.to_be_bytes());
+        }
+        builder.build()
+    }
+
+    /// 统计不存在的Key的误判数量
+    fn false_positives(filter: &Filter, len: u32) -> usize {
+        (len..len * 2)
+            .filter(|i| filter.may_contain(&i.to_be_bytes()))
+            .count()
+    }
+
+    #[test]
+    fn test_filter_policy() {
+        let len = 10000;
+
+        for policy in [FilterPolicy::Bloom(10), FilterPolicy::Ribbon(10)] {
+            let filter = build(policy, len);
+            assert!((0..len).all(|i| filter.may_contain(&i.to_be_bytes())));
+            // 理论误判率均约为1%
+            assert!(false_positives(&filter, len) < len as usize / 50);
+        }
+
+        let bloom = build(FilterPolicy::Bloom(10), len);
+        let ribbon = build(FilterPolicy::Ribbon(10), len);
+        assert!(ribbon.size() < bloom.size());
+
+        let filter = build(FilterPolicy::None, len);
+        assert!(filter.is_none());
+        assert_eq!(filter.size(), 0);
+        assert_eq!(false_positives(&filter, len), len as usize);
+    }
+
+    #[test]
+    fn test_filter_small() {
+        for policy in [FilterPolicy::Bloom(1), FilterPolicy::Ribbon(1)] {
+            for len in [0, 1, 2, 100] {
+                let filter = build(policy, len);
+                assert!((0..len).all(|i| filter.may_contain(&i.to_be_bytes())));
+            }
+        }
+    }
+}
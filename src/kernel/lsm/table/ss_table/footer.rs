@@ -1,11 +1,33 @@
 use crate::kernel::io::IoReader;
 use crate::kernel::Result;
+use crate::KernelError;
 use serde::{Deserialize, Serialize};
 use std::io::SeekFrom;
 
 /// Footer序列化长度定长
 /// 注意Footer序列化时，需要使用类似BinCode这样的定长序列化框架，否则若类似Rmp的话会导致Footer在不同数据时，长度不一致
-pub(crate) const TABLE_FOOTER_SIZE: usize = 21;
+///
+/// 由Footer本身与末尾的格式版本及魔数组成
+pub(crate) const TABLE_FOOTER_SIZE: usize = FOOTER_BODY_SIZE + FOOTER_TAIL_SIZE;
+
+/// 未记录格式版本的旧Footer序列化长度，即Footer本身的长度
+const FOOTER_BODY_SIZE: usize = 21;
+
+/// 格式版本(u32)与魔数(u32)的长度
+const FOOTER_TAIL_SIZE: usize = 8;
+
+/// 位于文件末尾，标识该SSTable的Footer中记录了格式版本
+///
+/// 旧格式的文件末尾为size_of_disk，即文件长度，因此魔数与文件长度相同时视为旧格式
+const TABLE_MAGIC: u32 = 0x4B49_5053;
+
+/// 未记录格式版本的旧格式
+///
+/// MetaBlock中的过滤器为GrowableBloom，且未记录删除标记数量与前缀过滤器
+pub(crate) const FORMAT_VERSION_LEGACY: u32 = 0;
+
+/// 当前写入的格式版本
+pub(crate) const FORMAT_VERSION: u32 = 1;
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
 #[repr(C, align(32))]
@@ -16,24 +38,73 @@ pub(crate) struct Footer {
     pub(crate) meta_offset: u32,
     pub(crate) meta_len: u32,
     pub(crate) size_of_disk: u32,
+    /// 格式版本，与魔数一同编码于Footer之后
+    #[serde(skip)]
+    pub(crate) version: u32,
 }
 
 impl Footer {
     /// 从对应文件的IOHandler中将Footer读取出来
     pub(crate) fn read_to_file(reader: &mut dyn IoReader) -> Result<Self> {
-        let mut buf = [0; TABLE_FOOTER_SIZE];
+        let file_size = reader.file_size()?;
+        let mut buf = vec![0; TABLE_FOOTER_SIZE.min(file_size as usize)];
+
+        let _ = reader.seek(SeekFrom::End(-(buf.len() as i64)))?;
+        reader.read_exact(&mut buf)?;
+
+        Self::decode(&buf, file_size)
+    }
+
+    /// 编码为写入文件末尾的字节，旧格式的Footer仍以旧格式编码
+    pub(crate) fn encode(&self) -> Result<Vec<u8>> {
+        let mut bytes = bincode::serialize(self)?;
+        if self.version != FORMAT_VERSION_LEGACY {
+            bytes.append(&mut bincode::serialize(&(self.version, TABLE_MAGIC))?);
+        }
+
+        Ok(bytes)
+    }
 
-        let _ = reader.seek(SeekFrom::End(-(TABLE_FOOTER_SIZE as i64)))?;
-        let _ = reader.read(&mut buf)?;
+    /// 解码文件末尾的字节，buf为文件末尾的TABLE_FOOTER_SIZE个字节(文件不足该长度时为整个文件)
+    pub(crate) fn decode(buf: &[u8], file_size: u64) -> Result<Self> {
+        let (version, magic): (u32, u32) =
+            bincode::deserialize(&buf[buf.len().saturating_sub(FOOTER_TAIL_SIZE)..])?;
 
-        Ok(bincode::deserialize(&buf)?)
+        if buf.len() == TABLE_FOOTER_SIZE
+            && magic == TABLE_MAGIC
+            && file_size != u64::from(TABLE_MAGIC)
+        {
+            if version > FORMAT_VERSION {
+                return Err(KernelError::UnsupportedTableFormat(version));
+            }
+            let mut footer: Footer = bincode::deserialize(&buf[..FOOTER_BODY_SIZE])?;
+            footer.version = version;
+
+            Ok(footer)
+        } else {
+            Ok(bincode::deserialize(
+                &buf[buf.len().saturating_sub(FOOTER_BODY_SIZE)..],
+            )?)
+        }
+    }
+
+    /// Footer于文件中所占的长度
+    pub(crate) fn encoded_len(&self) -> usize {
+        if self.version == FORMAT_VERSION_LEGACY {
+            FOOTER_BODY_SIZE
+        } else {
+            TABLE_FOOTER_SIZE
+        }
     }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::kernel::lsm::table::ss_table::footer::{Footer, TABLE_FOOTER_SIZE};
+    use crate::kernel::lsm::table::ss_table::footer::{
+        Footer, FORMAT_VERSION, FORMAT_VERSION_LEGACY, TABLE_FOOTER_SIZE,
+    };
     use crate::kernel::Result;
+    use crate::KernelError;
 
     #[test]
     fn test_footer() -> Result<()> {
@@ -44,9 +115,40 @@ mod test {
             meta_offset: 0,
             meta_len: 0,
             size_of_disk: 0,
+            version: FORMAT_VERSION,
         };
 
-        assert_eq!(bincode::serialize(&info)?.len(), TABLE_FOOTER_SIZE);
+        assert_eq!(info.encode()?.len(), TABLE_FOOTER_SIZE);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_footer_version() -> Result<()> {
+        let mut footer = Footer {
+            level: 1,
+            index_offset: 2,
+            index_len: 3,
+            meta_offset: 5,
+            meta_len: 7,
+            size_of_disk: 100,
+            version: FORMAT_VERSION,
+        };
+        let bytes = footer.encode()?;
+        assert_eq!(Footer::decode(&bytes, 100)?, footer);
+
+        // 旧格式的Footer之前仍为文件的其余内容
+        footer.version = FORMAT_VERSION_LEGACY;
+        let bytes = [vec![u8::MAX; 8], footer.encode()?].concat();
+        assert_eq!(bytes.len(), TABLE_FOOTER_SIZE);
+        assert_eq!(Footer::decode(&bytes, 100)?, footer);
+        assert_eq!(Footer::decode(&bytes[8..], 21)?, footer);
+
+        footer.version = FORMAT_VERSION + 1;
+        assert!(matches!(
+            Footer::decode(&footer.encode()?, 100),
+            Err(KernelError::UnsupportedTableFormat(_))
+        ));
 
         Ok(())
     }
@@ -1,51 +1,74 @@
 use crate::kernel::lsm::iterator::{ForwardIter, Iter, Seek};
 use crate::kernel::lsm::mem_table::KeyValue;
+use crate::kernel::lsm::storage::ReadOptions;
 use crate::kernel::lsm::table::ss_table::block::{BlockType, Index, Value};
 use crate::kernel::lsm::table::ss_table::block_iter::BlockIter;
 use crate::kernel::lsm::table::ss_table::SSTable;
 use crate::kernel::lsm::table::Table;
 use crate::kernel::Result;
 use crate::KernelError;
+use std::sync::Arc;
+
+/// 触发预读所需的连续顺序加载Data Block的次数
+const READAHEAD_TRIGGER: usize = 2;
+
+/// 预读窗口的初始大小
+const INITIAL_READAHEAD_SIZE: usize = 8 * 1024;
 
 pub(crate) struct SSTableIter<'a> {
     ss_table: &'a SSTable,
-    data_iter: BlockIter<'a, Value>,
-    index_iter: BlockIter<'a, Index>,
+    data_iter: BlockIter<Value>,
+    index_iter: BlockIter<Index>,
+    options: ReadOptions,
+    readahead: Readahead,
 }
 
 impl<'a> SSTableIter<'a> {
-    pub(crate) fn new(ss_table: &'a SSTable) -> Result<SSTableIter<'a>> {
+    pub(crate) fn new(ss_table: &'a SSTable, options: ReadOptions) -> Result<SSTableIter<'a>> {
+        let mut readahead = Readahead::new(&options);
         let mut index_iter = BlockIter::new(ss_table.index_block()?);
         let index = index_iter.next_err()?.ok_or(KernelError::DataEmpty)?.1;
-        let data_iter = Self::data_iter_init(ss_table, index)?;
+        let data_iter = Self::data_iter_init(ss_table, index, options, &mut readahead)?;
 
         Ok(Self {
             ss_table,
             data_iter,
             index_iter,
+            options,
+            readahead,
         })
     }
 
-    fn data_iter_init(ss_table: &'a SSTable, index: Index) -> Result<BlockIter<'a, Value>> {
-        let block = {
+    /// Tips: fill_cache为false时仅复用已缓存的Block，未命中时直接读取且不放入缓存
+    fn data_iter_init(
+        ss_table: &'a SSTable,
+        index: Index,
+        options: ReadOptions,
+        readahead: &mut Readahead,
+    ) -> Result<BlockIter<Value>> {
+        let key = (ss_table.gen(), Some(index));
+        readahead.record(index);
+
+        let block_type = if options.fill_cache {
             ss_table
                 .cache
-                .get_or_insert((ss_table.gen(), Some(index)), |(_, index)| {
-                    let index = (*index).ok_or_else(|| KernelError::DataEmpty)?;
-                    Ok(ss_table.data_block(index)?)
-                })
-                .map(|block_type| match block_type {
-                    BlockType::Data(data_block) => Some(data_block),
-                    _ => None,
-                })?
-        }
-        .ok_or(KernelError::DataEmpty)?;
+                .get_or_insert(key, |_| readahead.load(ss_table, index))?
+        } else {
+            match ss_table.cache.get(key) {
+                Some(block_type) => block_type,
+                None => readahead.load(ss_table, index)?,
+            }
+        };
+        let BlockType::Data(block) = block_type else {
+            return Err(KernelError::DataEmpty);
+        };
 
         Ok(BlockIter::new(block))
     }
 
     fn data_iter_seek(&mut self, seek: Seek<'_>, index: Index) -> Result<Option<KeyValue>> {
-        self.data_iter = Self::data_iter_init(self.ss_table, index)?;
+        self.data_iter =
+            Self::data_iter_init(self.ss_table, index, self.options, &mut self.readahead)?;
         Ok(self
             .data_iter
             .seek(seek)?
@@ -53,6 +76,84 @@ impl<'a> SSTableIter<'a> {
     }
 }
 
+/// 顺序读取Data Block时的自适应预读
+///
+/// 连续顺序加载的Data Block达到READAHEAD_TRIGGER后，以一次IO读取其后窗口范围内的数据，
+/// 之后的Data Block直接从缓冲区中解码；窗口自INITIAL_READAHEAD_SIZE起每次翻倍，
+/// 直至`ReadOptions::readahead_size`，出现非顺序的访问时重置
+struct Readahead {
+    max_size: usize,
+    window: usize,
+    /// 连续顺序加载的次数
+    sequential: usize,
+    /// 上一次加载的Data Block的起止位置
+    last: Option<(u32, u32)>,
+    buf_offset: u32,
+    buf: Vec<u8>,
+}
+
+impl Readahead {
+    fn new(options: &ReadOptions) -> Self {
+        let sequential = if options.sequential {
+            READAHEAD_TRIGGER
+        } else {
+            0
+        };
+
+        Readahead {
+            max_size: options.readahead_size,
+            window: INITIAL_READAHEAD_SIZE.min(options.readahead_size),
+            sequential,
+            last: None,
+            buf_offset: 0,
+            buf: Vec::new(),
+        }
+    }
+
+    /// 记录Data Block的访问以检测顺序读取
+    fn record(&mut self, index: Index) {
+        let offset = index.offset();
+
+        match self.last {
+            // 重复访问同一Block(如创建后立即Seek::First)时不影响顺序的判断
+            Some((start, _)) if start == offset => return,
+            Some((_, end)) if end == offset => self.sequential += 1,
+            Some(_) => {
+                self.sequential = 0;
+                self.window = INITIAL_READAHEAD_SIZE.min(self.max_size);
+            }
+            None => (),
+        }
+        self.last = Some((offset, offset + index.len() as u32));
+    }
+
+    /// 加载Data Block，顺序读取时优先从预读的缓冲区中解码
+    fn load(&mut self, ss_table: &SSTable, index: Index) -> Result<BlockType> {
+        let (offset, len) = (index.offset(), index.len());
+
+        if !self.is_buffered(offset, len) {
+            if self.max_size == 0 || self.sequential < READAHEAD_TRIGGER {
+                return ss_table.data_block(index);
+            }
+            let read_len = ((ss_table.data_end() - offset) as usize)
+                .min(self.window)
+                .max(len);
+
+            self.buf = ss_table.read_range(offset, read_len)?;
+            self.buf_offset = offset;
+            self.window = (self.window * 2).min(self.max_size);
+        }
+        let start = (offset - self.buf_offset) as usize;
+        let block = ss_table.decode_data_block(self.buf[start..start + len].to_vec())?;
+
+        Ok(BlockType::Data(Arc::new(block)))
+    }
+
+    fn is_buffered(&self, offset: u32, len: usize) -> bool {
+        offset >= self.buf_offset && (offset - self.buf_offset) as usize + len <= self.buf.len()
+    }
+}
+
 impl<'a> ForwardIter<'a> for SSTableIter<'a> {
     fn prev_err(&mut self) -> Result<Option<Self::Item>> {
         match self.data_iter.prev_err()? {
@@ -101,16 +202,14 @@ impl<'a> Iter<'a> for SSTableIter<'a> {
 mod tests {
     use crate::kernel::io::{FileExtension, IoFactory, IoType};
     use crate::kernel::lsm::iterator::{ForwardIter, Iter, Seek};
-    use crate::kernel::lsm::storage::Config;
+    use crate::kernel::lsm::storage::{Config, ReadOptions};
+    use crate::kernel::lsm::table::ss_table::block::BlockCache;
     use crate::kernel::lsm::table::ss_table::iter::SSTableIter;
     use crate::kernel::lsm::table::ss_table::SSTable;
     use crate::kernel::lsm::version::DEFAULT_SS_TABLE_PATH;
-    use crate::kernel::utils::lru_cache::ShardingLruCache;
     use crate::kernel::Result;
     use bincode::Options;
     use bytes::Bytes;
-    use std::collections::hash_map::RandomState;
-    use std::sync::Arc;
     use tempfile::TempDir;
 
     #[test]
@@ -136,11 +235,7 @@ mod tests {
             key.append(&mut bincode::options().with_big_endian().serialize(&i)?);
             vec_data.push((Bytes::from(key), Some(value.clone())));
         }
-        let cache = Arc::new(ShardingLruCache::new(
-            config.table_cache_size,
-            16,
-            RandomState::default(),
-        )?);
+        let cache = BlockCache::new(config.block_cache_size)?.handle();
 
         let ss_table = SSTable::new(
             &sst_factory,
@@ -152,7 +247,7 @@ mod tests {
             IoType::Direct,
         )?;
 
-        let mut iterator = SSTableIter::new(&ss_table)?;
+        let mut iterator = SSTableIter::new(&ss_table, ReadOptions::default())?;
 
         for i in 0..times {
             assert_eq!(iterator.next_err()?.unwrap(), vec_data[i]);
@@ -173,4 +268,55 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_iterator_readahead() -> Result<()> {
+        let temp_dir = TempDir::new().expect("unable to create temporary working directory");
+        let config = Config::new(temp_dir.into_path());
+        let sst_factory = IoFactory::new(
+            config.dir_path.join(DEFAULT_SS_TABLE_PATH),
+            FileExtension::SSTable,
+        )?;
+
+        let value =
+            Bytes::from_static(b"What you are you do not see, what you see is your shadow.");
+        let vec_data = (0..2333_u32)
+            .map(|i| (Bytes::from(i.to_be_bytes().to_vec()), Some(value.clone())))
+            .collect::<Vec<_>>();
+        let ss_table = SSTable::new(
+            &sst_factory,
+            &config,
+            BlockCache::new(config.block_cache_size)?.handle(),
+            1,
+            vec_data.clone(),
+            0,
+            IoType::Direct,
+        )?;
+
+        for readahead_size in [0, 1, 16 * 1024] {
+            // 不放入缓存以使每个Data Block都需要加载
+            let options = ReadOptions::new()
+                .fill_cache(false)
+                .readahead_size(readahead_size);
+            let mut iterator = SSTableIter::new(&ss_table, options)?;
+
+            for item in vec_data.iter() {
+                assert_eq!(iterator.next_err()?.as_ref(), Some(item));
+            }
+            assert_eq!(iterator.next_err()?, None);
+            assert_eq!(iterator.readahead.buf.is_empty(), readahead_size == 0);
+
+            // 反向读取与Seek不受预读缓冲区的影响
+            for i in (0..vec_data.len() - 1).rev() {
+                assert_eq!(iterator.prev_err()?.as_ref(), Some(&vec_data[i]));
+            }
+            assert_eq!(
+                iterator.seek(Seek::Backward(&vec_data[1234].0))?.as_ref(),
+                Some(&vec_data[1234])
+            );
+            assert_eq!(iterator.next_err()?.as_ref(), Some(&vec_data[1235]));
+        }
+
+        Ok(())
+    }
 }
@@ -1,28 +1,35 @@
 use crate::kernel::io::{IoFactory, IoReader, IoType};
 use crate::kernel::lsm::iterator::Iter;
-use crate::kernel::lsm::mem_table::KeyValue;
-use crate::kernel::lsm::storage::Config;
+use crate::kernel::lsm::mem_table::{key_value_bytes_len, KeyValue};
+use crate::kernel::lsm::prefix::{self, PrefixExtractor};
+use crate::kernel::lsm::storage::{Config, FilterPolicy, ReadOptions};
 use crate::kernel::lsm::table::ss_table::block::{
-    Block, BlockBuilder, BlockCache, BlockItem, BlockOptions, BlockType, CompressType, Index,
-    MetaBlock, Value,
+    Block, BlockBuilder, BlockCacheHandle, BlockItem, BlockOptions, BlockType, CompressType, Index,
+    MetaBlock, PrefixFilter, Value,
 };
-use crate::kernel::lsm::table::ss_table::footer::{Footer, TABLE_FOOTER_SIZE};
+use crate::kernel::lsm::table::ss_table::filter::FilterBuilder;
+use crate::kernel::lsm::table::ss_table::footer::{Footer, FORMAT_VERSION, TABLE_FOOTER_SIZE};
 use crate::kernel::lsm::table::ss_table::iter::SSTableIter;
 use crate::kernel::lsm::table::Table;
 use crate::kernel::Result;
 use crate::KernelError;
 use bytes::Bytes;
-use growable_bloom_filter::GrowableBloom;
 use itertools::Itertools;
-use parking_lot::Mutex;
-use std::io::SeekFrom;
+use std::fs::OpenOptions;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::Path;
 use std::sync::Arc;
 use tracing::info;
 
 pub(crate) mod block;
 pub(crate) mod block_iter;
+pub(crate) mod filter;
 mod footer;
 pub(crate) mod iter;
+pub(crate) mod secondary_cache;
+
+/// 限速写入时单次写入的数据量，每次写入前均向限速器请求对应的令牌
+pub(crate) const DEFAULT_RATE_LIMIT_CHUNK_SIZE: usize = 64 * 1024;
 
 /// SSTable
 ///
@@ -30,83 +37,115 @@ pub(crate) mod iter;
 pub(crate) struct SSTable {
     // 表索引信息
     footer: Footer,
-    // 文件IO操作器
-    reader: Mutex<Box<dyn IoReader>>,
+    // 文件IO操作器，通过read_at定位读取，因此可被并发读取而无需加锁
+    reader: Box<dyn IoReader>,
     // 该SSTable的唯一编号(时间递增)
     gen: i64,
     // 统计信息存储Block
     meta: MetaBlock,
-    // Block缓存(Index/Value)
-    cache: Arc<BlockCache>,
+    // Block缓存(Index/Value)，Config::pin_index_blocks包含所处Level时Index Block固定于其中
+    cache: BlockCacheHandle,
 }
 
 impl SSTable {
     pub(crate) fn new(
         io_factory: &IoFactory,
         config: &Config,
-        cache: Arc<BlockCache>,
+        cache: BlockCacheHandle,
         gen: i64,
         vec_data: Vec<KeyValue>,
         level: usize,
         io_type: IoType,
     ) -> Result<SSTable> {
-        let len = vec_data.len();
-        let data_restart_interval = config.data_restart_interval;
-        let index_restart_interval = config.index_restart_interval;
-        let mut filter = GrowableBloom::new(config.desired_error_prob, len);
+        let (footer, meta, bytes) = Self::encode(config, vec_data, level)?;
+        let mut writer = io_factory.writer(gen, io_type)?;
+        writer.write_all(&bytes)?;
+        writer.flush()?;
 
-        let mut builder = BlockBuilder::new(
-            BlockOptions::from(config)
-                .compress_type(CompressType::LZ4)
-                .data_restart_interval(data_restart_interval)
-                .index_restart_interval(index_restart_interval),
-        );
-        for data in vec_data {
-            let (key, value) = data;
-            let _ = filter.insert(&key);
-            builder.add((key, Value::from(value)));
-        }
-        let meta = MetaBlock {
-            filter,
-            len,
-            index_restart_interval,
-            data_restart_interval,
-        };
+        Self::open_created(io_factory, cache, gen, footer, meta, io_type)
+    }
 
-        let (data_bytes, index_bytes) = builder.build()?;
-        let meta_bytes = bincode::serialize(&meta)?;
-        let footer = Footer {
-            level: level as u8,
-            index_offset: data_bytes.len() as u32,
-            index_len: index_bytes.len() as u32,
-            meta_offset: (data_bytes.len() + index_bytes.len()) as u32,
-            meta_len: meta_bytes.len() as u32,
-            size_of_disk: (data_bytes.len()
-                + index_bytes.len()
-                + meta_bytes.len()
-                + TABLE_FOOTER_SIZE) as u32,
+    /// 与`SSTable::new`相同，但设置了`Config::rate_limiter`时，
+    /// 会将数据分块写入并在每次写入前向限速器请求该块的令牌，使flush与压缩的写入被平滑限速
+    pub(crate) async fn new_with_rate_limiter(
+        io_factory: &IoFactory,
+        config: &Config,
+        cache: BlockCacheHandle,
+        gen: i64,
+        vec_data: Vec<KeyValue>,
+        level: usize,
+        io_type: IoType,
+    ) -> Result<SSTable> {
+        let Some(rate_limiter) = &config.rate_limiter else {
+            return Self::new(io_factory, config, cache, gen, vec_data, level, io_type);
         };
+        let (footer, meta, bytes) = Self::encode(config, vec_data, level)?;
         let mut writer = io_factory.writer(gen, io_type)?;
-        writer.write_all(
-            data_bytes
-                .into_iter()
-                .chain(index_bytes)
-                .chain(meta_bytes)
-                .chain(bincode::serialize(&footer)?)
-                .collect_vec()
-                .as_mut(),
-        )?;
+        for chunk in bytes.chunks(DEFAULT_RATE_LIMIT_CHUNK_SIZE) {
+            rate_limiter.request(chunk.len() as u64).await;
+            writer.write_all(chunk)?;
+        }
         writer.flush()?;
+
+        Self::open_created(io_factory, cache, gen, footer, meta, io_type)
+    }
+
+    fn open_created(
+        io_factory: &IoFactory,
+        cache: BlockCacheHandle,
+        gen: i64,
+        footer: Footer,
+        meta: MetaBlock,
+        io_type: IoType,
+    ) -> Result<SSTable> {
         info!("[SsTable: {}][create][MetaBlock]: {:?}", gen, meta);
 
-        let reader = Mutex::new(io_factory.reader(gen, io_type)?);
-        Ok(SSTable {
+        let level = footer.level as usize;
+        let reader = io_factory.reader(gen, io_type)?;
+        SSTable {
             footer,
             reader,
             gen,
             meta,
             cache,
-        })
+        }
+        .pin_index_block(level)
+    }
+
+    /// 将有序的数据编码为完整的SSTable文件内容
+    ///
+    /// 请注意：vec_data必须是依照key值严格递增的
+    pub(crate) fn encode(
+        config: &Config,
+        vec_data: Vec<KeyValue>,
+        level: usize,
+    ) -> Result<(Footer, MetaBlock, Vec<u8>)> {
+        let mut builder = SSTableBuilder::new(config, level, vec_data.len());
+        for data in vec_data {
+            builder.add(data);
+        }
+        builder.finish()
+    }
+
+    /// 修改SSTable文件Footer中所记录的Level
+    ///
+    /// 仅用于尚未被Version引用的SSTable，如外部导入的SSTable
+    pub(crate) fn rewrite_level(path: &Path, level: usize) -> Result<()> {
+        let mut file = OpenOptions::new().read(true).write(true).open(path)?;
+        let file_size = file.metadata()?.len();
+        let mut buf = vec![0; TABLE_FOOTER_SIZE.min(file_size as usize)];
+
+        let _ = file.seek(SeekFrom::End(-(buf.len() as i64)))?;
+        file.read_exact(&mut buf)?;
+        let mut footer = Footer::decode(&buf, file_size)?;
+        footer.level = level as u8;
+
+        // 保持原有的格式版本，避免与MetaBlock的格式不一致
+        let _ = file.seek(SeekFrom::End(-(footer.encoded_len() as i64)))?;
+        file.write_all(&footer.encode()?)?;
+        file.sync_data()?;
+
+        Ok(())
     }
 
     /// 通过已经存在的文件构建SSTable
@@ -114,7 +153,7 @@ impl SSTable {
     /// 使用原有的路径与分区大小恢复出一个有内容的SSTable
     pub(crate) fn load_from_file(
         mut reader: Box<dyn IoReader>,
-        cache: Arc<BlockCache>,
+        cache: BlockCacheHandle,
     ) -> Result<Self> {
         let gen = reader.get_gen();
         let footer = Footer::read_to_file(reader.as_mut())?;
@@ -134,8 +173,7 @@ impl SSTable {
         let _ = reader.seek(SeekFrom::Start(*meta_offset as u64))?;
         let _ = reader.read(&mut buf)?;
 
-        let meta = bincode::deserialize(&buf)?;
-        let reader = Mutex::new(reader);
+        let meta = MetaBlock::decode(&buf, footer.version)?;
         Ok(SSTable {
             footer,
             gen,
@@ -145,31 +183,48 @@ impl SSTable {
         })
     }
 
+    /// 所处Level需要固定Index Block时，将其加载并固定于Block缓存中
+    ///
+    /// level以Version中记录的为准，Trivial Move后Footer中的Level可能已过时
+    pub(crate) fn pin_index_block(self, level: usize) -> Result<Self> {
+        if self.cache.is_pinned_level(level) {
+            self.cache.pin_index(self.gen, || self.load_index_block())?;
+        }
+
+        Ok(self)
+    }
+
     pub(crate) fn data_block(&self, index: Index) -> Result<BlockType> {
-        Ok(BlockType::Data(Self::loading_block(
-            self.reader.lock().as_mut(),
+        Ok(BlockType::Data(Arc::new(self.loading_block(
             index.offset(),
             index.len(),
             CompressType::LZ4,
             self.meta.data_restart_interval,
-        )?))
+        )?)))
     }
 
-    pub(crate) fn index_block(&self) -> Result<&Block<Index>> {
+    /// 以一次IO读取[offset, offset + len)范围内的数据，用于顺序读取时的预读
+    pub(crate) fn read_range(&self, offset: u32, len: usize) -> Result<Vec<u8>> {
+        let mut buf = vec![0; len];
+        self.reader.read_at(&mut buf, offset as u64)?;
+
+        Ok(buf)
+    }
+
+    /// 解码通过read_range读取的Data Block
+    pub(crate) fn decode_data_block(&self, buf: Vec<u8>) -> Result<Block<Value>> {
+        Block::decode(buf, CompressType::LZ4, self.meta.data_restart_interval)
+    }
+
+    /// Data Block区域的结束位置，即Index Block的起始位置
+    pub(crate) fn data_end(&self) -> u32 {
+        self.footer.index_offset
+    }
+
+    pub(crate) fn index_block(&self) -> Result<Arc<Block<Index>>> {
         self.cache
             .get_or_insert((self.gen(), None), |_| {
-                let Footer {
-                    index_offset,
-                    index_len,
-                    ..
-                } = self.footer;
-                Ok(BlockType::Index(Self::loading_block(
-                    self.reader.lock().as_mut(),
-                    index_offset,
-                    index_len as usize,
-                    CompressType::None,
-                    self.meta.index_restart_interval,
-                )?))
+                Ok(BlockType::Index(Arc::new(self.load_index_block()?)))
             })
             .map(|block_type| match block_type {
                 BlockType::Index(data_block) => Some(data_block),
@@ -178,8 +233,23 @@ impl SSTable {
             .ok_or(KernelError::DataEmpty)
     }
 
+    fn load_index_block(&self) -> Result<Block<Index>> {
+        let Footer {
+            index_offset,
+            index_len,
+            ..
+        } = self.footer;
+
+        self.loading_block(
+            index_offset,
+            index_len as usize,
+            CompressType::None,
+            self.meta.index_restart_interval,
+        )
+    }
+
     fn loading_block<T>(
-        reader: &mut dyn IoReader,
+        &self,
         offset: u32,
         len: usize,
         compress_type: CompressType,
@@ -188,37 +258,185 @@ impl SSTable {
     where
         T: BlockItem,
     {
-        let mut buf = vec![0; len];
-        let _ = reader.seek(SeekFrom::Start(offset as u64))?;
-        reader.read_exact(&mut buf)?;
+        Block::decode(
+            self.read_range(offset, len)?,
+            compress_type,
+            restart_interval,
+        )
+    }
+}
+
+/// SSTable的流式构建器
+///
+/// 依次添加Key严格递增的数据，数据直接进入BlockBuilder中构建为Block，最后编码为完整的SSTable文件内容
+pub(crate) struct SSTableBuilder {
+    level: usize,
+    builder: BlockBuilder,
+    filter: FilterBuilder,
+    prefix_filter: Option<(Arc<dyn PrefixExtractor>, FilterBuilder, Option<Bytes>)>,
+    data_restart_interval: usize,
+    index_restart_interval: usize,
+    len: usize,
+    tombstone_len: usize,
+    /// 已添加的键值对的数据量，与压缩时切分SSTable所使用的计算方式一致
+    data_size: usize,
+}
+
+impl SSTableBuilder {
+    /// len仅用于预分配过滤器的空间
+    pub(crate) fn new(config: &Config, level: usize, len: usize) -> Self {
+        let data_restart_interval = config.data_restart_interval;
+        let index_restart_interval = config.index_restart_interval;
+        let filter_policy = config.filter_policies[level];
+        // 不使用过滤器的Level同样不记录前缀过滤器
+        let prefix_filter = config
+            .prefix_extractor
+            .as_ref()
+            .filter(|_| filter_policy != FilterPolicy::None)
+            .map(|extractor| (Arc::clone(extractor), filter_policy.builder(len), None));
+
+        SSTableBuilder {
+            level,
+            builder: BlockBuilder::new(
+                BlockOptions::from(config)
+                    .compress_type(CompressType::LZ4)
+                    .data_restart_interval(data_restart_interval)
+                    .index_restart_interval(index_restart_interval),
+            ),
+            filter: filter_policy.builder(len),
+            prefix_filter,
+            data_restart_interval,
+            index_restart_interval,
+            len: 0,
+            tombstone_len: 0,
+            data_size: 0,
+        }
+    }
+
+    /// 请注意：需自行保证Key严格递增
+    pub(crate) fn add(&mut self, data: KeyValue) {
+        self.data_size += key_value_bytes_len(&data);
+        let (key, value) = data;
+        if value.is_none() {
+            self.tombstone_len += 1;
+        }
+        self.filter.add(&key);
+        if let Some((extractor, prefix_filter, last_prefix)) = &mut self.prefix_filter {
+            // Key有序，因此相同的前缀通常是连续的，跳过重复的前缀以免过滤器无谓地增长
+            if let Some(prefix) = extractor.prefix(&key) {
+                if last_prefix.as_deref() != Some(prefix) {
+                    prefix_filter.add(prefix);
+                    *last_prefix = Some(Bytes::copy_from_slice(prefix));
+                }
+            }
+        }
+        self.builder.add((key, Value::from(value)));
+        self.len += 1;
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
 
-        Block::decode(buf, compress_type, restart_interval)
+    pub(crate) fn data_size(&self) -> usize {
+        self.data_size
+    }
+
+    pub(crate) fn finish(self) -> Result<(Footer, MetaBlock, Vec<u8>)> {
+        let meta = MetaBlock {
+            filter: self.filter.build(),
+            len: self.len,
+            tombstone_len: self.tombstone_len,
+            index_restart_interval: self.index_restart_interval,
+            data_restart_interval: self.data_restart_interval,
+            prefix_filter: self
+                .prefix_filter
+                .map(|(extractor, filter, _)| PrefixFilter {
+                    extractor: extractor.name().to_owned(),
+                    filter: filter.build(),
+                }),
+        };
+
+        let (data_bytes, index_bytes) = self.builder.build()?;
+        let meta_bytes = bincode::serialize(&meta)?;
+        let footer = Footer {
+            level: self.level as u8,
+            index_offset: data_bytes.len() as u32,
+            index_len: index_bytes.len() as u32,
+            meta_offset: (data_bytes.len() + index_bytes.len()) as u32,
+            meta_len: meta_bytes.len() as u32,
+            size_of_disk: (data_bytes.len()
+                + index_bytes.len()
+                + meta_bytes.len()
+                + TABLE_FOOTER_SIZE) as u32,
+            version: FORMAT_VERSION,
+        };
+        let bytes = data_bytes
+            .into_iter()
+            .chain(index_bytes)
+            .chain(meta_bytes)
+            .chain(footer.encode()?)
+            .collect_vec();
+
+        Ok((footer, meta, bytes))
     }
 }
 
 impl Table for SSTable {
-    fn query(&self, key: &[u8]) -> Result<Option<Bytes>> {
-        if self.meta.filter.contains(key) {
-            let index_block = self.index_block()?;
-
-            if let BlockType::Data(data_block) = self.cache.get_or_insert(
-                (self.gen(), Some(index_block.find_with_upper(key))),
-                |(_, index)| {
-                    let index = (*index).ok_or_else(|| KernelError::DataEmpty)?;
-                    Ok(Self::data_block(self, index)?)
-                },
-            )? {
-                return Ok(data_block.find(key));
-            }
+    fn query(&self, key: &[u8]) -> Result<Option<Option<Bytes>>> {
+        let index_block = self.index_block()?;
+
+        if let BlockType::Data(data_block) = self.cache.get_or_insert(
+            (self.gen(), Some(index_block.find_with_upper(key))),
+            |(_, index)| {
+                let index = (*index).ok_or_else(|| KernelError::DataEmpty)?;
+                Self::data_block(self, index)
+            },
+        )? {
+            return Ok(data_block.find(key));
         }
 
         Ok(None)
     }
 
+    fn may_contain(&self, key: &[u8]) -> bool {
+        self.meta.filter.may_contain(key)
+    }
+
+    fn has_filter(&self) -> bool {
+        !self.meta.filter.is_none()
+    }
+
+    /// 前缀过滤器由同名的提取器生成且prefix可被其判断时才进行过滤
+    fn may_contain_prefix(&self, extractor: &dyn PrefixExtractor, prefix: &[u8]) -> bool {
+        match &self.meta.prefix_filter {
+            Some(PrefixFilter {
+                extractor: name,
+                filter,
+            }) if name == extractor.name() && prefix::is_in_domain(extractor, prefix) => {
+                filter.may_contain(prefix)
+            }
+            _ => true,
+        }
+    }
+
     fn len(&self) -> usize {
         self.meta.len
     }
 
+    fn tombstone_len(&self) -> usize {
+        self.meta.tombstone_len
+    }
+
+    fn filter_size(&self) -> usize {
+        self.meta.filter.size()
+            + self
+                .meta
+                .prefix_filter
+                .as_ref()
+                .map_or(0, |prefix_filter| prefix_filter.filter.size())
+    }
+
     fn size_of_disk(&self) -> u64 {
         self.footer.size_of_disk as u64
     }
@@ -231,26 +449,33 @@ impl Table for SSTable {
         self.footer.level as usize
     }
 
-    fn iter<'a>(&'a self) -> Result<Box<dyn Iter<'a, Item = KeyValue> + 'a>> {
-        Ok(SSTableIter::new(&self).map(Box::new)?)
+    fn iter_with_options<'a>(
+        &'a self,
+        options: ReadOptions,
+    ) -> Result<Box<dyn Iter<'a, Item = KeyValue> + 'a>> {
+        Ok(SSTableIter::new(&self, options).map(Box::new)?)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::kernel::io::{FileExtension, IoFactory, IoType};
+    use crate::kernel::lsm::compactor::LEVEL_0;
     use crate::kernel::lsm::log::LogLoader;
     use crate::kernel::lsm::mem_table::DEFAULT_WAL_PATH;
     use crate::kernel::lsm::storage::Config;
     use crate::kernel::lsm::table::loader::TableLoader;
+    use crate::kernel::lsm::table::ss_table::block::BlockCache;
+    use crate::kernel::lsm::table::ss_table::footer::{Footer, FORMAT_VERSION_LEGACY};
     use crate::kernel::lsm::table::ss_table::SSTable;
     use crate::kernel::lsm::table::{Table, TableType};
     use crate::kernel::lsm::version::DEFAULT_SS_TABLE_PATH;
-    use crate::kernel::utils::lru_cache::ShardingLruCache;
     use crate::kernel::Result;
     use bincode::Options;
     use bytes::Bytes;
-    use std::collections::hash_map::RandomState;
+    use growable_bloom_filter::GrowableBloom;
+    use itertools::Itertools;
+    use std::fs;
     use std::sync::Arc;
     use tempfile::TempDir;
 
@@ -284,20 +509,112 @@ mod tests {
             ));
         }
         // Tips: 此处Level需要为0以上，因为Level 0默认为Mem类型，容易丢失
-        let _ = sst_loader.create(1, vec_data.clone(), 1, TableType::SortedString)?;
+        let _ = tokio_test::block_on(sst_loader.create(
+            1,
+            vec_data.clone(),
+            1,
+            TableType::SortedString,
+        ))?;
         assert!(sst_loader.is_table_file_exist(1)?);
 
-        let ss_table = sst_loader.get(1).unwrap();
+        let ss_table = sst_loader.get(1, LEVEL_0).unwrap();
 
         for i in 0..times {
-            assert_eq!(ss_table.query(&vec_data[i].0)?, Some(value.clone()))
+            assert_eq!(ss_table.query(&vec_data[i].0)?, Some(Some(value.clone())))
         }
-        let cache = ShardingLruCache::new(config.table_cache_size, 16, RandomState::default())?;
-        let ss_table =
-            SSTable::load_from_file(sst_factory.reader(1, IoType::Direct)?, Arc::new(cache))?;
+        let cache = BlockCache::new(config.block_cache_size)?.handle();
+        let ss_table = SSTable::load_from_file(sst_factory.reader(1, IoType::Direct)?, cache)?;
         for i in 0..times {
-            assert_eq!(ss_table.query(&vec_data[i].0)?, Some(value.clone()))
+            assert_eq!(ss_table.query(&vec_data[i].0)?, Some(Some(value.clone())))
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_ss_table_legacy_format() -> Result<()> {
+        let temp_dir = TempDir::new().expect("unable to create temporary working directory");
+
+        let config = Config::new(temp_dir.into_path());
+        let sst_factory = IoFactory::new(
+            config.dir_path.join(DEFAULT_SS_TABLE_PATH),
+            FileExtension::SSTable,
+        )?;
+        let vec_data = (0_u32..1000)
+            .map(|i| {
+                let key = Bytes::from(i.to_be_bytes().to_vec());
+                (key.clone(), Some(key))
+            })
+            .collect_vec();
+
+        // 以旧格式重新编码MetaBlock与Footer，模拟旧版本写入的SSTable
+        let ss_table = SSTable::new(
+            &sst_factory,
+            &config,
+            BlockCache::new(config.block_cache_size)?.handle(),
+            1,
+            vec_data.clone(),
+            1,
+            IoType::Direct,
+        )?;
+        let footer = ss_table.footer;
+        let mut filter = GrowableBloom::new(0.05, vec_data.len());
+        for (key, _) in vec_data.iter() {
+            let _ = filter.insert(key);
         }
+        let meta_bytes = bincode::serialize(&(
+            filter,
+            vec_data.len(),
+            config.index_restart_interval,
+            config.data_restart_interval,
+        ))?;
+        let legacy_footer = Footer {
+            meta_len: meta_bytes.len() as u32,
+            size_of_disk: footer.meta_offset + meta_bytes.len() as u32 + 21,
+            version: FORMAT_VERSION_LEGACY,
+            ..footer
+        };
+        let sst_path = FileExtension::SSTable.path_with_gen(sst_factory.get_path(), 1);
+        let bytes = fs::read(&sst_path)?;
+        fs::write(
+            &sst_path,
+            [
+                &bytes[..footer.meta_offset as usize],
+                &meta_bytes,
+                &legacy_footer.encode()?,
+            ]
+            .concat(),
+        )?;
+
+        let ss_table = SSTable::load_from_file(
+            sst_factory.reader(1, IoType::Direct)?,
+            BlockCache::new(config.block_cache_size)?.handle(),
+        )?;
+        assert_eq!(ss_table.footer, legacy_footer);
+        assert_eq!(ss_table.len(), vec_data.len());
+        assert_eq!(ss_table.tombstone_len(), 0);
+        // 旧格式的过滤器同样计入过滤器统计
+        assert!(ss_table.has_filter());
+        assert!(ss_table.filter_size() > 0);
+        for (key, value) in vec_data.iter() {
+            assert!(ss_table.may_contain(key));
+            assert_eq!(ss_table.query(key)?, Some(value.clone()));
+        }
+
+        // 修改Level时仍保持旧格式
+        SSTable::rewrite_level(&sst_path, 2)?;
+        let ss_table = SSTable::load_from_file(
+            sst_factory.reader(1, IoType::Direct)?,
+            BlockCache::new(config.block_cache_size)?.handle(),
+        )?;
+        assert_eq!(
+            ss_table.footer,
+            Footer {
+                level: 2,
+                ..legacy_footer
+            }
+        );
+        assert_eq!(ss_table.query(&vec_data[0].0)?, Some(vec_data[0].1.clone()));
 
         Ok(())
     }
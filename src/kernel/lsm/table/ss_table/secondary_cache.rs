@@ -0,0 +1,257 @@
+use crate::kernel::lsm::table::ss_table::block::{Block, BlockCacheKey, BlockType};
+use crate::kernel::utils::cache_counter::{CacheCounter, CacheRecorder};
+use crate::kernel::Result;
+use crate::KernelError;
+use parking_lot::Mutex;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{self, Debug, Formatter};
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::sync::Arc;
+
+/// 基于本地文件的二级Block缓存
+///
+/// 使用固定大小的缓存文件与内存中的索引，从`BlockCache`中被驱逐的Block会写入其中，
+/// Block缓存未命中时会先查询二级缓存再读取SSTable，适用于将缓存文件放置于比数据目录更快的设备上
+/// - 缓存文件以环形缓冲区的方式写入，空间不足时从头覆盖最早写入的Block
+/// - Block以带有CRC的原始格式存储，读取时校验失败则视为未命中
+/// - 索引仅存在于内存中，因此缓存文件在每次创建时都会被清空
+pub struct SecondaryCache {
+    inner: Mutex<SecondaryInner>,
+    recorder: CacheRecorder,
+    capacity: u64,
+}
+
+struct SecondaryInner {
+    file: File,
+    index: HashMap<BlockCacheKey, SecondarySlot>,
+    /// 以文件偏移排序的Key，用于找出将被覆盖的Block
+    order: BTreeMap<u64, BlockCacheKey>,
+    /// 下一个Block的写入位置
+    write_offset: u64,
+}
+
+#[derive(Clone, Copy)]
+struct SecondarySlot {
+    offset: u64,
+    len: u64,
+    restart_interval: usize,
+}
+
+impl SecondaryCache {
+    /// 在path处创建容量为capacity(单位为B)的缓存文件
+    #[inline]
+    pub fn new(path: impl AsRef<Path>, capacity: u64) -> Result<Arc<Self>> {
+        if capacity < 1 {
+            return Err(KernelError::CacheSizeOverFlow);
+        }
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)?;
+        file.set_len(capacity)?;
+
+        Ok(Arc::new(SecondaryCache {
+            inner: Mutex::new(SecondaryInner {
+                file,
+                index: HashMap::new(),
+                order: BTreeMap::new(),
+                write_offset: 0,
+            }),
+            recorder: CacheRecorder::default(),
+            capacity,
+        }))
+    }
+
+    /// 缓存文件的容量，单位为B
+    #[inline]
+    pub fn capacity(&self) -> u64 {
+        self.capacity
+    }
+
+    /// 当前缓存的Block在文件中占用的空间，单位为B
+    #[inline]
+    pub fn usage(&self) -> u64 {
+        self.inner.lock().index.values().map(|slot| slot.len).sum()
+    }
+
+    /// 二级缓存的统计，驱逐数为被覆盖的Block数量
+    #[inline]
+    pub fn counter(&self) -> CacheCounter {
+        self.recorder.counter()
+    }
+
+    /// 写入被Block缓存驱逐的Block，已存在或超出容量时不做处理
+    pub(crate) fn insert(&self, key: BlockCacheKey, block: &BlockType) -> Result<()> {
+        let mut inner = self.inner.lock();
+        if inner.index.contains_key(&key) {
+            return Ok(());
+        }
+        let (bytes, restart_interval) = match block {
+            BlockType::Data(block) => (block.to_raw()?, block.restart_interval()),
+            BlockType::Index(block) => (block.to_raw()?, block.restart_interval()),
+        };
+        let len = bytes.len() as u64;
+        if len > self.capacity {
+            return Ok(());
+        }
+        if inner.write_offset + len > self.capacity {
+            inner.write_offset = 0;
+        }
+        let offset = inner.write_offset;
+        let evictions = inner.overwrite(offset, offset + len);
+        self.recorder.record_evictions(evictions);
+
+        let _ = inner.file.seek(SeekFrom::Start(offset))?;
+        inner.file.write_all(&bytes)?;
+
+        let _ = inner.index.insert(
+            key,
+            SecondarySlot {
+                offset,
+                len,
+                restart_interval,
+            },
+        );
+        let _ = inner.order.insert(offset, key);
+        inner.write_offset = offset + len;
+        self.recorder.record_insert();
+
+        Ok(())
+    }
+
+    /// 读取缓存的Block，不存在或校验失败时返回None
+    pub(crate) fn lookup(&self, key: &BlockCacheKey) -> Option<BlockType> {
+        let mut inner = self.inner.lock();
+        let option_block = inner.index.get(key).copied().and_then(|slot| {
+            let result = inner.read(key, slot);
+            if result.is_err() {
+                // 数据已损坏，移除后由SSTable重新读取
+                let _ = inner.index.remove(key);
+                let _ = inner.order.remove(&slot.offset);
+            }
+            result.ok()
+        });
+
+        self.recorder.record_access(option_block.is_some());
+        option_block
+    }
+}
+
+impl SecondaryInner {
+    /// 移除与[start, end)重叠的Block，返回被移除的数量
+    fn overwrite(&mut self, start: u64, end: u64) -> u64 {
+        let overlapped = self
+            .order
+            .range(..end)
+            .rev()
+            .map_while(|(offset, key)| {
+                self.index
+                    .get(key)
+                    .filter(|slot| slot.offset + slot.len > start)
+                    .map(|_| (*offset, *key))
+            })
+            .collect::<Vec<_>>();
+
+        for (offset, key) in &overlapped {
+            let _ = self.order.remove(offset);
+            let _ = self.index.remove(key);
+        }
+        overlapped.len() as u64
+    }
+
+    fn read(&mut self, key: &BlockCacheKey, slot: SecondarySlot) -> Result<BlockType> {
+        let mut buf = vec![0; slot.len as usize];
+        let _ = self.file.seek(SeekFrom::Start(slot.offset))?;
+        self.file.read_exact(&mut buf)?;
+
+        // Index为None时对应Index Block
+        Ok(match key.2 {
+            Some(_) => BlockType::Data(Arc::new(Block::from_raw(buf, slot.restart_interval)?)),
+            None => BlockType::Index(Arc::new(Block::from_raw(buf, slot.restart_interval)?)),
+        })
+    }
+}
+
+impl Debug for SecondaryCache {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SecondaryCache")
+            .field("capacity", &self.capacity())
+            .field("usage", &self.usage())
+            .field("counter", &self.counter())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::kernel::lsm::table::ss_table::block::{Block, BlockType, Index, Value};
+    use crate::kernel::lsm::table::ss_table::secondary_cache::SecondaryCache;
+    use crate::kernel::Result;
+    use bytes::Bytes;
+    use std::fs;
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    fn data_block(i: u8) -> Block<Value> {
+        Block::new(
+            (0..10)
+                .map(|j| {
+                    (
+                        Bytes::from(vec![i, j]),
+                        Value::from(Some(Bytes::from(vec![i; 100]))),
+                    )
+                })
+                .collect(),
+            4,
+        )
+    }
+
+    #[test]
+    fn test_secondary_cache() -> Result<()> {
+        let temp_dir = TempDir::new().expect("unable to create temporary working directory");
+        let path = temp_dir.path().join("secondary_cache");
+        let block_len = data_block(0).to_raw()?.len() as u64;
+        // 仅能容纳3个Block
+        let cache = SecondaryCache::new(&path, block_len * 3 + 1)?;
+        assert_eq!(fs::metadata(&path)?.len(), cache.capacity());
+
+        for i in 0..3 {
+            cache.insert(
+                (0, 1, Some(Index::new(i, 0))),
+                &BlockType::Data(Arc::new(data_block(i as u8))),
+            )?;
+        }
+        assert_eq!(cache.usage(), block_len * 3);
+        match cache.lookup(&(0, 1, Some(Index::new(1, 0)))) {
+            Some(BlockType::Data(block)) => assert_eq!(block.as_ref(), &data_block(1)),
+            _ => panic!("block not found"),
+        }
+
+        // 空间不足时从头覆盖最早写入的Block
+        cache.insert(
+            (0, 1, Some(Index::new(3, 0))),
+            &BlockType::Data(Arc::new(data_block(3))),
+        )?;
+        assert!(cache.lookup(&(0, 1, Some(Index::new(0, 0)))).is_none());
+        assert!(cache.lookup(&(0, 1, Some(Index::new(2, 0)))).is_some());
+        assert!(cache.lookup(&(0, 1, Some(Index::new(3, 0)))).is_some());
+
+        let counter = cache.counter();
+        assert_eq!(counter.inserts, 4);
+        assert_eq!(counter.evictions, 1);
+        assert_eq!(counter.hits, 3);
+        assert_eq!(counter.misses, 1);
+
+        // 文件内容损坏时视为未命中
+        fs::write(&path, vec![0; (block_len * 3 + 1) as usize])?;
+        assert!(cache.lookup(&(0, 1, Some(Index::new(2, 0)))).is_none());
+        assert_eq!(cache.usage(), block_len * 2);
+
+        Ok(())
+    }
+}
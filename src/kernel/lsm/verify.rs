@@ -0,0 +1,230 @@
+use crate::kernel::io::{FileExtension, IoFactory, IoType};
+use crate::kernel::lsm::compactor::LEVEL_0;
+use crate::kernel::lsm::storage::Config;
+use crate::kernel::lsm::table::meta::TableMeta;
+use crate::kernel::lsm::table::scope::Scope;
+use crate::kernel::lsm::table::ss_table::block::{BlockCache, BlockCacheHandle};
+use crate::kernel::lsm::table::ss_table::SSTable;
+use crate::kernel::lsm::table::{Table, TableType};
+use crate::kernel::lsm::version::{Version, DEFAULT_SS_TABLE_PATH};
+use crate::kernel::Result;
+use crate::KernelError;
+
+/// 校验时所发现的问题
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum VerifyIssue {
+    /// Version所引用的Table不存在
+    TableMissing { level: usize, gen: i64 },
+    /// Table无法解码(Footer、MetaBlock、Block校验码等)或数据与统计不符
+    TableCorrupted {
+        level: usize,
+        gen: i64,
+        reason: String,
+    },
+    /// Table中的数据未能通过过滤器
+    FilterMismatch { level: usize, gen: i64, keys: usize },
+    /// Version中所记录的Scope与Table实际的首尾Key不符
+    ScopeMismatch { level: usize, gen: i64 },
+    /// Level 1-N中相邻的Table无序或相交
+    LevelOverlap {
+        level: usize,
+        gen: i64,
+        next_gen: i64,
+    },
+    /// Level的统计数据与其Table的统计数据之和不符
+    LevelMetaMismatch {
+        level: usize,
+        size_of_disk: (u64, u64),
+        len: (usize, usize),
+    },
+    /// VersionMeta与所有Table的统计数据之和不符
+    VersionMetaMismatch {
+        size_of_disk: (u64, u64),
+        len: (usize, usize),
+    },
+}
+
+/// 完整性校验结果
+///
+/// Mismatch类问题中的元组为(记录值, 实际值)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VerifyReport {
+    /// 校验的Table数量
+    pub tables: usize,
+    /// 校验的数据条数
+    pub entries: usize,
+    pub issues: Vec<VerifyIssue>,
+}
+
+impl VerifyReport {
+    #[inline]
+    pub fn is_ok(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// 对Version进行完整性校验
+///
+/// 持久化的Table会绕过TableLoader直接从文件读取，以避免缓存掩盖磁盘上的损坏，
+/// 且文件丢失时不会像TableLoader一样尝试通过WAL恢复
+pub(crate) fn verify_version(config: &Config, version: &Version) -> Result<VerifyReport> {
+    let sst_factory = IoFactory::new(
+        config.path().join(DEFAULT_SS_TABLE_PATH),
+        FileExtension::SSTable,
+    )?;
+    // 使用独立的Block缓存使每个Block都从磁盘读取并校验
+    let cache = BlockCache::new(config.block_cache_size)?.handle();
+    let mut report = VerifyReport::default();
+    let mut total_meta = TableMeta::default();
+    // 存在无法读取的Table时统计数据必然不符，此时不再重复报告统计数据的问题
+    let mut is_all_loaded = true;
+
+    for (level, vec_scope) in version.level_slice.iter().enumerate() {
+        let mut level_meta = TableMeta::default();
+        let mut is_level_loaded = true;
+
+        for (offset, scope) in vec_scope.iter().enumerate() {
+            let gen = scope.get_gen();
+            report.tables += 1;
+
+            let option_ss_table;
+            let table: &dyn Table = if matches!(config.level_table_type[level], TableType::Skip) {
+                match version.table(level, offset) {
+                    Some(table) => table,
+                    None => {
+                        report.issues.push(VerifyIssue::TableMissing { level, gen });
+                        is_level_loaded = false;
+                        continue;
+                    }
+                }
+            } else {
+                if !sst_factory.exists(gen)? {
+                    report.issues.push(VerifyIssue::TableMissing { level, gen });
+                    is_level_loaded = false;
+                    continue;
+                }
+                match load_ss_table(&sst_factory, &cache, gen) {
+                    Ok(ss_table) => option_ss_table = ss_table,
+                    Err(err) => {
+                        report.issues.push(VerifyIssue::TableCorrupted {
+                            level,
+                            gen,
+                            reason: err.to_string(),
+                        });
+                        is_level_loaded = false;
+                        continue;
+                    }
+                }
+                &option_ss_table
+            };
+
+            level_meta = TableMeta::fusion(&[level_meta, TableMeta::from(table)]);
+            if let Err(err) = verify_table(level, scope, table, &mut report) {
+                report.issues.push(VerifyIssue::TableCorrupted {
+                    level,
+                    gen,
+                    reason: err.to_string(),
+                });
+            }
+        }
+
+        if level != LEVEL_0 {
+            for window in vec_scope.windows(2) {
+                if window[0].end >= window[1].start {
+                    report.issues.push(VerifyIssue::LevelOverlap {
+                        level,
+                        gen: window[0].get_gen(),
+                        next_gen: window[1].get_gen(),
+                    });
+                }
+            }
+        }
+
+        let recorded = version.level_metas[level];
+        if is_level_loaded && recorded != level_meta {
+            report.issues.push(VerifyIssue::LevelMetaMismatch {
+                level,
+                size_of_disk: (recorded.size_of_disk, level_meta.size_of_disk),
+                len: (recorded.len, level_meta.len),
+            });
+        }
+        total_meta = TableMeta::fusion(&[total_meta, level_meta]);
+        is_all_loaded &= is_level_loaded;
+    }
+
+    let recorded = version.meta_data;
+    if is_all_loaded
+        && (recorded.size_of_disk, recorded.len) != (total_meta.size_of_disk, total_meta.len)
+    {
+        report.issues.push(VerifyIssue::VersionMetaMismatch {
+            size_of_disk: (recorded.size_of_disk, total_meta.size_of_disk),
+            len: (recorded.len, total_meta.len),
+        });
+    }
+
+    Ok(report)
+}
+
+fn load_ss_table(sst_factory: &IoFactory, cache: &BlockCacheHandle, gen: i64) -> Result<SSTable> {
+    let reader = sst_factory.reader(gen, IoType::Direct)?;
+    let file_size = reader.file_size()?;
+    let ss_table = SSTable::load_from_file(reader, cache.clone())?;
+
+    if ss_table.size_of_disk() != file_size {
+        return Err(KernelError::TableCorrupted(gen));
+    }
+    Ok(ss_table)
+}
+
+/// 遍历Table的所有数据，校验其顺序、统计数据、过滤器与Scope
+fn verify_table(
+    level: usize,
+    scope: &Scope,
+    table: &dyn Table,
+    report: &mut VerifyReport,
+) -> Result<()> {
+    let gen = table.gen();
+    let mut iter = table.iter()?;
+    let mut option_first = None;
+    let mut option_last = None;
+    let mut len = 0;
+    let mut tombstone_len = 0;
+    let mut filter_miss = 0;
+
+    while let Some((key, value)) = iter.next_err()? {
+        if matches!(&option_last, Some(last_key) if last_key >= &key) {
+            return Err(KernelError::TableCorrupted(gen));
+        }
+        if !table.may_contain(&key) {
+            filter_miss += 1;
+        }
+        if value.is_none() {
+            tombstone_len += 1;
+        }
+        if option_first.is_none() {
+            option_first = Some(key.clone());
+        }
+        option_last = Some(key);
+        len += 1;
+    }
+    report.entries += len;
+
+    if len != table.len() || tombstone_len != table.tombstone_len() {
+        return Err(KernelError::TableCorrupted(gen));
+    }
+    if filter_miss > 0 {
+        report.issues.push(VerifyIssue::FilterMismatch {
+            level,
+            gen,
+            keys: filter_miss,
+        });
+    }
+    if option_first.as_ref() != Some(&scope.start) || option_last.as_ref() != Some(&scope.end) {
+        report
+            .issues
+            .push(VerifyIssue::ScopeMismatch { level, gen });
+    }
+
+    Ok(())
+}
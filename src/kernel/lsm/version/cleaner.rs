@@ -1,8 +1,16 @@
+use crate::kernel::io::FileExtension;
+use crate::kernel::lsm::mem_table::DEFAULT_WAL_PATH;
+use crate::kernel::lsm::storage::Config;
 use crate::kernel::lsm::table::loader::TableLoader;
+use crate::kernel::lsm::version::{Version, DEFAULT_SS_TABLE_PATH};
+use crate::kernel::{sorted_gen_list, Result};
 use itertools::Itertools;
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
 use std::sync::Arc;
 use tokio::sync::mpsc::UnboundedReceiver;
-use tracing::error;
+use tracing::{error, info};
 
 #[derive(Debug)]
 pub(crate) enum CleanTag {
@@ -95,4 +103,89 @@ impl Cleaner {
             .find(|(_, (vn, _))| vn == &ver_num)
             .map(|(index, _)| index)
     }
+
+    /// 启动时清理未被Version引用的冗余文件
+    ///
+    /// 在log_and_apply与Cleaner删除之间宕机时，已被移除的SSTable与其WAL会残留在文件夹中，
+    /// 而Cleaner仅会删除此次运行中记录的文件，因此需要在启动时以恢复的Version为准进行清理
+    /// - SSTable: 仅保留Version所引用的Gen
+    /// - WAL: 仅删除不晚于Version::flushed_log_gen且未被Version所引用(Table的备份)的Gen，
+    ///   之后的WAL中的数据尚未持久化，Version中未记录flushed_log_gen时则不清理WAL
+    ///
+    /// 设置了`Config::quarantine_dir`时将文件移动至该目录而非删除
+    ///
+    /// Tips: 需在`VersionStatus::recover_unflushed_logs`之后调用，
+    /// 且Manifest末尾的记录被丢弃时Version不可信，此时不应调用
+    pub(crate) fn clean_obsolete_files(
+        config: &Config,
+        version: &Version,
+        wal_gen: i64,
+    ) -> Result<()> {
+        let live_gens: HashSet<i64> = version.gens().collect();
+
+        Self::clean_obsolete_with_dir(
+            config,
+            DEFAULT_SS_TABLE_PATH,
+            FileExtension::SSTable,
+            |gen| !live_gens.contains(&gen),
+        )?;
+
+        let Some(flushed_log_gen) = version.flushed_log_gen else {
+            info!("[Cleaner][clean_obsolete_files]: Skip WAL without flushed log gen");
+            return Ok(());
+        };
+        Self::clean_obsolete_with_dir(config, DEFAULT_WAL_PATH, FileExtension::Log, |gen| {
+            gen <= flushed_log_gen && gen != wal_gen && !live_gens.contains(&gen)
+        })
+    }
+
+    fn clean_obsolete_with_dir<F>(
+        config: &Config,
+        dir_name: &str,
+        extension: FileExtension,
+        is_obsolete: F,
+    ) -> Result<()>
+    where
+        F: Fn(i64) -> bool,
+    {
+        let dir_path = config.path().join(dir_name);
+
+        if !dir_path.exists() {
+            return Ok(());
+        }
+        for gen in sorted_gen_list(&dir_path, extension)? {
+            if !is_obsolete(gen) {
+                continue;
+            }
+            let file_path = extension.path_with_gen(&dir_path, gen);
+
+            if let Some(quarantine_dir) = &config.quarantine_dir {
+                let quarantine_path = quarantine_dir.join(dir_name);
+                fs::create_dir_all(&quarantine_path)?;
+                Self::move_file(&file_path, &extension.path_with_gen(&quarantine_path, gen))?;
+                info!(
+                    "[Cleaner][clean_obsolete_files][{}: {}]: Quarantined to {:?}",
+                    dir_name, gen, quarantine_path
+                );
+            } else {
+                fs::remove_file(&file_path)?;
+                info!(
+                    "[Cleaner][clean_obsolete_files][{}: {}]: Removed",
+                    dir_name, gen
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 隔离目录可能不在同一文件系统上，此时rename会失败，因此退化为复制后删除
+    fn move_file(from: &Path, to: &Path) -> Result<()> {
+        if fs::rename(from, to).is_err() {
+            let _ = fs::copy(from, to)?;
+            fs::remove_file(from)?;
+        }
+
+        Ok(())
+    }
 }
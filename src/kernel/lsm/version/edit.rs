@@ -1,5 +1,6 @@
 use crate::kernel::lsm::table::meta::TableMeta;
 use crate::kernel::lsm::table::scope::Scope;
+use bytes::Bytes;
 use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
 
@@ -11,8 +12,10 @@ pub(crate) enum VersionEdit {
     // Level 0则请忽略第二位的index参数，默认会放至最尾
     /// ((Vec(scope), Level), Index, TableMeta)
     NewFile((Vec<Scope>, usize), usize, TableMeta),
-    // // Level and SSTable Gen List
-    // CompactPoint(usize, Vec<i64>),
+    /// (Level, 该Level上次压缩的最大Key)
+    CompactPoint(usize, Bytes),
+    /// 该Gen及之前的WAL中的数据均已持久化至Version中
+    FlushedLogGen(i64),
 }
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
@@ -2,6 +2,8 @@ use crate::kernel::lsm::iterator::level_iter::LevelIter;
 use crate::kernel::lsm::iterator::merging_iter::MergingIter;
 use crate::kernel::lsm::iterator::{Iter, Seek};
 use crate::kernel::lsm::mem_table::KeyValue;
+use crate::kernel::lsm::prefix::PrefixExtractor;
+use crate::kernel::lsm::storage::ReadOptions;
 use crate::kernel::lsm::version::Version;
 use crate::kernel::Result;
 
@@ -12,7 +14,14 @@ pub struct VersionIter<'a> {
 
 impl<'a> VersionIter<'a> {
     pub(crate) fn new(version: &'a Version) -> Result<VersionIter<'a>> {
-        let vec_iter = Self::merging_with_version(version)?;
+        Self::with_options(version, ReadOptions::default())
+    }
+
+    pub(crate) fn with_options(
+        version: &'a Version,
+        options: ReadOptions,
+    ) -> Result<VersionIter<'a>> {
+        let vec_iter = Self::merging_with_version(version, options)?;
 
         Ok(Self {
             merge_iter: MergingIter::new(vec_iter)?,
@@ -21,21 +30,42 @@ impl<'a> VersionIter<'a> {
 
     pub(crate) fn merging_with_version(
         version: &'a Version,
+        options: ReadOptions,
     ) -> Result<Vec<Box<dyn Iter<'a, Item = KeyValue> + 'a>>> {
         let mut vec_iter: Vec<Box<dyn Iter<'a, Item = KeyValue> + 'a>> = Vec::new();
 
         for table in version.tables_by_level_0() {
-            vec_iter.push(table.iter()?);
+            vec_iter.push(table.iter_with_options(options)?);
         }
 
-        for level in 1..6 {
-            if let Ok(level_iter) = LevelIter::new(version, level) {
+        for level in 1..version.num_levels() {
+            if let Ok(level_iter) = LevelIter::new(version, level, options) {
                 vec_iter.push(Box::new(level_iter));
             }
         }
 
         Ok(vec_iter)
     }
+
+    /// 前缀迭代所使用的Table迭代器，仅包含可能存在以prefix为前缀的Key的Table
+    ///
+    /// Level 1-N中同样以Table为单位进行筛选，因此不使用LevelIter
+    pub(crate) fn merging_with_prefix(
+        version: &'a Version,
+        extractor: Option<&dyn PrefixExtractor>,
+        prefix: &[u8],
+        options: ReadOptions,
+    ) -> Result<Vec<Box<dyn Iter<'a, Item = KeyValue> + 'a>>> {
+        let mut vec_iter: Vec<Box<dyn Iter<'a, Item = KeyValue> + 'a>> = Vec::new();
+
+        for level in 0..version.num_levels() {
+            for table in version.tables_by_prefix(level, extractor, prefix) {
+                vec_iter.push(table.iter_with_options(options)?);
+            }
+        }
+
+        Ok(vec_iter)
+    }
 }
 
 impl<'a> Iter<'a> for VersionIter<'a> {
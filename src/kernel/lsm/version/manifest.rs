@@ -0,0 +1,211 @@
+use crate::kernel::io::{FileExtension, IoFactory, IoType};
+use crate::kernel::lsm::log::{LogReader, LogWriter};
+use crate::kernel::lsm::storage::Gen;
+use crate::kernel::lsm::version::edit::VersionEdit;
+use crate::kernel::{sorted_gen_list, Result};
+use crate::KernelError;
+use std::fs::{self, File, OpenOptions};
+use std::io::{ErrorKind, Read, Seek, Write};
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+pub(crate) const DEFAULT_CURRENT_FILE: &str = "CURRENT";
+
+const DEFAULT_CURRENT_TEMP_FILE: &str = "CURRENT.tmp";
+
+/// Manifest: VersionEdit的持久化日志
+///
+/// 当前生效的Manifest仅由CURRENT文件指定，恢复时不对文件进行任何猜测
+/// - 切换Manifest时先将快照完整写入新的Manifest并落盘
+/// - 再通过写入临时文件并rename的方式原子地更新CURRENT
+/// - 最后才删除旧的Manifest
+///
+/// 因此任意时刻宕机，CURRENT所指向的Manifest都是完整的
+pub(crate) struct Manifest {
+    dir_path: PathBuf,
+    writer: LogWriter<File>,
+    gen: i64,
+}
+
+/// 从Manifest中恢复出的VersionEdit批次
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct Recovery {
+    pub(crate) vec_batch: Vec<Vec<VersionEdit>>,
+    /// 末尾是否存在因宕机而写入不完整并被丢弃的记录
+    pub(crate) is_torn: bool,
+}
+
+impl Manifest {
+    /// 读取CURRENT所指向的Manifest中的所有VersionEdit批次
+    ///
+    /// 不存在CURRENT时则尝试读取旧版本的VersionLog，均不存在时返回None
+    pub(crate) fn recover(dir_path: &Path) -> Result<Option<Recovery>> {
+        if let Some(gen) = Self::current_gen(dir_path)? {
+            let path = FileExtension::Manifest.path_with_gen(dir_path, gen);
+
+            return Ok(Some(Self::load(File::open(path)?)?));
+        }
+
+        Self::recover_legacy(dir_path)
+    }
+
+    /// 创建新的Manifest并写入快照，完成后将CURRENT指向该Manifest
+    ///
+    /// 成功后会删除文件夹中其余的Manifest与旧版本的VersionLog
+    pub(crate) fn create(dir_path: &Path, snapshot: &[VersionEdit]) -> Result<Self> {
+        fs::create_dir_all(dir_path)?;
+        let (gen, file) = Self::create_file(dir_path)?;
+        let mut manifest = Manifest {
+            dir_path: dir_path.to_path_buf(),
+            writer: LogWriter::new(file),
+            gen,
+        };
+
+        manifest.add_record(snapshot)?;
+        Self::set_current(dir_path, gen)?;
+        manifest.clean_obsolete()?;
+        info!("[Manifest: {}][create]: CURRENT switched", gen);
+
+        Ok(manifest)
+    }
+
+    /// 持久化一组VersionEdit
+    pub(crate) fn add_record(&mut self, vec_version_edit: &[VersionEdit]) -> Result<()> {
+        let _ = self
+            .writer
+            .add_record(&bincode::serialize(vec_version_edit)?)?;
+        self.writer.flush()?;
+        self.writer.get_mut().sync_data()?;
+
+        Ok(())
+    }
+
+    pub(crate) fn gen(&self) -> i64 {
+        self.gen
+    }
+
+    /// 读取所有VersionEdit批次
+    ///
+    /// 宕机时仅末尾的记录可能写入不完整，此时丢弃该记录并恢复之前的数据，
+    /// 而其余位置的读取失败(IO错误、校验码不符、无法解码)说明文件已损坏，直接返回错误
+    fn load<R: Read + Seek>(src: R) -> Result<Recovery> {
+        let mut reader = LogReader::new(src);
+        let mut vec_batch = Vec::new();
+        let mut is_torn = false;
+        let mut buf = vec![0; 128];
+
+        loop {
+            match reader.read(&mut buf) {
+                Ok(0) => break,
+                Ok(_) => vec_batch.push(bincode::deserialize::<Vec<VersionEdit>>(&buf)?),
+                Err(err) => {
+                    let is_tail = match &err {
+                        KernelError::Io(io_err) => io_err.kind() == ErrorKind::UnexpectedEof,
+                        KernelError::CrcMisMatch => reader.is_eof()?,
+                        _ => false,
+                    };
+                    if !is_tail {
+                        return Err(err);
+                    }
+                    warn!(
+                        "[Manifest][load]: discard the torn record at the end after {} batches",
+                        vec_batch.len()
+                    );
+                    is_torn = true;
+                    break;
+                }
+            }
+        }
+
+        Ok(Recovery { vec_batch, is_torn })
+    }
+
+    /// 宕机前未完成切换的Manifest可能残留在文件夹中，因此跳过已被占用的Gen
+    fn create_file(dir_path: &Path) -> Result<(i64, File)> {
+        loop {
+            let gen = Gen::create();
+
+            match OpenOptions::new()
+                .create_new(true)
+                .write(true)
+                .open(FileExtension::Manifest.path_with_gen(dir_path, gen))
+            {
+                Ok(file) => return Ok((gen, file)),
+                Err(err) if err.kind() == ErrorKind::AlreadyExists => continue,
+                Err(err) => return Err(err.into()),
+            }
+        }
+    }
+
+    fn current_gen(dir_path: &Path) -> Result<Option<i64>> {
+        let current_path = dir_path.join(DEFAULT_CURRENT_FILE);
+
+        if !current_path.exists() {
+            return Ok(None);
+        }
+        let gen = fs::read_to_string(current_path)?
+            .trim()
+            .trim_end_matches(&format!(".{}", FileExtension::Manifest.extension_str()))
+            .parse::<i64>()
+            .map_err(|_| std::io::Error::from(ErrorKind::InvalidData))?;
+
+        Ok(Some(gen))
+    }
+
+    /// 写入临时文件并落盘后rename为CURRENT，使CURRENT的更新为原子操作
+    fn set_current(dir_path: &Path, gen: i64) -> Result<()> {
+        let temp_path = dir_path.join(DEFAULT_CURRENT_TEMP_FILE);
+        let mut temp_file = File::create(&temp_path)?;
+
+        writeln!(
+            temp_file,
+            "{gen}.{}",
+            FileExtension::Manifest.extension_str()
+        )?;
+        temp_file.sync_all()?;
+        fs::rename(temp_path, dir_path.join(DEFAULT_CURRENT_FILE))?;
+        // 保证rename本身落盘
+        File::open(dir_path)?.sync_all()?;
+
+        Ok(())
+    }
+
+    /// 删除非当前的Manifest与旧版本的VersionLog
+    fn clean_obsolete(&self) -> Result<()> {
+        for gen in sorted_gen_list(&self.dir_path, FileExtension::Manifest)? {
+            if gen != self.gen {
+                fs::remove_file(FileExtension::Manifest.path_with_gen(&self.dir_path, gen))?;
+            }
+        }
+        for gen in sorted_gen_list(&self.dir_path, FileExtension::Log)? {
+            fs::remove_file(FileExtension::Log.path_with_gen(&self.dir_path, gen))?;
+        }
+
+        Ok(())
+    }
+
+    /// 兼容旧版本的VersionLog
+    ///
+    /// 旧版本中快照总是新Log的第一条记录，因此选取最新的一个可读出记录的Log即可，
+    /// 迁移后会在Manifest创建时被删除
+    fn recover_legacy(dir_path: &Path) -> Result<Option<Recovery>> {
+        let factory = IoFactory::new(dir_path, FileExtension::Log)?;
+
+        for gen in sorted_gen_list(dir_path, FileExtension::Log)?
+            .into_iter()
+            .rev()
+        {
+            let recovery = Self::load(factory.reader(gen, IoType::Direct)?)?;
+
+            if !recovery.vec_batch.is_empty() {
+                info!(
+                    "[Manifest][recover_legacy]: migrate from version log {}",
+                    gen
+                );
+                return Ok(Some(recovery));
+            }
+        }
+
+        Ok(None)
+    }
+}
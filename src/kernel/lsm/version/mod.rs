@@ -1,6 +1,6 @@
-use crate::kernel::io::{FileExtension, IoFactory};
-use crate::kernel::lsm::compactor::LEVEL_0;
-use crate::kernel::lsm::storage::{Config, Gen};
+use crate::kernel::lsm::compactor::{CompactionPri, LEVEL_0};
+use crate::kernel::lsm::prefix::PrefixExtractor;
+use crate::kernel::lsm::storage::Config;
 use crate::kernel::lsm::table::loader::TableLoader;
 use crate::kernel::lsm::table::meta::TableMeta;
 use crate::kernel::lsm::table::scope::Scope;
@@ -8,16 +8,18 @@ use crate::kernel::lsm::table::Table;
 use crate::kernel::lsm::version::cleaner::CleanTag;
 use crate::kernel::lsm::version::edit::{EditType, VersionEdit};
 use crate::kernel::lsm::version::meta::VersionMeta;
-use crate::kernel::{sorted_gen_list, Result};
+use crate::kernel::Result;
+use crate::KernelError;
 use bytes::Bytes;
 use itertools::Itertools;
 use std::sync::Arc;
 use tokio::sync::mpsc::UnboundedSender;
 use tracing::{error, info};
 
-mod cleaner;
+pub(crate) mod cleaner;
 pub(crate) mod edit;
 pub(crate) mod iter;
+pub(crate) mod manifest;
 mod meta;
 pub(crate) mod status;
 #[cfg(test)]
@@ -27,22 +29,7 @@ pub(crate) const DEFAULT_SS_TABLE_PATH: &str = "ss_table";
 pub(crate) const DEFAULT_VERSION_PATH: &str = "version";
 pub(crate) const DEFAULT_VERSION_LOG_THRESHOLD: usize = 233;
 
-pub(crate) type LevelSlice = [Vec<Scope>; 7];
-
-fn snapshot_gen(factory: &IoFactory) -> Result<i64> {
-    if let Ok(gen_list) = sorted_gen_list(factory.get_path(), FileExtension::Log) {
-        return Ok(match *gen_list.as_slice() {
-            [.., old_snapshot, new_snapshot] => {
-                factory.clean(new_snapshot)?;
-                old_snapshot
-            }
-            [snapshot] => snapshot,
-            _ => Gen::create(),
-        });
-    }
-
-    Ok(Gen::create())
-}
+pub(crate) type LevelSlice = Vec<Vec<Scope>>;
 
 #[derive(Clone)]
 pub(crate) struct Version {
@@ -54,6 +41,15 @@ pub(crate) struct Version {
     /// 以索引0为level-0这样的递推，存储文件的gen值
     /// 每个Version各持有各自的Gen矩阵
     pub(crate) level_slice: LevelSlice,
+    /// 各Level的压缩指针，即该Level上次压缩的最大Key
+    /// 用于CompactionPri::RoundRobin时轮询选取压缩的Table
+    pub(crate) compact_points: Vec<Option<Bytes>>,
+    /// 各Level的统计数据
+    pub(crate) level_metas: Vec<TableMeta>,
+    /// 已持久化至Version中的最新WAL的Gen，之后的WAL中的数据尚未持久化
+    ///
+    /// 旧版本的Version中未记录，此时为None
+    pub(crate) flushed_log_gen: Option<i64>,
     /// 统计数据
     pub(crate) meta_data: VersionMeta,
     /// 清除信号发送器
@@ -70,6 +66,10 @@ impl Version {
         self.len() == 0
     }
 
+    pub(crate) fn num_levels(&self) -> usize {
+        self.level_slice.len()
+    }
+
     pub(crate) fn level_len(&self, level: usize) -> usize {
         self.level_slice[level].len()
     }
@@ -78,16 +78,25 @@ impl Version {
         self.meta_data.size_of_disk
     }
 
+    /// 当前Version所引用的所有Table的Gen
+    pub(crate) fn gens(&self) -> impl Iterator<Item = i64> + '_ {
+        self.level_slice.iter().flatten().map(Scope::get_gen)
+    }
+
     /// 通过一组VersionEdit载入Version
     pub(crate) fn load_from_log(
         vec_log: Vec<VersionEdit>,
         ss_table_loader: &Arc<TableLoader>,
         clean_tx: UnboundedSender<CleanTag>,
+        num_levels: usize,
     ) -> Result<Self> {
         let mut version = Self {
             version_num: 0,
             table_loader: Arc::clone(ss_table_loader),
-            level_slice: Self::level_slice_new(),
+            level_slice: vec![Vec::new(); num_levels],
+            compact_points: vec![None; num_levels],
+            level_metas: vec![TableMeta::default(); num_levels],
+            flushed_log_gen: None,
             meta_data: VersionMeta {
                 size_of_disk: 0,
                 len: 0,
@@ -96,11 +105,31 @@ impl Version {
         };
 
         version.apply(vec_log)?;
+        version.recount_level_metas();
         version_display(&version, "load_from_log");
 
         Ok(version)
     }
 
+    /// 重新统计Level 1及以下中存在Table但统计数据为空的Level
+    ///
+    /// 旧版本写入的Version快照中Level 1及以下的统计数据均为0，
+    /// 若不重新统计则这些Level因磁盘占用为0而永远不会触发Major压缩
+    fn recount_level_metas(&mut self) {
+        for level in 1..self.num_levels() {
+            if self.level_slice[level].is_empty() || self.level_metas[level].size_of_disk > 0 {
+                continue;
+            }
+            let level_meta = {
+                let tables = (0..self.level_len(level))
+                    .filter_map(|offset| self.table(level, offset))
+                    .collect_vec();
+                TableMeta::from(tables.as_slice())
+            };
+            self.level_metas[level] = level_meta;
+        }
+    }
+
     /// Version对VersionEdit的应用处理
     ///
     /// Tips: 当此处像Cleaner发送Tag::Add时，此时的version中不需要的gens
@@ -110,34 +139,64 @@ impl Version {
     /// 也可以算作是一种Major Compaction异常时的备份？
     pub(crate) fn apply(&mut self, vec_version_edit: Vec<VersionEdit>) -> Result<()> {
         let mut del_gens = Vec::new();
+        let mut new_gens = Vec::new();
         let mut vec_statistics_sst_meta = Vec::new();
 
         for version_edit in vec_version_edit {
+            if let VersionEdit::DeleteFile((_, level), _)
+            | VersionEdit::NewFile((_, level), _, _)
+            | VersionEdit::CompactPoint(level, _) = &version_edit
+            {
+                // 已有数据的Level超出当前所配置的层数
+                if *level >= self.num_levels() {
+                    return Err(KernelError::LevelOver);
+                }
+            }
+
             match version_edit {
                 VersionEdit::DeleteFile((mut vec_gen, level), sst_meta) => {
                     vec_statistics_sst_meta.push(EditType::Del(sst_meta));
+                    let level_meta = &mut self.level_metas[level];
+                    level_meta.size_of_disk = level_meta
+                        .size_of_disk
+                        .saturating_sub(sst_meta.size_of_disk);
+                    level_meta.len = level_meta.len.saturating_sub(sst_meta.len);
 
                     self.level_slice[level].retain(|scope| !vec_gen.contains(&scope.get_gen()));
                     del_gens.append(&mut vec_gen);
                 }
                 VersionEdit::NewFile((vec_scope, level), index, sst_meta) => {
                     vec_statistics_sst_meta.push(EditType::Add(sst_meta));
+                    self.level_metas[level] =
+                        TableMeta::fusion(&[self.level_metas[level], sst_meta]);
+                    new_gens.extend(vec_scope.iter().map(Scope::get_gen));
 
                     // Level 0中的Table绝对是以gen为优先级
-                    // Level N中则不以gen为顺序，此处对gen排序是因为单次NewFile中的gen肯定是有序的
-                    let scope_iter = vec_scope.into_iter().sorted_by_key(Scope::get_gen);
+                    // Level N中则以Key为顺序，Trivial Move时移动的Table的gen并不一定与Key顺序一致
                     if level == LEVEL_0 {
-                        for scope in scope_iter {
+                        for scope in vec_scope.into_iter().sorted_by_key(Scope::get_gen) {
                             self.level_slice[level].push(scope);
                         }
                     } else {
-                        for scope in scope_iter.rev() {
+                        for scope in vec_scope
+                            .into_iter()
+                            .sorted_by(|scope_a, scope_b| scope_a.start.cmp(&scope_b.start))
+                            .rev()
+                        {
                             self.level_slice[level].insert(index, scope);
                         }
                     }
                 }
+                VersionEdit::CompactPoint(level, key) => {
+                    self.compact_points[level] = Some(key);
+                }
+                VersionEdit::FlushedLogGen(gen) => {
+                    self.flushed_log_gen = self.flushed_log_gen.max(Some(gen));
+                }
             }
         }
+        // Trivial Move时被移动的Table会同时存在于DeleteFile与NewFile中，此时不能将其文件删除
+        del_gens.retain(|gen| !new_gens.contains(gen));
 
         self.meta_data
             .statistical_process(vec_statistics_sst_meta)?;
@@ -150,47 +209,34 @@ impl Version {
         Ok(())
     }
 
-    fn level_slice_new() -> [Vec<Scope>; 7] {
-        [
-            Vec::new(),
-            Vec::new(),
-            Vec::new(),
-            Vec::new(),
-            Vec::new(),
-            Vec::new(),
-            Vec::new(),
-        ]
-    }
-
     /// 把当前version的leveSlice中的数据转化为一组versionEdit 作为新version_log的base
     pub(crate) fn to_vec_edit(&self) -> Vec<VersionEdit> {
-        fn sst_meta_by_level(level: usize, size_of_disk: u64, len: usize) -> TableMeta {
-            if LEVEL_0 == level {
-                TableMeta { size_of_disk, len }
-            } else {
-                TableMeta::default()
-            }
-        }
-
         self.level_slice
             .iter()
             .enumerate()
             .filter_map(|(level, vec_scope)| {
                 (!vec_scope.is_empty()).then(|| {
-                    VersionEdit::NewFile(
-                        (vec_scope.clone(), level),
-                        0,
-                        sst_meta_by_level(level, self.size_of_disk(), self.len()),
-                    )
+                    VersionEdit::NewFile((vec_scope.clone(), level), 0, self.level_metas[level])
                 })
             })
+            .chain(
+                self.compact_points
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(level, option_key)| {
+                        option_key
+                            .as_ref()
+                            .map(|key| VersionEdit::CompactPoint(level, key.clone()))
+                    }),
+            )
+            .chain(self.flushed_log_gen.map(VersionEdit::FlushedLogGen))
             .collect_vec()
     }
 
     pub(crate) fn table(&self, level: usize, offset: usize) -> Option<&dyn Table> {
         self.level_slice[level]
             .get(offset)
-            .and_then(|scope| self.table_loader.get(scope.get_gen()))
+            .and_then(|scope| self.table_loader.get(scope.get_gen(), level))
     }
 
     /// 只限定获取Level 0的Table
@@ -199,40 +245,125 @@ impl Version {
     pub(crate) fn tables_by_level_0(&self) -> Vec<&dyn Table> {
         self.level_slice[LEVEL_0]
             .iter()
-            .filter_map(|scope| self.table_loader.get(scope.get_gen()))
+            .filter_map(|scope| self.table_loader.get(scope.get_gen(), LEVEL_0))
             .collect_vec()
     }
 
-    pub(crate) fn index(&self, level: usize, source_gen: i64) -> Option<usize> {
-        self.level_slice[level]
-            .iter()
-            .enumerate()
-            .find(|(_, scope)| source_gen.eq(&scope.get_gen()))
-            .map(|(index, _)| index)
-    }
-
-    pub(crate) fn first_tables(
+    /// 选取Major压缩时指定Level中的Tables
+    ///
+    /// - Level 0: 由旧至新选取，因为Level 0中的Table之间可能存在重叠
+    /// - Level N: 优先选取删除标记占比达到阈值的Table，否则依照CompactionPri进行选取
+    pub(crate) fn pick_tables(
         &self,
+        config: &Config,
         level: usize,
-        size: usize,
     ) -> Option<(Vec<&dyn Table>, Vec<Scope>)> {
-        if self.level_slice[level].is_empty() {
+        let level_len = self.level_slice[level].len();
+        let size = config.major_select_file_size.max(1);
+
+        if level_len == 0 {
             return None;
         }
+        let start = if level == LEVEL_0 {
+            0
+        } else {
+            self.tombstone_dense_index(config, level)
+                .unwrap_or_else(|| match config.compaction_pri {
+                    CompactionPri::RoundRobin => self.compact_point_index(level),
+                    CompactionPri::MinOverlappingRatio => self.min_overlapping_index(level, size),
+                })
+                // 尽可能选取满size个Table
+                .min(level_len.saturating_sub(size))
+        };
 
         Some(
-            self.level_slice[level]
+            self.level_slice[level][start..]
                 .iter()
                 .take(size)
                 .filter_map(|scope| {
                     self.table_loader
-                        .get(scope.get_gen())
+                        .get(scope.get_gen(), level)
                         .map(|ss_table| (ss_table, scope.clone()))
                 })
                 .unzip(),
         )
     }
 
+    /// 获取压缩指针之后的第一个Table的位置，若已至末尾则回到开头
+    fn compact_point_index(&self, level: usize) -> usize {
+        self.compact_points[level]
+            .as_ref()
+            .map(|key| self.level_slice[level].partition_point(|scope| scope.start <= key))
+            .filter(|index| *index < self.level_slice[level].len())
+            .unwrap_or(0)
+    }
+
+    /// 获取与下一Level重叠比例最小的连续size个Table的起始位置
+    ///
+    /// 重叠比例 = 下一Level中相交的Tables的磁盘占用 / 选取的Tables的磁盘占用
+    fn min_overlapping_index(&self, level: usize, size: usize) -> usize {
+        let scopes_l = &self.level_slice[level];
+        let Some(scopes_ll) = self.level_slice.get(level + 1) else {
+            return 0;
+        };
+        let size_of_disk = |scope: &Scope, level: usize| {
+            self.table_loader
+                .get(scope.get_gen(), level)
+                .map_or(0, |table| table.size_of_disk())
+        };
+        // 下一Level的磁盘占用前缀和，用于快速计算任意连续范围内的磁盘占用
+        let prefix_ll =
+            Self::prefix_sum(scopes_ll.iter().map(|scope| size_of_disk(scope, level + 1)));
+        let prefix_l = Self::prefix_sum(scopes_l.iter().map(|scope| size_of_disk(scope, level)));
+
+        (0..=scopes_l.len().saturating_sub(size))
+            .min_by_key(|&start| {
+                let end = (start + size).min(scopes_l.len());
+                let (first_key, last_key) = (&scopes_l[start].start, &scopes_l[end - 1].end);
+                let ll_start = scopes_ll.partition_point(|scope| scope.end < *first_key);
+                let ll_end = scopes_ll.partition_point(|scope| scope.start <= *last_key);
+
+                let overlapping = prefix_ll[ll_end.max(ll_start)] - prefix_ll[ll_start];
+                let self_size = (prefix_l[end] - prefix_l[start]).max(1);
+
+                u128::from(overlapping) * 1_000_000 / u128::from(self_size)
+            })
+            .unwrap_or(0)
+    }
+
+    fn prefix_sum(iter: impl Iterator<Item = u64>) -> Vec<u64> {
+        let mut sum = 0;
+
+        [0].into_iter()
+            .chain(iter.map(|size| {
+                sum += size;
+                sum
+            }))
+            .collect_vec()
+    }
+
+    /// 获取指定Level中删除标记占比最高且达到阈值的Table的位置
+    fn tombstone_dense_index(&self, config: &Config, level: usize) -> Option<usize> {
+        self.level_slice[level]
+            .iter()
+            .enumerate()
+            .filter_map(|(index, scope)| {
+                self.table_loader
+                    .get(scope.get_gen(), level)
+                    .filter(|table| Self::is_tombstone_dense(config, *table))
+                    .map(|table| (index, table.tombstone_len() * 1000 / table.len()))
+            })
+            .max_by_key(|(_, permille)| *permille)
+            .map(|(index, _)| index)
+    }
+
+    /// 判断Table中的删除标记占比是否达到阈值
+    pub(crate) fn is_tombstone_dense(config: &Config, table: &dyn Table) -> bool {
+        config.tombstone_compaction_percent > 0
+            && table.len() > 0
+            && table.tombstone_len() * 100 >= table.len() * config.tombstone_compaction_percent
+    }
+
     /// 获取指定level中与scope冲突的Tables和Scopes
     pub(crate) fn tables_by_scopes(
         &self,
@@ -244,7 +375,7 @@ impl Version {
             .filter(|scope| scope.meet(target_scope))
             .filter_map(|scope| {
                 self.table_loader
-                    .get(scope.get_gen())
+                    .get(scope.get_gen(), level)
                     .map(|ss_table| (ss_table, scope.clone()))
             })
             .unzip()
@@ -258,44 +389,107 @@ impl Version {
         self.level_slice[level]
             .iter()
             .filter(|scope| fn_meet(scope))
-            .filter_map(|scope| self.table_loader.get(scope.get_gen()))
+            .filter_map(|scope| self.table_loader.get(scope.get_gen(), level))
+            .collect_vec()
+    }
+
+    /// 获取指定level中可能存在以prefix为前缀的Key的Tables
+    ///
+    /// 跳过Scope不相交或前缀过滤器排除了该前缀的Table
+    pub(crate) fn tables_by_prefix(
+        &self,
+        level: usize,
+        extractor: Option<&dyn PrefixExtractor>,
+        prefix: &[u8],
+    ) -> Vec<&dyn Table> {
+        self.tables_by_meet_scope(level, |scope| scope.meet_by_prefix(prefix))
+            .into_iter()
+            .filter(|table| {
+                extractor.map_or(true, |extractor| {
+                    table.may_contain_prefix(extractor, prefix)
+                })
+            })
+            .collect_vec()
+    }
+
+    /// 获取指定level中对应gen的Scopes
+    pub(crate) fn scopes_by_gens(&self, level: usize, gens: &[i64]) -> Vec<Scope> {
+        self.level_slice[level]
+            .iter()
+            .filter(|scope| gens.contains(&scope.get_gen()))
+            .cloned()
             .collect_vec()
     }
 
+    /// 判断指定level之下的所有Level中是否不存在与scope相交的数据
+    ///
+    /// 若为true则说明该level对于此scope而言已经是最底层，压缩时可以将删除标记与被覆盖的旧数据一并清除
+    pub(crate) fn is_bottom_with_scope(&self, level: usize, scope: &Scope) -> bool {
+        self.level_slice[level + 1..]
+            .iter()
+            .flatten()
+            .all(|level_scope| !level_scope.meet(scope))
+    }
+
     /// 使用Key从现有Tables中获取对应的数据
+    ///
+    /// 命中删除标记时即停止查询，避免更底层Level中被删除的旧数据被读出
     pub(crate) fn query(&self, key: &[u8]) -> Result<Option<Bytes>> {
         let table_loader = &self.table_loader;
         // Level 0的Table是无序且Table间的数据是可能重复的,因此需要遍历
         for scope in self.level_slice[LEVEL_0].iter().rev() {
             if scope.meet_by_key(key) {
-                if let Some(ss_table) = table_loader.get(scope.get_gen()) {
-                    if let Some(value) = ss_table.query(key)? {
-                        return Ok(Some(value));
+                if let Some(ss_table) = table_loader.get(scope.get_gen(), LEVEL_0) {
+                    if let Some(option_value) = self.query_table(LEVEL_0, ss_table, key)? {
+                        return Ok(option_value);
                     }
                 }
             }
         }
-        // Level 1-7的数据排布有序且唯一，因此在每一个等级可以直接找到唯一一个Key可能在范围内的Table
-        for level in 1..7 {
+        // Level 1-N的数据排布有序且唯一，因此在每一个等级可以直接找到唯一一个Key可能在范围内的Table
+        for level in 1..self.num_levels() {
             let offset = self.query_meet_index(key, level);
 
+            // 该Level中不存在该Key时需要继续查询更底层的Level
             if let Some(scope) = self.level_slice[level].get(offset) {
-                return if let Some(ss_table) = table_loader.get(scope.get_gen()) {
-                    ss_table.query(key)
-                } else {
-                    Ok(None)
-                };
+                if scope.meet_by_key(key) {
+                    if let Some(ss_table) = table_loader.get(scope.get_gen(), level) {
+                        if let Some(option_value) = self.query_table(level, ss_table, key)? {
+                            return Ok(option_value);
+                        }
+                    }
+                }
             }
         }
 
         Ok(None)
     }
 
-    /// 获取指定Table索引位置
-    pub(crate) fn find_index_by_level(&self, option_first: Option<i64>, level: usize) -> usize {
-        option_first
-            .and_then(|gen| self.index(level, gen))
-            .unwrap_or(0)
+    /// 先通过过滤器排除不存在的Key，再读取Block进行查询，并记录过滤器的判断结果
+    ///
+    /// 返回值同`Table::query`，Some(None)表示命中删除标记
+    fn query_table(
+        &self,
+        level: usize,
+        table: &dyn Table,
+        key: &[u8],
+    ) -> Result<Option<Option<Bytes>>> {
+        let may_contain = table.may_contain(key);
+        let option_value = if may_contain { table.query(key)? } else { None };
+
+        if table.has_filter() {
+            self.table_loader
+                .filter_recorder()
+                .record(level, may_contain, option_value.is_some());
+        }
+        Ok(option_value)
+    }
+
+    /// 获取scope在指定Level中应插入的索引位置
+    ///
+    /// 即该Level中第一个与scope相交或位于scope之后的Table的位置
+    pub(crate) fn insert_index_by_scope(&self, level: usize, scope: &Scope) -> usize {
+        self.level_slice[level].partition_point(|level_scope| level_scope.end < scope.start)
     }
 
     pub(crate) fn query_meet_index(&self, key: &[u8], level: usize) -> usize {
@@ -304,11 +498,98 @@ impl Version {
             .unwrap_or_else(|index| index.saturating_sub(1))
     }
 
-    /// 判断是否溢出指定的Table数量
+    /// 判断是否溢出指定Level的压缩阈值
+    ///
+    /// - Level 0: 以Table数量进行判断，因为SkipTable并不占用磁盘且Level 0中的Table数量直接影响读取性能
+    /// - Level N: 以数据的磁盘占用与该Level的目标大小进行判断
     pub(crate) fn is_threshold_exceeded_major(&self, config: &Config, level: usize) -> bool {
-        self.level_slice[level].len()
-            >= (config.major_threshold_with_sst_size
-                * config.level_sst_magnification.pow(level as u32))
+        if level == LEVEL_0 {
+            self.level_slice[level].len() >= config.major_threshold_with_sst_size
+        } else {
+            let size_of_disk = self.level_metas[level].size_of_disk;
+
+            size_of_disk > 0 && size_of_disk > self.level_targets(config).1[level]
+        }
+    }
+
+    /// 判断指定Level是否需要进行Major压缩
+    ///
+    /// Table数量溢出阈值或存在删除标记占比达到阈值的Table时需要压缩
+    pub(crate) fn is_compaction_needed(&self, config: &Config, level: usize) -> bool {
+        self.is_threshold_exceeded_major(config, level)
+            || self.tombstone_dense_index(config, level).is_some()
+    }
+
+    /// 获取Base Level与各Level的目标大小
+    ///
+    /// - 静态: Base Level为Level 1，Level N的目标大小为max_bytes_for_level_base * level_sst_magnification^(N - 1)
+    /// - 动态: 以最大的Level的实际大小作为最底层的目标大小，并依照倍率向上推算各Level的目标大小，
+    ///   直至目标大小不超过max_bytes_for_level_base的Level作为Base Level，
+    ///   Base Level之上的Level目标大小为0，Level 0将直接压缩至Base Level，
+    ///   使大部分数据都处于最底层以限制空间放大
+    pub(crate) fn level_targets(&self, config: &Config) -> (usize, Vec<u64>) {
+        let num_levels = self.num_levels();
+        let last_level = num_levels - 1;
+        let magnification = config.level_sst_magnification.max(1) as u64;
+        let base = config.max_bytes_for_level_base;
+        let mut targets = vec![0; num_levels];
+
+        if !config.level_compaction_dynamic_level_bytes {
+            let mut target = base;
+            for level_target in targets.iter_mut().skip(1) {
+                *level_target = target;
+                target = target.saturating_mul(magnification);
+            }
+            return (1, targets);
+        }
+        let max_level_size = self.level_metas[1..]
+            .iter()
+            .map(|meta| meta.size_of_disk)
+            .max()
+            .unwrap_or(0);
+        let mut base_level = last_level;
+        targets[last_level] = max_level_size.max(base);
+
+        while base_level > 1 && targets[base_level] > base {
+            targets[base_level - 1] = targets[base_level] / magnification;
+            base_level -= 1;
+        }
+
+        (base_level, targets)
+    }
+
+    /// 获取指定Level压缩的目标Level
+    ///
+    /// Level 0压缩至Base Level，但Base Level之上仍有数据时(如动态目标大小变化时)则压缩至其中第一个存在数据的Level，
+    /// 以保证较新的数据总是位于较上层的Level中
+    pub(crate) fn next_level(&self, config: &Config, level: usize) -> usize {
+        if level == LEVEL_0 {
+            let base_level = self.level_targets(config).0;
+
+            (1..base_level)
+                .find(|level| !self.level_slice[*level].is_empty())
+                .unwrap_or(base_level)
+        } else {
+            level + 1
+        }
+    }
+
+    /// 压缩债务：Level 0超出阈值的SSTable数量所对应的预估数据大小与各Level超出目标大小的数据大小之和
+    pub(crate) fn compaction_debt(&self, config: &Config) -> u64 {
+        let level_0_debt = self.level_slice[LEVEL_0]
+            .len()
+            .saturating_sub(config.major_threshold_with_sst_size) as u64
+            * config.sst_file_size as u64;
+        let targets = self.level_targets(config).1;
+
+        (1..self.num_levels() - 1)
+            .map(|level| {
+                self.level_metas[level]
+                    .size_of_disk
+                    .saturating_sub(targets[level])
+            })
+            .sum::<u64>()
+            + level_0_debt
     }
 }
 
@@ -1,15 +1,20 @@
-use crate::kernel::io::{FileExtension, IoFactory, IoType, IoWriter};
-use crate::kernel::lsm::log::{LogLoader, LogWriter};
-use crate::kernel::lsm::storage::{Config, Gen};
+use crate::kernel::io::{FileExtension, IoFactory};
+use crate::kernel::lsm::compactor::LEVEL_0;
+use crate::kernel::lsm::log::LogLoader;
+use crate::kernel::lsm::mem_table::DEFAULT_WAL_PATH;
+use crate::kernel::lsm::storage::{Config, READ_ONLY_MESSAGE};
 use crate::kernel::lsm::table::loader::TableLoader;
 use crate::kernel::lsm::version::cleaner::Cleaner;
 use crate::kernel::lsm::version::edit::VersionEdit;
+use crate::kernel::lsm::version::manifest::Manifest;
 use crate::kernel::lsm::version::{
-    snapshot_gen, version_display, Version, DEFAULT_SS_TABLE_PATH, DEFAULT_VERSION_PATH,
+    version_display, Version, DEFAULT_SS_TABLE_PATH, DEFAULT_VERSION_PATH,
 };
-use crate::kernel::Result;
+use crate::kernel::{sorted_gen_list, Result};
+use crate::KernelError;
 use itertools::Itertools;
-use std::mem;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 use tokio::sync::mpsc::unbounded_channel;
@@ -19,14 +24,17 @@ use tracing::info;
 /// 用于切换Version的封装Inner
 struct VersionInner {
     version: Arc<Version>,
-    ver_log_writer: (LogWriter<Box<dyn IoWriter>>, i64),
+    /// 只读模式下不创建Manifest
+    manifest: Option<Manifest>,
 }
 
 pub(crate) struct VersionStatus {
     inner: RwLock<VersionInner>,
     ss_table_loader: Arc<TableLoader>,
-    log_factory: Arc<IoFactory>,
+    manifest_path: PathBuf,
     edit_approximate_count: AtomicUsize,
+    /// 恢复时Manifest末尾是否存在被丢弃的不完整记录
+    is_manifest_torn: bool,
 }
 
 impl VersionStatus {
@@ -41,24 +49,22 @@ impl VersionStatus {
             wal,
         )?);
 
-        let log_factory = Arc::new(IoFactory::new(
-            config.path().join(DEFAULT_VERSION_PATH),
-            FileExtension::Log,
-        )?);
-
-        let (ver_log_loader, vec_batch_log, log_gen) = LogLoader::reload(
-            config.path(),
-            (DEFAULT_VERSION_PATH, Some(snapshot_gen(&log_factory)?)),
-            IoType::Direct,
-            |bytes| Ok(bincode::deserialize::<Vec<VersionEdit>>(bytes)?),
-        )?;
-
-        let vec_log = vec_batch_log.into_iter().flatten().collect_vec();
-
-        let edit_approximate_count = AtomicUsize::new(vec_log.len());
+        let manifest_path = config.path().join(DEFAULT_VERSION_PATH);
+        let (vec_log, is_manifest_torn) = match Manifest::recover(&manifest_path)? {
+            Some(recovery) => (
+                recovery.vec_batch.into_iter().flatten().collect_vec(),
+                recovery.is_torn,
+            ),
+            None => (Vec::new(), false),
+        };
 
         let (clean_tx, clean_rx) = unbounded_channel();
-        let version = Arc::new(Version::load_from_log(vec_log, &ss_table_loader, clean_tx)?);
+        let version = Arc::new(Version::load_from_log(
+            vec_log,
+            &ss_table_loader,
+            clean_tx,
+            config.num_levels,
+        )?);
 
         let mut cleaner = Cleaner::new(&ss_table_loader, clean_rx);
 
@@ -66,19 +72,67 @@ impl VersionStatus {
             cleaner.listen().await;
         });
 
-        let ver_log_writer = ver_log_loader.writer(log_gen)?;
+        // 每次启动时都以当前Version的快照创建新的Manifest，避免对旧Manifest的续写
+        let manifest = if config.read_only {
+            None
+        } else {
+            Some(Manifest::create(&manifest_path, &version.to_vec_edit())?)
+        };
 
         Ok(Self {
-            inner: RwLock::new(VersionInner {
-                version,
-                ver_log_writer: ((ver_log_writer), log_gen),
-            }),
+            inner: RwLock::new(VersionInner { version, manifest }),
             ss_table_loader,
-            log_factory,
-            edit_approximate_count,
+            manifest_path,
+            edit_approximate_count: AtomicUsize::new(0),
+            is_manifest_torn,
         })
     }
 
+    /// 恢复时Manifest末尾的记录是否因宕机而被丢弃
+    ///
+    /// 此时Version可能缺少最后一次变更，其所引用的文件不可信，不应以此清理文件
+    pub(crate) fn is_manifest_torn(&self) -> bool {
+        self.is_manifest_torn
+    }
+
+    /// 将宕机前尚未持久化至Version的WAL重建为Level 0的Table
+    ///
+    /// MemTable仅会重放最新的WAL，而在MemTable::swap与log_and_apply之间宕机时，
+    /// immutable所对应的WAL既未被重放也未被Version引用，因此需要在启动时将其持久化
+    ///
+    /// Tips: 旧版本的Version中未记录flushed_log_gen，此时无法区分未持久化的WAL与已被压缩的Table的残留，因此不进行处理
+    pub(crate) async fn recover_unflushed_logs(&self, config: &Config, log_gen: i64) -> Result<()> {
+        let version = self.current().await;
+        let Some(flushed_log_gen) = version.flushed_log_gen else {
+            return Ok(());
+        };
+        let live_gens: HashSet<i64> = version.gens().collect();
+        drop(version);
+
+        for gen in sorted_gen_list(&config.path().join(DEFAULT_WAL_PATH), FileExtension::Log)? {
+            if gen <= flushed_log_gen || gen >= log_gen || live_gens.contains(&gen) {
+                continue;
+            }
+            let mut vec_edit = Vec::with_capacity(2);
+            if let Some((scope, meta)) = self
+                .loader()
+                .create_with_wal(gen, config.level_table_type[LEVEL_0])
+                .await?
+            {
+                vec_edit.push(VersionEdit::NewFile((vec![scope], LEVEL_0), 0, meta));
+            }
+            vec_edit.push(VersionEdit::FlushedLogGen(gen));
+            self.log_and_apply(vec_edit, config.ver_log_snapshot_threshold)
+                .await?;
+            info!(
+                "[VersionStatus][recover_unflushed_logs][WAL: {}]: Flushed",
+                gen
+            );
+        }
+
+        Ok(())
+    }
+
     pub(crate) async fn current(&self) -> Arc<Version> {
         Arc::clone(&self.inner.read().await.version)
     }
@@ -91,50 +145,48 @@ impl VersionStatus {
     ) -> Result<()> {
         let mut new_version = Version::clone(self.current().await.as_ref());
         let mut inner = self.inner.write().await;
+        let VersionInner { version, manifest } = &mut *inner;
+        let manifest = manifest
+            .as_mut()
+            .ok_or(KernelError::NotSupport(READ_ONLY_MESSAGE))?;
         version_display(&new_version, "log_and_apply");
 
         if self.edit_approximate_count.load(Ordering::Relaxed) >= snapshot_threshold {
-            Self::write_snap_shot(&mut inner, &self.log_factory).await?;
+            Self::write_snap_shot(version, manifest, &self.manifest_path)?;
+            self.edit_approximate_count.store(0, Ordering::Relaxed);
         } else {
             let _ = self.edit_approximate_count.fetch_add(1, Ordering::Relaxed);
         }
 
-        let _ = inner
-            .ver_log_writer
-            .0
-            .add_record(&bincode::serialize(&vec_version_edit)?)?;
+        manifest.add_record(&vec_version_edit)?;
 
         new_version.apply(vec_version_edit)?;
-        inner.version = Arc::new(new_version);
+        *version = Arc::new(new_version);
 
         Ok(())
     }
 
-    async fn write_snap_shot(inner: &mut VersionInner, log_factory: &IoFactory) -> Result<()> {
-        let version = &inner.version;
+    /// 以当前Version的快照切换至新的Manifest
+    ///
+    /// 新的Manifest完整落盘并由CURRENT指向后才会删除旧的Manifest，因此过程中宕机不会丢失Version
+    fn write_snap_shot(
+        version: &Version,
+        manifest: &mut Manifest,
+        manifest_path: &Path,
+    ) -> Result<()> {
         info!(
             "[Version: {}][write_snap_shot]: Start Snapshot!",
             version.version_num
         );
-        let new_gen = Gen::create();
-        let new_writer = log_factory.writer(new_gen, IoType::Direct)?;
-        let (mut old_writer, old_gen) = mem::replace(
-            &mut inner.ver_log_writer,
-            (LogWriter::new(new_writer), new_gen),
+        let old_gen = manifest.gen();
+        *manifest = Manifest::create(manifest_path, &version.to_vec_edit())?;
+        info!(
+            "[Version: {}][write_snap_shot]: Manifest {} -> {}",
+            version.version_num,
+            old_gen,
+            manifest.gen()
         );
 
-        old_writer.flush()?;
-
-        // 在快照中 append edit, 防止快照中宕机发生在删除旧 log 之后造成 增量 edit 未写入新log的问题
-        let snap_shot_version_edits = version.to_vec_edit();
-        let _ = inner
-            .ver_log_writer
-            .0
-            .add_record(&bincode::serialize(&snap_shot_version_edits)?)?;
-
-        // 删除旧的 version log
-        log_factory.clean(old_gen)?;
-
         Ok(())
     }
 
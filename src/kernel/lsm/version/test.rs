@@ -1,13 +1,18 @@
-use crate::kernel::io::IoType;
+use crate::kernel::io::{FileExtension, IoType};
+use crate::kernel::lsm::compactor::CompactionPri;
 use crate::kernel::lsm::log::LogLoader;
 use crate::kernel::lsm::storage::Config;
+use crate::kernel::lsm::table::meta::TableMeta;
 use crate::kernel::lsm::table::TableType;
 use crate::kernel::lsm::version::edit::VersionEdit;
+use crate::kernel::lsm::version::manifest::{Manifest, Recovery, DEFAULT_CURRENT_FILE};
 use crate::kernel::lsm::version::status::VersionStatus;
 use crate::kernel::lsm::version::Version;
 use crate::kernel::lsm::version::DEFAULT_VERSION_PATH;
-use crate::kernel::Result;
+use crate::kernel::{sorted_gen_list, Result};
+use crate::KernelError;
 use bytes::Bytes;
+use std::fs;
 use std::sync::Arc;
 use std::time::Duration;
 use tempfile::TempDir;
@@ -33,19 +38,23 @@ fn test_version_clean() -> Result<()> {
 
         let sst_loader = ver_status.loader().clone();
 
-        let (scope_1, meta_1) = sst_loader.create(
-            1,
-            vec![(Bytes::from_static(b"test"), None)],
-            0,
-            TableType::SortedString,
-        )?;
-
-        let (scope_2, meta_2) = sst_loader.create(
-            2,
-            vec![(Bytes::from_static(b"test"), None)],
-            0,
-            TableType::SortedString,
-        )?;
+        let (scope_1, meta_1) = sst_loader
+            .create(
+                1,
+                vec![(Bytes::from_static(b"test"), None)],
+                0,
+                TableType::SortedString,
+            )
+            .await?;
+
+        let (scope_2, meta_2) = sst_loader
+            .create(
+                2,
+                vec![(Bytes::from_static(b"test"), None)],
+                0,
+                TableType::SortedString,
+            )
+            .await?;
 
         let vec_edit_1 = vec![VersionEdit::NewFile((vec![scope_1], 0), 0, meta_1)];
 
@@ -68,19 +77,17 @@ fn test_version_clean() -> Result<()> {
         ver_status.log_and_apply(vec_edit_3, 2).await?;
 
         // 测试对比快照
-        let (_, snapshot, _) = LogLoader::reload(
-            config.path(),
-            (DEFAULT_VERSION_PATH, None),
-            IoType::Direct,
-            |bytes| Ok(bincode::deserialize::<Vec<VersionEdit>>(bytes)?),
-        )?;
+        let snapshot = Manifest::recover(&config.path().join(DEFAULT_VERSION_PATH))?;
 
         assert_eq!(
             snapshot,
-            vec![
-                vec![VersionEdit::NewFile((vec![scope_2], 0), 0, meta_2)],
-                vec![VersionEdit::DeleteFile((vec![2], 0), meta_2)],
-            ]
+            Some(Recovery {
+                vec_batch: vec![
+                    vec![VersionEdit::NewFile((vec![scope_2], 0), 0, meta_2)],
+                    vec![VersionEdit::DeleteFile((vec![2], 0), meta_2)],
+                ],
+                is_torn: false,
+            })
         );
 
         assert!(sst_loader.is_table_file_exist(1)?);
@@ -125,19 +132,25 @@ fn test_version_apply_and_log() -> Result<()> {
         // 因为VersionStatus检测无Log时会扫描当前文件夹下的SSTable进行重组以进行容灾
         let ver_status_1 = VersionStatus::load_with_path(config.clone(), wal.clone())?;
 
-        let (scope_1, meta_1) = ver_status_1.loader().create(
-            1,
-            vec![(Bytes::from_static(b"test"), None)],
-            0,
-            TableType::SortedString,
-        )?;
-
-        let (scope_2, meta_2) = ver_status_1.loader().create(
-            2,
-            vec![(Bytes::from_static(b"test"), None)],
-            0,
-            TableType::SortedString,
-        )?;
+        let (scope_1, meta_1) = ver_status_1
+            .loader()
+            .create(
+                1,
+                vec![(Bytes::from_static(b"test"), None)],
+                0,
+                TableType::SortedString,
+            )
+            .await?;
+
+        let (scope_2, meta_2) = ver_status_1
+            .loader()
+            .create(
+                2,
+                vec![(Bytes::from_static(b"test"), None)],
+                0,
+                TableType::SortedString,
+            )
+            .await?;
 
         let vec_edit = vec![
             VersionEdit::NewFile((vec![scope_1], 0), 0, meta_1),
@@ -147,19 +160,25 @@ fn test_version_apply_and_log() -> Result<()> {
 
         ver_status_1.log_and_apply(vec_edit, 10).await?;
 
-        let (scope_3, meta_3) = ver_status_1.loader().create(
-            3,
-            vec![(Bytes::from_static(b"test3"), None)],
-            0,
-            TableType::SortedString,
-        )?;
-
-        let (scope_4, meta_4) = ver_status_1.loader().create(
-            4,
-            vec![(Bytes::from_static(b"test4"), None)],
-            0,
-            TableType::SortedString,
-        )?;
+        let (scope_3, meta_3) = ver_status_1
+            .loader()
+            .create(
+                3,
+                vec![(Bytes::from_static(b"test3"), None)],
+                0,
+                TableType::SortedString,
+            )
+            .await?;
+
+        let (scope_4, meta_4) = ver_status_1
+            .loader()
+            .create(
+                4,
+                vec![(Bytes::from_static(b"test4"), None)],
+                0,
+                TableType::SortedString,
+            )
+            .await?;
 
         let vec_edit2 = vec![
             VersionEdit::NewFile((vec![scope_3], 0), 0, meta_3),
@@ -181,3 +200,384 @@ fn test_version_apply_and_log() -> Result<()> {
         Ok(())
     })
 }
+
+#[test]
+fn test_version_recount_level_metas() -> Result<()> {
+    let temp_dir = TempDir::new().expect("unable to create temporary working directory");
+
+    tokio_test::block_on(async move {
+        let config = Config::new(temp_dir.into_path());
+
+        let (wal, _, _) = LogLoader::reload(
+            config.path(),
+            (DEFAULT_VERSION_PATH, Some(1)),
+            IoType::Direct,
+            |_| Ok(()),
+        )?;
+        let ver_status_1 = VersionStatus::load_with_path(config.clone(), wal.clone())?;
+
+        let mut vec_scope = Vec::new();
+        let mut vec_meta = Vec::new();
+        for gen in 1..=2 {
+            let (scope, meta) = ver_status_1
+                .loader()
+                .create(
+                    gen,
+                    vec![(Bytes::from(format!("test{gen}")), None)],
+                    1,
+                    TableType::SortedString,
+                )
+                .await?;
+            vec_scope.push(scope);
+            vec_meta.push(meta);
+        }
+        // 模拟旧版本的Version快照：Level 1的统计数据为0
+        ver_status_1
+            .log_and_apply(
+                vec![VersionEdit::NewFile(
+                    (vec_scope, 1),
+                    0,
+                    TableMeta::default(),
+                )],
+                10,
+            )
+            .await?;
+        assert_eq!(
+            ver_status_1.current().await.level_metas[1],
+            TableMeta::default()
+        );
+        drop(ver_status_1);
+
+        let ver_status_2 = VersionStatus::load_with_path(config, wal.clone())?;
+        let version_2 = ver_status_2.current().await;
+
+        assert_eq!(version_2.level_metas[1], TableMeta::fusion(&vec_meta));
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_manifest_torn_and_corrupted() -> Result<()> {
+    let temp_dir = TempDir::new().expect("unable to create temporary working directory");
+    let dir_path = temp_dir.path().join(DEFAULT_VERSION_PATH);
+
+    let batch = |key: &'static [u8]| vec![VersionEdit::CompactPoint(1, Bytes::from_static(key))];
+    let mut manifest = Manifest::create(&dir_path, &batch(b"1"))?;
+    manifest.add_record(&batch(b"2"))?;
+    manifest.add_record(&batch(b"3"))?;
+    let path = FileExtension::Manifest.path_with_gen(&dir_path, manifest.gen());
+    drop(manifest);
+    let bytes = fs::read(&path)?;
+
+    // 末尾的记录写入不完整时丢弃该记录
+    fs::write(&path, &bytes[..bytes.len() - 3])?;
+    assert_eq!(
+        Manifest::recover(&dir_path)?,
+        Some(Recovery {
+            vec_batch: vec![batch(b"1"), batch(b"2")],
+            is_torn: true,
+        })
+    );
+
+    // 末尾的记录长度完整但内容损坏时同样视为写入不完整
+    let mut torn_bytes = bytes.clone();
+    *torn_bytes.last_mut().unwrap() ^= u8::MAX;
+    fs::write(&path, &torn_bytes)?;
+    assert_eq!(
+        Manifest::recover(&dir_path)?,
+        Some(Recovery {
+            vec_batch: vec![batch(b"1"), batch(b"2")],
+            is_torn: true,
+        })
+    );
+
+    // 中间的记录损坏时不能当作文件末尾而丢弃之后的记录
+    let mut corrupted_bytes = bytes;
+    corrupted_bytes[10] ^= u8::MAX;
+    fs::write(&path, &corrupted_bytes)?;
+    assert!(matches!(
+        Manifest::recover(&dir_path),
+        Err(KernelError::CrcMisMatch)
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_version_manifest_switch() -> Result<()> {
+    let temp_dir = TempDir::new().expect("unable to create temporary working directory");
+
+    tokio_test::block_on(async move {
+        let config = Config::new(temp_dir.into_path());
+        let manifest_path = config.path().join(DEFAULT_VERSION_PATH);
+
+        let (wal, _, _) = LogLoader::reload(
+            config.path(),
+            (DEFAULT_VERSION_PATH, Some(1)),
+            IoType::Direct,
+            |_| Ok(()),
+        )?;
+        let ver_status_1 = VersionStatus::load_with_path(config.clone(), wal.clone())?;
+
+        for gen in 1..=4 {
+            let (scope, meta) = ver_status_1
+                .loader()
+                .create(
+                    gen,
+                    vec![(Bytes::from(format!("test{gen}")), None)],
+                    0,
+                    TableType::SortedString,
+                )
+                .await?;
+            // 阈值为1时每两次log_and_apply便会切换一次Manifest
+            ver_status_1
+                .log_and_apply(vec![VersionEdit::NewFile((vec![scope], 0), 0, meta)], 1)
+                .await?;
+        }
+        let version_1 = Version::clone(ver_status_1.current().await.as_ref());
+        drop(ver_status_1);
+
+        // 旧的Manifest在切换后均被删除，CURRENT指向唯一的Manifest
+        let manifest_gens = sorted_gen_list(&manifest_path, FileExtension::Manifest)?;
+        assert_eq!(manifest_gens.len(), 1);
+        assert_eq!(
+            fs::read_to_string(manifest_path.join(DEFAULT_CURRENT_FILE))?.trim(),
+            format!("{}.manifest", manifest_gens[0])
+        );
+
+        // 模拟切换过程中宕机：新的Manifest仅写入了一半且CURRENT尚未更新
+        fs::write(
+            FileExtension::Manifest.path_with_gen(&manifest_path, manifest_gens[0] + 1),
+            b"broken",
+        )?;
+        fs::write(manifest_path.join("CURRENT.tmp"), b"broken")?;
+
+        let ver_status_2 = VersionStatus::load_with_path(config, wal.clone())?;
+        let version_2 = ver_status_2.current().await;
+
+        assert_eq!(version_1.level_slice, version_2.level_slice);
+        assert_eq!(version_1.meta_data, version_2.meta_data);
+        assert_eq!(
+            sorted_gen_list(&manifest_path, FileExtension::Manifest)?.len(),
+            1
+        );
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_version_pick_tables() -> Result<()> {
+    let temp_dir = TempDir::new().expect("unable to create temporary working directory");
+
+    tokio_test::block_on(async move {
+        let config = Config::new(temp_dir.into_path()).major_select_file_size(1);
+
+        let (wal, _, _) = LogLoader::reload(
+            config.path(),
+            (DEFAULT_VERSION_PATH, Some(1)),
+            IoType::Direct,
+            |_| Ok(()),
+        )?;
+        let ver_status = VersionStatus::load_with_path(config.clone(), wal.clone())?;
+        let loader = ver_status.loader();
+        let value = Some(Bytes::from_static(b"value"));
+
+        // Level 1: [a, b] [c, d(删除标记)] [e, f]
+        let (scope_1, meta_1) = loader
+            .create(
+                1,
+                vec![
+                    (Bytes::from_static(b"a"), value.clone()),
+                    (Bytes::from_static(b"b"), value.clone()),
+                ],
+                1,
+                TableType::SortedString,
+            )
+            .await?;
+        let (scope_2, meta_2) = loader
+            .create(
+                2,
+                vec![
+                    (Bytes::from_static(b"c"), value.clone()),
+                    (Bytes::from_static(b"d"), None),
+                ],
+                1,
+                TableType::SortedString,
+            )
+            .await?;
+        let (scope_3, meta_3) = loader
+            .create(
+                3,
+                vec![
+                    (Bytes::from_static(b"e"), value.clone()),
+                    (Bytes::from_static(b"f"), value.clone()),
+                ],
+                1,
+                TableType::SortedString,
+            )
+            .await?;
+        // Level 2: [a, c] [d]，[e, f]在Level 2中不存在重叠
+        let (scope_4, meta_4) = loader
+            .create(
+                4,
+                vec![
+                    (Bytes::from_static(b"a"), value.clone()),
+                    (Bytes::from_static(b"b"), value.clone()),
+                    (Bytes::from_static(b"c"), value.clone()),
+                ],
+                2,
+                TableType::SortedString,
+            )
+            .await?;
+        let (scope_5, meta_5) = loader
+            .create(
+                5,
+                vec![(Bytes::from_static(b"d"), value.clone())],
+                2,
+                TableType::SortedString,
+            )
+            .await?;
+
+        ver_status
+            .log_and_apply(
+                vec![
+                    VersionEdit::NewFile(
+                        (vec![scope_1, scope_2, scope_3], 1),
+                        0,
+                        TableMeta::fusion(&[meta_1, meta_2, meta_3]),
+                    ),
+                    VersionEdit::NewFile(
+                        (vec![scope_4, scope_5], 2),
+                        0,
+                        TableMeta::fusion(&[meta_4, meta_5]),
+                    ),
+                ],
+                10,
+            )
+            .await?;
+
+        let picked_gen = |version: &Version, config: &Config| {
+            version
+                .pick_tables(config, 1)
+                .map(|(tables, _)| tables.iter().map(|table| table.gen()).collect::<Vec<_>>())
+        };
+
+        // 删除标记占比达到阈值时优先选取，且即使Table数量未达到阈值也需要压缩
+        let version = ver_status.current().await;
+        assert!(version.is_compaction_needed(&config, 1));
+        assert_eq!(picked_gen(&version, &config), Some(vec![2]));
+
+        // 重叠比例最小的[e, f]
+        let config = config.tombstone_compaction_percent(0);
+        assert!(!version.is_compaction_needed(&config, 1));
+        assert_eq!(picked_gen(&version, &config), Some(vec![3]));
+
+        // 以压缩指针进行轮询
+        let config = config.compaction_pri(CompactionPri::RoundRobin);
+        assert_eq!(picked_gen(&version, &config), Some(vec![1]));
+        drop(version);
+
+        ver_status
+            .log_and_apply(
+                vec![VersionEdit::CompactPoint(1, Bytes::from_static(b"b"))],
+                10,
+            )
+            .await?;
+        assert_eq!(
+            picked_gen(ver_status.current().await.as_ref(), &config),
+            Some(vec![2])
+        );
+
+        ver_status
+            .log_and_apply(
+                vec![VersionEdit::CompactPoint(1, Bytes::from_static(b"f"))],
+                10,
+            )
+            .await?;
+        assert_eq!(
+            picked_gen(ver_status.current().await.as_ref(), &config),
+            Some(vec![1])
+        );
+
+        // 压缩指针需要能够被持久化并恢复
+        let compact_points = ver_status.current().await.compact_points.clone();
+        drop(ver_status);
+
+        let ver_status = VersionStatus::load_with_path(config, wal)?;
+        assert_eq!(ver_status.current().await.compact_points, compact_points);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_version_query_tombstone() -> Result<()> {
+    let temp_dir = TempDir::new().expect("unable to create temporary working directory");
+
+    tokio_test::block_on(async move {
+        let config = Config::new(temp_dir.into_path());
+
+        let (wal, _, _) = LogLoader::reload(
+            config.path(),
+            (DEFAULT_VERSION_PATH, Some(1)),
+            IoType::Direct,
+            |_| Ok(()),
+        )?;
+        let ver_status = VersionStatus::load_with_path(config.clone(), wal.clone())?;
+        let loader = ver_status.loader();
+        let old_value = Some(Bytes::from_static(b"old"));
+
+        // Level 0: [j(删除标记)]
+        let (scope_1, meta_1) = loader
+            .create(
+                1,
+                vec![(Bytes::from_static(b"j"), None)],
+                0,
+                TableType::SortedString,
+            )
+            .await?;
+        // Level 1: [k(删除标记)]
+        let (scope_2, meta_2) = loader
+            .create(
+                2,
+                vec![(Bytes::from_static(b"k"), None)],
+                1,
+                TableType::SortedString,
+            )
+            .await?;
+        // Level 2: [j, k, l]
+        let (scope_3, meta_3) = loader
+            .create(
+                3,
+                vec![
+                    (Bytes::from_static(b"j"), old_value.clone()),
+                    (Bytes::from_static(b"k"), old_value.clone()),
+                    (Bytes::from_static(b"l"), old_value.clone()),
+                ],
+                2,
+                TableType::SortedString,
+            )
+            .await?;
+
+        ver_status
+            .log_and_apply(
+                vec![
+                    VersionEdit::NewFile((vec![scope_1], 0), 0, meta_1),
+                    VersionEdit::NewFile((vec![scope_2], 1), 0, meta_2),
+                    VersionEdit::NewFile((vec![scope_3], 2), 0, meta_3),
+                ],
+                10,
+            )
+            .await?;
+
+        // 命中删除标记时不再查询更底层的旧数据
+        let version = ver_status.current().await;
+        assert_eq!(version.query(b"j")?, None);
+        assert_eq!(version.query(b"k")?, None);
+        assert_eq!(version.query(b"l")?, old_value);
+
+        Ok(())
+    })
+}
@@ -0,0 +1,45 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// 缓存的命中、插入与驱逐计数
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheCounter {
+    pub hits: u64,
+    pub misses: u64,
+    /// 实际放入缓存的次数
+    pub inserts: u64,
+    /// 因容量不足而被驱逐的次数，不包含主动移除
+    pub evictions: u64,
+}
+
+/// 可并发记录的CacheCounter
+#[derive(Debug, Default)]
+pub(crate) struct CacheRecorder {
+    hits: AtomicU64,
+    misses: AtomicU64,
+    inserts: AtomicU64,
+    evictions: AtomicU64,
+}
+
+impl CacheRecorder {
+    pub(crate) fn record_access(&self, is_hit: bool) {
+        let counter = if is_hit { &self.hits } else { &self.misses };
+        let _ = counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_insert(&self) {
+        let _ = self.inserts.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_evictions(&self, evictions: u64) {
+        let _ = self.evictions.fetch_add(evictions, Ordering::Relaxed);
+    }
+
+    pub(crate) fn counter(&self) -> CacheCounter {
+        CacheCounter {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            inserts: self.inserts.load(Ordering::Relaxed),
+            evictions: self.evictions.load(Ordering::Relaxed),
+        }
+    }
+}
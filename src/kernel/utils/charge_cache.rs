@@ -0,0 +1,723 @@
+use crate::error::CacheError;
+use crate::kernel::utils::cache_counter::CacheRecorder;
+use crate::kernel::utils::lru_cache::Result;
+use parking_lot::Mutex;
+use std::collections::hash_map::{DefaultHasher, RandomState};
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{BuildHasher, Hash, Hasher};
+use std::sync::Arc;
+
+/// 缓存的驱逐策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CachePolicy {
+    /// 最近最少使用，驱逐最久未使用的数据
+    Lru,
+    /// W-TinyLFU，具有抗扫描能力
+    ///
+    /// 新数据先进入占容量1%的窗口LRU中，被窗口驱逐时通过频率草图(Count-Min Sketch)
+    /// 与主区域(SLRU)中将被驱逐的数据比较访问频率，仅频率更高时才准入，
+    /// 因此一次性的全量扫描不会将热点数据从缓存中挤出
+    TinyLfu,
+}
+
+/// 缓存数据变化的监听器
+///
+/// Tips: 在分片锁内调用，因此应当足够轻量
+pub(crate) trait ChargeListener<K, V>: Send + Sync {
+    /// 数据实际放入缓存时调用
+    fn on_insert(&self, key: &K, value: &V);
+
+    /// 数据因容量不足被驱逐(包括未通过TinyLfu准入)时调用
+    fn on_evict(&self, key: &K, value: &V);
+}
+
+/// 直接以CacheRecorder统计插入与驱逐次数
+impl<K, V> ChargeListener<K, V> for CacheRecorder {
+    fn on_insert(&self, _: &K, _: &V) {
+        self.record_insert();
+    }
+
+    fn on_evict(&self, _: &K, _: &V) {
+        self.record_evictions(1);
+    }
+}
+
+/// 以权重(通常为字节数)为容量单位的分片缓存
+///
+/// 每个数据的权重由charger计算，缓存保证所有数据的权重之和不超过容量，
+/// 超出时依照CachePolicy驱逐数据
+/// - 与`ShardingLruCache`不同，读取时返回数据的克隆，因此被驱逐的数据可以安全地释放，
+///   大块数据应使用Arc等廉价克隆的类型进行包装
+/// - 单个数据的权重超出分片容量时不会被缓存，以保证权重之和严格不超过容量
+pub(crate) struct ShardingChargeCache<K, V, S = RandomState> {
+    sharding_vec: Vec<Mutex<ChargeShard<K, V>>>,
+    hasher: S,
+    charger: fn(&V) -> usize,
+}
+
+impl<K: Hash + Eq + Clone, V: Clone, S: BuildHasher> ShardingChargeCache<K, V, S> {
+    pub(crate) fn new(
+        cap: usize,
+        sharding_size: usize,
+        hasher: S,
+        charger: fn(&V) -> usize,
+        policy: CachePolicy,
+    ) -> Result<Self> {
+        if cap < 1 || sharding_size < 1 {
+            return Err(CacheError::CacheSizeOverFlow);
+        }
+        // 以字节为单位时容量通常无法被分片数整除，因此向上取整
+        let sharding_cap = cap.div_ceil(sharding_size);
+        let sharding_vec = (0..sharding_size)
+            .map(|_| Mutex::new(ChargeShard::new(sharding_cap, policy)))
+            .collect();
+
+        Ok(ShardingChargeCache {
+            sharding_vec,
+            hasher,
+            charger,
+        })
+    }
+
+    /// 设置数据变化的监听器，可用于统计
+    pub(crate) fn listener(mut self, listener: Arc<dyn ChargeListener<K, V>>) -> Self {
+        for shard in self.sharding_vec.iter_mut() {
+            shard.get_mut().listener = Some(Arc::clone(&listener));
+        }
+        self
+    }
+
+    pub(crate) fn get(&self, key: &K) -> Option<V> {
+        self.shard(key).lock().get(key)
+    }
+
+    pub(crate) fn put(&self, key: K, value: V) -> Option<V> {
+        let charge = (self.charger)(&value);
+        let mut shard = self.shard(&key).lock();
+
+        shard.record(&key);
+        shard.put(key, value, charge)
+    }
+
+    pub(crate) fn remove(&self, key: &K) -> Option<V> {
+        self.shard(key).lock().remove(key)
+    }
+
+    /// 固定数据，使其在unpin前不会被驱逐
+    ///
+    /// 固定的数据同样计入容量，并会驱逐未固定的数据以腾出空间，
+    /// 因此固定数据的权重之和可能超出容量，此时缓存中仅剩固定的数据
+    pub(crate) fn pin(&self, key: K, value: V) {
+        let charge = (self.charger)(&value);
+
+        self.shard(&key).lock().pin(key, value, charge)
+    }
+
+    /// 解除数据的固定并将其移除，数据未被固定时不做处理
+    pub(crate) fn unpin(&self, key: &K) -> Option<V> {
+        self.shard(key).lock().unpin(key)
+    }
+
+    /// 获取数据，不存在时通过fn_once生成并插入
+    ///
+    /// Tips: fn_once在分片锁外执行，避免加载(如磁盘IO)阻塞同一分片的其他读取；
+    /// 并发加载同一数据时可能重复执行fn_once，插入前会再次检查，以先插入的数据为准
+    pub(crate) fn get_or_insert<F>(&self, key: K, fn_once: F) -> Result<V>
+    where
+        F: FnOnce(&K) -> Result<V>,
+    {
+        // get中已记录此次访问，因此插入时无需再次记录
+        if let Some(value) = self.shard(&key).lock().get(&key) {
+            return Ok(value);
+        }
+        let value = fn_once(&key)?;
+        let charge = (self.charger)(&value);
+        let mut shard = self.shard(&key).lock();
+
+        if let Some(value) = shard.peek(&key) {
+            return Ok(value);
+        }
+        let _ = shard.put(key, value.clone(), charge);
+
+        Ok(value)
+    }
+
+    /// 当前所有数据的权重之和
+    pub(crate) fn usage(&self) -> usize {
+        self.sharding_vec
+            .iter()
+            .map(|shard| shard.lock().usage())
+            .sum()
+    }
+
+    pub(crate) fn capacity(&self) -> usize {
+        self.sharding_vec.iter().map(|shard| shard.lock().cap).sum()
+    }
+
+    #[allow(dead_code)]
+    pub(crate) fn len(&self) -> usize {
+        self.sharding_vec
+            .iter()
+            .map(|shard| shard.lock().inner.len())
+            .sum()
+    }
+
+    #[allow(dead_code)]
+    pub(crate) fn is_empty(&self) -> bool {
+        self.sharding_vec
+            .iter()
+            .all(|shard| shard.lock().inner.is_empty())
+    }
+
+    /// 通过key获取hash值后对其求余获取对应分片
+    fn shard(&self, key: &K) -> &Mutex<ChargeShard<K, V>> {
+        let mut hasher = self.hasher.build_hasher();
+        key.hash(&mut hasher);
+        &self.sharding_vec[hasher.finish() as usize % self.sharding_vec.len()]
+    }
+}
+
+/// 数据所处的区域
+///
+/// Lru策略下所有未固定的数据均处于Window中
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Segment {
+    Window = 0,
+    Probation = 1,
+    Protected = 2,
+    /// 被固定的数据，不参与驱逐
+    Pinned = 3,
+}
+
+struct Slot<V> {
+    value: V,
+    charge: usize,
+    /// 最近一次使用的时刻
+    tick: u64,
+    segment: Segment,
+}
+
+/// 区域中以使用时刻排序的Key，首位即为最久未使用的数据
+struct SegmentOrder<K> {
+    order: BTreeMap<u64, K>,
+    usage: usize,
+}
+
+impl<K> Default for SegmentOrder<K> {
+    fn default() -> Self {
+        SegmentOrder {
+            order: BTreeMap::new(),
+            usage: 0,
+        }
+    }
+}
+
+struct ChargeShard<K, V> {
+    inner: HashMap<K, Slot<V>>,
+    segments: [SegmentOrder<K>; 4],
+    /// 仅TinyLfu策略存在
+    sketch: Option<FrequencySketch>,
+    listener: Option<Arc<dyn ChargeListener<K, V>>>,
+    tick: u64,
+    cap: usize,
+    window_cap: usize,
+    protected_cap: usize,
+}
+
+impl<K: Hash + Eq + Clone, V: Clone> ChargeShard<K, V> {
+    fn new(cap: usize, policy: CachePolicy) -> Self {
+        let (window_cap, protected_cap, sketch) = match policy {
+            CachePolicy::Lru => (cap, 0, None),
+            CachePolicy::TinyLfu => {
+                let window_cap = cap / 100;
+
+                (
+                    window_cap,
+                    (cap - window_cap) * 4 / 5,
+                    Some(FrequencySketch::new(MIN_SKETCH_WIDTH)),
+                )
+            }
+        };
+
+        ChargeShard {
+            inner: HashMap::new(),
+            segments: Default::default(),
+            sketch,
+            listener: None,
+            tick: 0,
+            cap,
+            window_cap,
+            protected_cap,
+        }
+    }
+
+    fn usage(&self) -> usize {
+        self.segments.iter().map(|segment| segment.usage).sum()
+    }
+
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+
+    /// 在频率草图中记录一次访问
+    fn record(&mut self, key: &K) {
+        if let Some(sketch) = &mut self.sketch {
+            // 草图宽度跟随数据量增长，以降低哈希冲突导致的频率高估
+            // 扩容时保留缓存中已有数据的频率，避免热点数据因丢失频率而在准入比较中落败
+            if self.inner.len() > sketch.width() {
+                let mut new_sketch = FrequencySketch::new(self.inner.len());
+                for hash in self.inner.keys().map(hash_key) {
+                    new_sketch.raise(hash, sketch.frequency(hash));
+                }
+                *sketch = new_sketch;
+            }
+            sketch.increment(hash_key(key));
+        }
+    }
+
+    fn frequency(&self, key: &K) -> u8 {
+        self.sketch
+            .as_ref()
+            .map_or(0, |sketch| sketch.frequency(hash_key(key)))
+    }
+
+    fn get(&mut self, key: &K) -> Option<V> {
+        self.record(key);
+        let segment = self.inner.get(key)?.segment;
+
+        match segment {
+            // 试用区中的数据再次被访问时晋升至保护区
+            Segment::Probation => {
+                self.relink(key, Segment::Protected);
+                while self.segments[Segment::Protected as usize].usage > self.protected_cap {
+                    match self.segments[Segment::Protected as usize]
+                        .order
+                        .first_key_value()
+                    {
+                        Some((_, demote_key)) => {
+                            let demote_key = demote_key.clone();
+                            self.relink(&demote_key, Segment::Probation);
+                        }
+                        None => break,
+                    }
+                }
+            }
+            segment => self.relink(key, segment),
+        }
+
+        self.inner.get(key).map(|slot| slot.value.clone())
+    }
+
+    /// 获取数据但不视为一次使用
+    fn peek(&self, key: &K) -> Option<V> {
+        self.inner.get(key).map(|slot| slot.value.clone())
+    }
+
+    fn put(&mut self, key: K, value: V, charge: usize) -> Option<V> {
+        let old_value = self.remove(&key);
+        if charge > self.cap {
+            return old_value;
+        }
+
+        let tick = self.next_tick();
+        let window = &mut self.segments[Segment::Window as usize];
+        let _ = window.order.insert(tick, key.clone());
+        window.usage += charge;
+        if let Some(listener) = &self.listener {
+            listener.on_insert(&key, &value);
+        }
+        let _ = self.inner.insert(
+            key,
+            Slot {
+                value,
+                charge,
+                tick,
+                segment: Segment::Window,
+            },
+        );
+
+        while self.segments[Segment::Window as usize].usage > self.window_cap {
+            match self.segments[Segment::Window as usize]
+                .order
+                .first_key_value()
+            {
+                Some((_, candidate)) => {
+                    let candidate = candidate.clone();
+                    self.admit(&candidate);
+                }
+                None => break,
+            }
+        }
+        self.shrink();
+
+        old_value
+    }
+
+    fn pin(&mut self, key: K, value: V, charge: usize) {
+        let _ = self.remove(&key);
+
+        let tick = self.next_tick();
+        let pinned = &mut self.segments[Segment::Pinned as usize];
+        let _ = pinned.order.insert(tick, key.clone());
+        pinned.usage += charge;
+        if let Some(listener) = &self.listener {
+            listener.on_insert(&key, &value);
+        }
+        let _ = self.inner.insert(
+            key,
+            Slot {
+                value,
+                charge,
+                tick,
+                segment: Segment::Pinned,
+            },
+        );
+        self.shrink();
+    }
+
+    fn unpin(&mut self, key: &K) -> Option<V> {
+        match self.inner.get(key) {
+            Some(slot) if slot.segment == Segment::Pinned => self.remove(key),
+            _ => None,
+        }
+    }
+
+    /// 总权重超出容量(通常由固定数据占用导致)时，依次从试用区、窗口与保护区中驱逐最久未使用的数据
+    fn shrink(&mut self) {
+        while self.usage() > self.cap {
+            let victim = [Segment::Probation, Segment::Window, Segment::Protected]
+                .into_iter()
+                .find_map(|segment| self.segments[segment as usize].order.first_key_value())
+                .map(|(_, victim)| victim.clone());
+
+            match victim {
+                Some(victim) => self.evict(&victim),
+                None => break,
+            }
+        }
+    }
+
+    /// 将被窗口驱逐的数据尝试准入主区域，Lru策略下直接驱逐
+    ///
+    /// 主区域空间不足时与其中最久未使用的数据比较访问频率，候选数据频率更高时才替换之
+    fn admit(&mut self, candidate: &K) {
+        let charge = match self.inner.get(candidate) {
+            Some(slot) if self.sketch.is_some() => slot.charge,
+            _ => {
+                self.evict(candidate);
+                return;
+            }
+        };
+        let main_cap = self.cap - self.window_cap;
+        if charge > main_cap {
+            self.evict(candidate);
+            return;
+        }
+        let candidate_freq = self.frequency(candidate);
+
+        while self.segments[Segment::Probation as usize].usage
+            + self.segments[Segment::Protected as usize].usage
+            + charge
+            > main_cap
+        {
+            let victim = [Segment::Probation, Segment::Protected]
+                .into_iter()
+                .find_map(|segment| self.segments[segment as usize].order.first_key_value())
+                .map(|(_, victim)| victim.clone());
+
+            match victim {
+                Some(victim) if self.frequency(&victim) < candidate_freq => self.evict(&victim),
+                _ => {
+                    self.evict(candidate);
+                    return;
+                }
+            }
+        }
+        self.relink(candidate, Segment::Probation);
+    }
+
+    /// 将数据移动至segment中并更新为最近使用
+    fn relink(&mut self, key: &K, segment: Segment) {
+        let tick = self.next_tick();
+
+        if let Some(slot) = self.inner.get_mut(key) {
+            let old_segment = &mut self.segments[slot.segment as usize];
+            let _ = old_segment.order.remove(&slot.tick);
+            old_segment.usage -= slot.charge;
+
+            let new_segment = &mut self.segments[segment as usize];
+            let _ = new_segment.order.insert(tick, key.clone());
+            new_segment.usage += slot.charge;
+
+            slot.tick = tick;
+            slot.segment = segment;
+        }
+    }
+
+    /// 因容量不足而移除数据，并通知监听器
+    fn evict(&mut self, key: &K) {
+        if let Some(value) = self.remove(key) {
+            if let Some(listener) = &self.listener {
+                listener.on_evict(key, &value);
+            }
+        }
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        self.inner.remove(key).map(|slot| {
+            let segment = &mut self.segments[slot.segment as usize];
+            let _ = segment.order.remove(&slot.tick);
+            segment.usage -= slot.charge;
+            slot.value
+        })
+    }
+}
+
+fn hash_key<K: Hash>(key: &K) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    hasher.finish()
+}
+
+const MIN_SKETCH_WIDTH: usize = 16;
+
+const SKETCH_SEEDS: [u64; 4] = [
+    0xc3a5_c85c_97cb_3127,
+    0xb492_b66f_be98_f273,
+    0x9ae1_6a3b_2f90_404f,
+    0xcbf2_9ce4_8422_2325,
+];
+
+/// 计数上限，与4bit计数器一致，避免历史热点数据的频率过高而难以被淘汰
+const MAX_FREQUENCY: u8 = 15;
+
+/// 用于估算访问频率的Count-Min Sketch
+///
+/// 累计记录次数达到宽度的10倍时所有计数减半，使频率随时间衰减以适应热点的变化
+struct FrequencySketch {
+    table: Vec<u8>,
+    width_mask: usize,
+    additions: usize,
+    sample_size: usize,
+}
+
+impl FrequencySketch {
+    fn new(width: usize) -> Self {
+        let width = width.max(MIN_SKETCH_WIDTH).next_power_of_two();
+
+        FrequencySketch {
+            table: vec![0; width * SKETCH_SEEDS.len()],
+            width_mask: width - 1,
+            additions: 0,
+            sample_size: width * 10,
+        }
+    }
+
+    fn width(&self) -> usize {
+        self.width_mask + 1
+    }
+
+    fn index(&self, hash: u64, row: usize) -> usize {
+        let hash = hash.wrapping_mul(SKETCH_SEEDS[row]);
+
+        row * self.width() + ((hash ^ (hash >> 32)) as usize & self.width_mask)
+    }
+
+    fn increment(&mut self, hash: u64) {
+        let mut is_added = false;
+
+        for row in 0..SKETCH_SEEDS.len() {
+            let index = self.index(hash, row);
+            if self.table[index] < MAX_FREQUENCY {
+                self.table[index] += 1;
+                is_added = true;
+            }
+        }
+        if is_added {
+            self.additions += 1;
+            if self.additions >= self.sample_size {
+                self.reset();
+            }
+        }
+    }
+
+    /// 使hash对应的计数至少为frequency
+    fn raise(&mut self, hash: u64, frequency: u8) {
+        for row in 0..SKETCH_SEEDS.len() {
+            let index = self.index(hash, row);
+            self.table[index] = self.table[index].max(frequency);
+        }
+    }
+
+    fn frequency(&self, hash: u64) -> u8 {
+        (0..SKETCH_SEEDS.len())
+            .map(|row| self.table[self.index(hash, row)])
+            .min()
+            .unwrap_or(0)
+    }
+
+    fn reset(&mut self) {
+        for count in self.table.iter_mut() {
+            *count >>= 1;
+        }
+        self.additions /= 2;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::kernel::utils::charge_cache::{CachePolicy, ShardingChargeCache};
+    use crate::kernel::utils::lru_cache::Result;
+    use std::collections::hash_map::RandomState;
+
+    #[test]
+    fn test_charge_cache() -> Result<()> {
+        let cache: ShardingChargeCache<usize, Vec<u8>> =
+            ShardingChargeCache::new(100, 1, RandomState::default(), Vec::len, CachePolicy::Lru)?;
+
+        assert_eq!(cache.put(1, vec![0; 40]), None);
+        assert_eq!(cache.put(2, vec![0; 40]), None);
+        assert_eq!(cache.usage(), 80);
+        // 使用后1变为最近使用，因此驱逐2
+        assert!(cache.get(&1).is_some());
+        assert_eq!(cache.put(3, vec![0; 40]), None);
+        assert!(cache.get(&2).is_none());
+        assert!(cache.get(&1).is_some());
+        assert_eq!(cache.usage(), 80);
+
+        // 覆盖时替换原有的权重
+        assert_eq!(cache.put(1, vec![1; 10]), Some(vec![0; 40]));
+        assert_eq!(cache.usage(), 50);
+
+        // 超出容量的数据不会被缓存，且不影响其余数据
+        assert_eq!(cache.put(4, vec![0; 200]), None);
+        assert!(cache.get(&4).is_none());
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.usage(), 50);
+        assert_eq!(cache.get_or_insert(4, |_| Ok(vec![4; 200]))?, vec![4; 200]);
+        assert!(cache.get(&4).is_none());
+
+        assert_eq!(cache.get_or_insert(5, |_| Ok(vec![5; 20]))?, vec![5; 20]);
+        assert_eq!(cache.get_or_insert(5, |_| Ok(vec![0; 20]))?, vec![5; 20]);
+        assert_eq!(cache.remove(&5), Some(vec![5; 20]));
+        assert_eq!(cache.remove(&1), Some(vec![1; 10]));
+        assert_eq!(cache.remove(&3), Some(vec![0; 40]));
+        assert!(cache.is_empty());
+        assert_eq!(cache.usage(), 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_charge_cache_get_or_insert_unlocked() -> Result<()> {
+        let cache: ShardingChargeCache<usize, Vec<u8>> =
+            ShardingChargeCache::new(100, 1, RandomState::default(), Vec::len, CachePolicy::Lru)?;
+
+        // 加载时不持有分片锁，因此加载中可以读写同一分片
+        let value = cache.get_or_insert(1, |_| {
+            assert_eq!(cache.put(2, vec![2; 10]), None);
+            assert!(cache.get(&2).is_some());
+            // 模拟并发加载同一数据时先插入的一方
+            assert_eq!(cache.put(1, vec![1; 10]), None);
+            Ok(vec![0; 10])
+        })?;
+        assert_eq!(value, vec![1; 10]);
+        assert_eq!(cache.get(&1), Some(vec![1; 10]));
+        assert_eq!(cache.usage(), 20);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_charge_cache_pin() -> Result<()> {
+        for policy in [CachePolicy::Lru, CachePolicy::TinyLfu] {
+            let cache: ShardingChargeCache<usize, Vec<u8>> =
+                ShardingChargeCache::new(100, 1, RandomState::default(), Vec::len, policy)?;
+
+            let _ = cache.put(1, vec![0; 30]);
+            cache.pin(2, vec![0; 60]);
+            assert_eq!(cache.usage(), 90);
+
+            // 固定数据占用的容量会驱逐未固定的数据
+            cache.pin(3, vec![0; 30]);
+            assert!(cache.get(&1).is_none());
+            assert_eq!(cache.usage(), 90);
+
+            // 固定数据不会被后续写入驱逐
+            for i in 10..20 {
+                let _ = cache.put(i, vec![0; 10]);
+            }
+            assert!(cache.get(&2).is_some());
+            assert!(cache.get(&3).is_some());
+            assert!(cache.usage() <= 100);
+
+            // 未固定的数据无法被unpin
+            assert_eq!(cache.unpin(&19), None);
+            assert_eq!(cache.unpin(&2), Some(vec![0; 60]));
+            assert!(cache.get(&2).is_none());
+            assert_eq!(cache.unpin(&3), Some(vec![0; 30]));
+            assert!(cache.usage() <= 10);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_sharding_charge_cache() -> Result<()> {
+        let cache: ShardingChargeCache<usize, Vec<u8>> =
+            ShardingChargeCache::new(1000, 8, RandomState::default(), Vec::len, CachePolicy::Lru)?;
+
+        assert_eq!(cache.capacity(), 1000);
+        for i in 0..1000 {
+            let _ = cache.put(i, vec![0; 10]);
+            assert!(cache.usage() <= cache.capacity());
+        }
+        assert!(ShardingChargeCache::<usize, Vec<u8>>::new(
+            0,
+            8,
+            RandomState::default(),
+            Vec::len,
+            CachePolicy::Lru
+        )
+        .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_tiny_lfu_scan_resistant() -> Result<()> {
+        // 每轮访问所有热点数据，并穿插与缓存容量等量的一次性扫描数据
+        // 返回后半段轮次中热点数据的命中次数
+        fn hot_hits(policy: CachePolicy) -> Result<usize> {
+            let cache: ShardingChargeCache<usize, Vec<u8>> =
+                ShardingChargeCache::new(1000, 1, RandomState::default(), Vec::len, policy)?;
+            let mut scan_key = 1000;
+            let mut hits = 0;
+
+            for round in 0..20 {
+                for i in 0..50 {
+                    let mut is_hit = true;
+                    let _ = cache.get_or_insert(i, |_| {
+                        is_hit = false;
+                        Ok(vec![0; 10])
+                    })?;
+                    if round >= 10 && is_hit {
+                        hits += 1;
+                    }
+                    for _ in 0..2 {
+                        let _ = cache.get_or_insert(scan_key, |_| Ok(vec![0; 10]))?;
+                        scan_key += 1;
+                    }
+                    assert!(cache.usage() <= cache.capacity());
+                }
+            }
+            Ok(hits)
+        }
+
+        assert!(hot_hits(CachePolicy::TinyLfu)? > 450);
+        assert_eq!(hot_hits(CachePolicy::Lru)?, 0);
+
+        Ok(())
+    }
+}
@@ -99,6 +99,8 @@ pub(crate) struct LruCache<K, V> {
     tail: Option<NodeReadPtr<K, V>>,
     inner: HashMap<KeyRef<K, V>, NodeReadPtr<K, V>>,
     cap: usize,
+    /// 因容量不足而被驱逐的数量
+    evictions: u64,
     marker: PhantomData<Node<K, V>>,
 }
 
@@ -146,6 +148,33 @@ impl<K: Hash + Eq + PartialEq, V, S: BuildHasher> ShardingLruCache<K, V, S> {
         self.shard(key).lock().remove(key)
     }
 
+    pub(crate) fn len(&self) -> usize {
+        self.sharding_vec.iter().map(|lru| lru.lock().len()).sum()
+    }
+
+    pub(crate) fn evictions(&self) -> u64 {
+        self.sharding_vec
+            .iter()
+            .map(|lru| lru.lock().evictions)
+            .sum()
+    }
+
+    /// 对缓存中所有的数据以fn_sum求和，如统计数据的内存占用
+    pub(crate) fn sum_by<F>(&self, fn_sum: F) -> usize
+    where
+        F: Fn(&V) -> usize,
+    {
+        self.sharding_vec
+            .iter()
+            .map(|lru| {
+                lru.lock()
+                    .iter()
+                    .map(|(_, value)| fn_sum(value))
+                    .sum::<usize>()
+            })
+            .sum()
+    }
+
     #[allow(dead_code)]
     pub(crate) fn is_empty(&self) -> bool {
         for lru in &self.sharding_vec {
@@ -189,6 +218,7 @@ impl<K: Hash + Eq + PartialEq, V> LruCache<K, V> {
             tail: None,
             inner: HashMap::new(),
             cap,
+            evictions: 0,
             marker: PhantomData,
         })
     }
@@ -246,6 +276,7 @@ impl<K: Hash + Eq + PartialEq, V> LruCache<K, V> {
             if self.inner.len() >= self.cap {
                 self.detach(tail);
                 let _ignore = self.inner.remove(&KeyRef(tail));
+                self.evictions += 1;
             }
         }
     }
@@ -339,7 +370,6 @@ impl<K: Hash + Eq + PartialEq, V> LruCache<K, V> {
     pub(crate) fn is_empty(&self) -> bool {
         self.inner.is_empty()
     }
-    #[allow(dead_code)]
     pub(crate) fn iter(&self) -> LruCacheIter<K, V> {
         LruCacheIter {
             inner: self.inner.iter(),
@@ -1 +1,4 @@
+pub mod cache_counter;
+pub mod charge_cache;
 pub mod lru_cache;
+pub mod rate_limiter;
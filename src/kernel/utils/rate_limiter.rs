@@ -0,0 +1,204 @@
+use parking_lot::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+const NANOS_PER_SEC: u128 = 1_000_000_000;
+
+/// 自动调节时最低速率为设定速率的1/DEFAULT_AUTO_TUNED_DIVISOR
+pub(crate) const DEFAULT_AUTO_TUNED_DIVISOR: u64 = 4;
+
+/// 令牌桶限速器
+///
+/// 用于限制flush与压缩时的磁盘IO速率，避免压缩的突发IO影响前台读写的延迟
+/// - 令牌桶容量为一秒的速率，允许小幅度的突发IO
+/// - 单次请求超出剩余令牌时会使令牌数为负，并异步等待直至令牌补充归零，不会阻塞执行器线程
+/// - 开启自动调节时会根据压缩债务在[bytes_per_sec / 4, bytes_per_sec]之间调整速率，
+///   债务越高则速率越高，使压缩能够尽快追上写入
+#[derive(Debug)]
+pub struct RateLimiter {
+    /// 设定的速率上限
+    max_bytes_per_sec: u64,
+    /// 当前生效的速率
+    bytes_per_sec: AtomicU64,
+    auto_tuned: bool,
+    bucket: Mutex<Bucket>,
+
+    total_bytes_through: AtomicU64,
+    total_requests: AtomicU64,
+    total_throttled_requests: AtomicU64,
+    total_wait_nanos: AtomicU64,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    available: i128,
+    last_refill: Instant,
+}
+
+/// 限速器运行统计
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimiterStats {
+    /// 设定的速率上限
+    pub max_bytes_per_sec: u64,
+    /// 当前生效的速率
+    pub bytes_per_sec: u64,
+    pub auto_tuned: bool,
+    /// 累计通过的字节数
+    pub total_bytes_through: u64,
+    /// 累计请求次数
+    pub total_requests: u64,
+    /// 累计被限速而等待的请求次数
+    pub total_throttled_requests: u64,
+    /// 累计等待时间
+    pub total_wait_time: Duration,
+}
+
+impl RateLimiter {
+    #[inline]
+    pub fn new(bytes_per_sec: u64, auto_tuned: bool) -> Self {
+        let bytes_per_sec = bytes_per_sec.max(1);
+
+        RateLimiter {
+            max_bytes_per_sec: bytes_per_sec,
+            bytes_per_sec: AtomicU64::new(bytes_per_sec),
+            auto_tuned,
+            bucket: Mutex::new(Bucket {
+                available: i128::from(bytes_per_sec),
+                last_refill: Instant::now(),
+            }),
+            total_bytes_through: AtomicU64::new(0),
+            total_requests: AtomicU64::new(0),
+            total_throttled_requests: AtomicU64::new(0),
+            total_wait_nanos: AtomicU64::new(0),
+        }
+    }
+
+    /// 请求指定字节数的令牌，令牌不足时异步等待直至令牌补充
+    #[inline]
+    pub async fn request(&self, bytes: u64) {
+        let bytes_per_sec = self.bytes_per_sec.load(Ordering::Relaxed);
+        let wait_nanos = {
+            let mut bucket = self.bucket.lock();
+            bucket.refill(bytes_per_sec);
+            bucket.available -= i128::from(bytes);
+
+            if bucket.available < 0 {
+                (bucket.available.unsigned_abs() * NANOS_PER_SEC / u128::from(bytes_per_sec)) as u64
+            } else {
+                0
+            }
+        };
+
+        let _ = self.total_bytes_through.fetch_add(bytes, Ordering::Relaxed);
+        let _ = self.total_requests.fetch_add(1, Ordering::Relaxed);
+
+        if wait_nanos > 0 {
+            let _ = self
+                .total_throttled_requests
+                .fetch_add(1, Ordering::Relaxed);
+            let _ = self
+                .total_wait_nanos
+                .fetch_add(wait_nanos, Ordering::Relaxed);
+            tokio::time::sleep(Duration::from_nanos(wait_nanos)).await;
+        }
+    }
+
+    /// 根据待压缩的数据量(压缩债务)调整速率
+    ///
+    /// 债务达到一秒的速率上限时以全速进行，无债务时则以最低速率进行
+    pub(crate) fn tune(&self, debt_bytes: u64) {
+        if !self.auto_tuned {
+            return;
+        }
+        let max = self.max_bytes_per_sec;
+        let min = (max / DEFAULT_AUTO_TUNED_DIVISOR).max(1);
+        // 以u128计算避免速率较大时乘积溢出，结果不超过max因此可安全转换回u64
+        let bytes_per_sec = u128::from(min)
+            + u128::from(max - min) * u128::from(debt_bytes.min(max)) / u128::from(max);
+
+        self.bytes_per_sec
+            .store(bytes_per_sec as u64, Ordering::Relaxed);
+    }
+
+    #[inline]
+    pub fn bytes_per_sec(&self) -> u64 {
+        self.bytes_per_sec.load(Ordering::Relaxed)
+    }
+
+    #[inline]
+    pub fn stats(&self) -> RateLimiterStats {
+        RateLimiterStats {
+            max_bytes_per_sec: self.max_bytes_per_sec,
+            bytes_per_sec: self.bytes_per_sec(),
+            auto_tuned: self.auto_tuned,
+            total_bytes_through: self.total_bytes_through.load(Ordering::Relaxed),
+            total_requests: self.total_requests.load(Ordering::Relaxed),
+            total_throttled_requests: self.total_throttled_requests.load(Ordering::Relaxed),
+            total_wait_time: Duration::from_nanos(self.total_wait_nanos.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+impl Bucket {
+    /// 按距离上次补充的时间补充令牌，令牌数不超过一秒的速率
+    fn refill(&mut self, bytes_per_sec: u64) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_refill).as_nanos();
+        let refill = elapsed * u128::from(bytes_per_sec) / NANOS_PER_SEC;
+
+        if refill > 0 {
+            self.available = (self.available + refill as i128).min(i128::from(bytes_per_sec));
+            self.last_refill = now;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::kernel::utils::rate_limiter::RateLimiter;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_rate_limiter_request() {
+        let limiter = RateLimiter::new(1024 * 1024, false);
+
+        let start = Instant::now();
+        tokio_test::block_on(async {
+            // 令牌桶初始为满，首次请求不会等待
+            limiter.request(1024 * 1024).await;
+            assert!(start.elapsed() < Duration::from_millis(100));
+
+            limiter.request(512 * 1024).await;
+            assert!(start.elapsed() >= Duration::from_millis(400));
+        });
+
+        let stats = limiter.stats();
+        assert_eq!(stats.total_bytes_through, 1536 * 1024);
+        assert_eq!(stats.total_requests, 2);
+        assert_eq!(stats.total_throttled_requests, 1);
+        assert!(stats.total_wait_time >= Duration::from_millis(400));
+    }
+
+    #[test]
+    fn test_rate_limiter_tune() {
+        let limiter = RateLimiter::new(1000, true);
+
+        limiter.tune(0);
+        assert_eq!(limiter.bytes_per_sec(), 250);
+        limiter.tune(500);
+        assert_eq!(limiter.bytes_per_sec(), 625);
+        limiter.tune(u64::MAX);
+        assert_eq!(limiter.bytes_per_sec(), 1000);
+
+        let limiter = RateLimiter::new(1000, false);
+        limiter.tune(0);
+        assert_eq!(limiter.bytes_per_sec(), 1000);
+
+        // 速率较大时计算不应溢出
+        let limiter = RateLimiter::new(u64::MAX, true);
+        limiter.tune(u64::MAX);
+        assert_eq!(limiter.bytes_per_sec(), u64::MAX);
+        limiter.tune(0);
+        assert_eq!(limiter.bytes_per_sec(), u64::MAX / 4);
+    }
+}
@@ -1,10 +1,6 @@
 #![feature(type_ascription)]
-#![feature(fs_try_exists)]
-#![feature(cursor_remaining)]
-#![feature(result_flattening)]
 #![feature(buf_read_has_data_left)]
 #![feature(slice_pattern)]
-#![feature(bound_map)]
 #![deny(
 // The following are allowed by default lints according to
 // https://doc.rust-lang.org/rustc/lints/listing/allowed-by-default.html
@@ -21,7 +17,6 @@ missing_copy_implementations,
 non_ascii_idents,
 // non_exhaustive_omitted_patterns, unstable
 noop_method_call,
-pointer_structural_match,
 rust_2021_incompatible_closure_captures,
 rust_2021_incompatible_or_patterns,
 rust_2021_prefixes_incompatible_syntax,
@@ -1,7 +1,9 @@
 use crate::error::ConnectionError;
+use crate::kernel::lsm::storage::CacheStats;
 use crate::kernel::{ByteUtils, CommandData};
 use crate::net::connection::Connection;
 use crate::net::{kv_encode_with_len, option_from_key_value, Result};
+use crate::proto::net_pb;
 use crate::proto::net_pb::{CommandOption, KeyValue};
 use crate::KernelError;
 use itertools::Itertools;
@@ -126,6 +128,26 @@ impl Client {
         self.value_option(5).await.map(|len| len as usize)
     }
 
+    /// 服务端的缓存运行统计
+    #[inline]
+    pub async fn cache_stats(&mut self) -> Result<CacheStats> {
+        let send_option = CommandOption {
+            r#type: 8,
+            bytes: vec![],
+            value: 0,
+        };
+
+        let result_option = self.send_cmd(send_option).await?;
+
+        if result_option.r#type == 8 {
+            Ok(net_pb::CacheStatsInfo::decode(&*result_option.bytes)
+                .map_err(|_| ConnectionError::DecodeErr)?
+                .into())
+        } else {
+            Err(ConnectionError::StoreErr(KernelError::NotMatchCmd))
+        }
+    }
+
     /// 数值控制选项通用流程
     async fn value_option(&mut self, type_num: i32) -> Result<u64> {
         let send_option = CommandOption {
@@ -1,6 +1,9 @@
 use crate::error::ConnectionError;
 
+use crate::kernel::lsm::storage::CacheStats;
+use crate::kernel::utils::cache_counter::CacheCounter;
 use crate::kernel::ByteUtils;
+use crate::proto::net_pb;
 use crate::proto::net_pb::{CommandOption, KeyValue};
 use crate::KernelError;
 use prost::Message;
@@ -48,3 +51,73 @@ fn kv_encode_with_len(key_value: &KeyValue) -> Result<Vec<u8>> {
         Err(ConnectionError::StoreErr(KernelError::DataEmpty))
     }
 }
+
+impl From<CacheCounter> for net_pb::CacheCounter {
+    #[inline]
+    fn from(counter: CacheCounter) -> Self {
+        net_pb::CacheCounter {
+            hits: counter.hits,
+            misses: counter.misses,
+            inserts: counter.inserts,
+            evictions: counter.evictions,
+        }
+    }
+}
+
+impl From<net_pb::CacheCounter> for CacheCounter {
+    #[inline]
+    fn from(counter: net_pb::CacheCounter) -> Self {
+        CacheCounter {
+            hits: counter.hits,
+            misses: counter.misses,
+            inserts: counter.inserts,
+            evictions: counter.evictions,
+        }
+    }
+}
+
+impl From<CacheStats> for net_pb::CacheStatsInfo {
+    #[inline]
+    fn from(stats: CacheStats) -> Self {
+        net_pb::CacheStatsInfo {
+            table_cache: Some(stats.table_cache.into()),
+            table_cache_usage: stats.table_cache_usage,
+            table_cache_capacity: stats.table_cache_capacity,
+            index_block: Some(stats.index_block.into()),
+            data_block: Some(stats.data_block.into()),
+            block_cache_usage: stats.block_cache_usage,
+            block_cache_capacity: stats.block_cache_capacity,
+            index_block_pinned_usage: stats.index_block_pinned_usage,
+            filter_usage: stats.filter_usage,
+            row_cache: Some(stats.row_cache.into()),
+            row_cache_usage: stats.row_cache_usage,
+            row_cache_capacity: stats.row_cache_capacity,
+        }
+    }
+}
+
+impl From<net_pb::CacheStatsInfo> for CacheStats {
+    #[inline]
+    fn from(stats: net_pb::CacheStatsInfo) -> Self {
+        CacheStats {
+            table_cache: stats
+                .table_cache
+                .map(CacheCounter::from)
+                .unwrap_or_default(),
+            table_cache_usage: stats.table_cache_usage,
+            table_cache_capacity: stats.table_cache_capacity,
+            index_block: stats
+                .index_block
+                .map(CacheCounter::from)
+                .unwrap_or_default(),
+            data_block: stats.data_block.map(CacheCounter::from).unwrap_or_default(),
+            block_cache_usage: stats.block_cache_usage,
+            block_cache_capacity: stats.block_cache_capacity,
+            index_block_pinned_usage: stats.index_block_pinned_usage,
+            filter_usage: stats.filter_usage,
+            row_cache: stats.row_cache.map(CacheCounter::from).unwrap_or_default(),
+            row_cache_usage: stats.row_cache_usage,
+            row_cache_capacity: stats.row_cache_capacity,
+        }
+    }
+}
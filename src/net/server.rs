@@ -1,8 +1,10 @@
+use crate::error::ConnectionError;
 use crate::kernel::lsm::storage::LsmStore;
 use crate::kernel::{options_none, ByteUtils, CommandData, Storage};
 use crate::net::connection::Connection;
 use crate::net::shutdown::Shutdown;
 use crate::net::{key_value_from_option, kv_encode_with_len, Result};
+use crate::proto::net_pb;
 use crate::proto::net_pb::{CommandOption, KeyValue};
 use bytes::Bytes;
 use chrono::Local;
@@ -224,6 +226,19 @@ impl Handler {
                 7 => {
                     break;
                 }
+                8 => {
+                    let mut bytes = vec![];
+                    net_pb::CacheStatsInfo::from(self.kv_store.cache_stats())
+                        .encode(&mut bytes)
+                        .map_err(|_| ConnectionError::EncodeErr)?;
+                    self.connection
+                        .write(CommandOption {
+                            r#type: 8,
+                            bytes,
+                            value: 0,
+                        })
+                        .await?;
+                }
                 _ => {}
             }
         }
@@ -1,3 +1,5 @@
+// 生成的消息类型由prost决定其派生，因此忽略此lint
+#[allow(missing_copy_implementations)]
 pub mod net_pb {
     include!(concat!(env!("OUT_DIR"), "/net_pb.rs"));
 }
@@ -264,6 +264,14 @@ fn io_type_test(factory: &IoFactory, io_type: IoType) -> Result<()> {
     assert_eq!(reader.seek(SeekFrom::End(-1))?, 5);
     assert_eq!(reader.seek(SeekFrom::Current(-1))?, 4);
 
+    let mut buf = [0; 3];
+    reader.read_at(&mut buf, 1)?;
+    assert_eq!([b'2', b'3', b'4'], buf);
+    // 仅Unix下read_at不影响读取器当前的位置，Windows下seek_read会移动文件的游标
+    #[cfg(unix)]
+    assert_eq!(reader.stream_position()?, 4);
+    assert!(reader.read_at(&mut buf, 4).is_err());
+
     assert_eq!(reader.file_size()?, 6);
     assert!(factory.exists(1)?);
     factory.clean(1)?;